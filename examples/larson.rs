@@ -0,0 +1,41 @@
+// larson.rs - Sweeps a red light back and forth on a Blinkt! board, similar to
+// the Knight Rider scanner. The position of the light is calculated with
+// sub-pixel precision, and its intensity is spread over the pixels it covers,
+// using Blinkt::draw_point_f(), so the light moves smoothly instead of jumping
+// from pixel to pixel.
+//
+// Interrupting the process by pressing Ctrl-C causes the application to exit
+// immediately without clearing the pixels. Check out the solid_signals.rs
+// example to learn how to properly handle incoming signals to prevent an
+// abnormal termination.
+
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
+
+use blinkt::{wave, Blinkt, FrameClock};
+
+const NUM_PIXELS: usize = 8;
+const WIDTH: f32 = 1.5;
+const SWEEP_SECS: f32 = 1.5;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut blinkt = Blinkt::new()?;
+    let mut clock = FrameClock::new();
+
+    blinkt.set_all_pixels_brightness(0.2);
+
+    loop {
+        clock.tick();
+
+        // Move back and forth between the centers of the first and last pixels.
+        let t = wave::triangle(clock.elapsed(), 0.5 / SWEEP_SECS, 0.0);
+        let position = 0.5 + t * (NUM_PIXELS - 1) as f32;
+
+        blinkt.clear();
+        blinkt.draw_point_f(position, 255, 0, 0, WIDTH);
+        blinkt.show()?;
+
+        thread::sleep(Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! CPU affinity for the thread that sends frames.
+//!
+//! In bitbanging mode, frames can get corrupted when the thread that sends them
+//! is preempted by other processes, which results in glitches when the system
+//! is under heavy load. Pinning that thread to a core that's isolated from the
+//! scheduler, with the `isolcpus` kernel parameter, prevents other processes
+//! from interrupting it.
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! #
+//! # use blinkt::{affinity, Blinkt};
+//! #
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! // Run the thread that calls show() on core 3, isolated through isolcpus=3.
+//! affinity::pin_current_thread(3)?;
+//!
+//! let mut blinkt = Blinkt::new()?;
+//! blinkt.set_all_pixels(255, 0, 0);
+//! blinkt.show()?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use std::io;
+use std::mem;
+
+use crate::Result;
+
+/// Restricts the current thread to run on the specified CPU core.
+///
+/// Cores are numbered starting at `0`. Call this from the thread that calls
+/// `show()`, before any frames are sent.
+pub fn pin_current_thread(core: usize) -> Result<()> {
+    set_current_thread_affinity(&[core])
+}
+
+/// Restricts the current thread to run on any of the specified CPU cores.
+///
+/// Cores are numbered starting at `0`.
+pub fn set_current_thread_affinity(cores: &[usize]) -> Result<()> {
+    // Safe because cpu_set_t is a plain bit mask, and all-zero is a valid value.
+    let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+
+    for &core in cores {
+        if core >= libc::CPU_SETSIZE as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid CPU core").into());
+        }
+
+        unsafe { libc::CPU_SET(core, &mut set) };
+    }
+
+    // A PID of 0 refers to the calling thread.
+    if unsafe { libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &set) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+
+    Ok(())
+}
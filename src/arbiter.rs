@@ -0,0 +1,158 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::{Duration, Instant};
+
+use crate::{Blinkt, Pixel};
+
+#[derive(Debug, Clone)]
+struct Submission {
+    source: String,
+    priority: u8,
+    expires: Option<Instant>,
+    sequence: u64,
+    frame: Vec<Pixel>,
+}
+
+/// Resolves which of several competing frames is shown.
+///
+/// Multiple sources, like a background effect, a notification system and a
+/// manual override, each submit their own frame with a priority and an
+/// optional time-to-live. The frame with the highest priority that hasn't
+/// expired yet wins. When several frames share the highest priority, the most
+/// recently submitted frame wins.
+///
+/// To share an `Arbiter` between threads, wrap it in an `Arc<Mutex<Arbiter>>`.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// # use std::time::Duration;
+/// #
+/// # use blinkt::{Arbiter, Blinkt, Pixel};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #     let mut blinkt = Blinkt::new()?;
+/// let mut arbiter = Arbiter::new();
+///
+/// let mut alert = Pixel::default();
+/// alert.set_rgb(255, 0, 0);
+///
+/// arbiter.submit("background", 0, None, &[Pixel::default(); 8]);
+/// arbiter.submit("alert", 10, Some(Duration::from_secs(5)), &[alert; 8]);
+///
+/// // Shows the alert for the next 5 seconds, and the background afterwards.
+/// arbiter.apply(&mut blinkt);
+/// blinkt.show()?;
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Arbiter {
+    submissions: Vec<Submission>,
+    sequence: u64,
+}
+
+impl Arbiter {
+    /// Constructs a new, empty `Arbiter`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Submits a frame on behalf of `source`, replacing any frame previously
+    /// submitted by the same source.
+    ///
+    /// Frames with a higher `priority` take precedence. When `ttl` is set, the
+    /// frame is automatically withdrawn once the specified time has passed.
+    pub fn submit(&mut self, source: &str, priority: u8, ttl: Option<Duration>, frame: &[Pixel]) {
+        self.withdraw(source);
+        self.sequence += 1;
+
+        self.submissions.push(Submission {
+            source: source.to_owned(),
+            priority,
+            expires: ttl.map(|ttl| Instant::now() + ttl),
+            sequence: self.sequence,
+            frame: frame.to_vec(),
+        });
+    }
+
+    /// Withdraws the frame submitted by `source`.
+    ///
+    /// Returns `true` if `source` had a frame submitted.
+    pub fn withdraw(&mut self, source: &str) -> bool {
+        let len = self.submissions.len();
+        self.submissions
+            .retain(|submission| submission.source != source);
+
+        self.submissions.len() != len
+    }
+
+    /// Withdraws all submitted frames.
+    pub fn clear(&mut self) {
+        self.submissions.clear();
+    }
+
+    /// Returns the source of the frame that's currently selected to be shown.
+    pub fn active_source(&mut self) -> Option<&str> {
+        self.active().map(|submission| submission.source.as_str())
+    }
+
+    /// Returns the frame that's currently selected to be shown.
+    ///
+    /// Any expired frames are withdrawn before a frame is selected.
+    pub fn resolve(&mut self) -> Option<&[Pixel]> {
+        self.active().map(|submission| submission.frame.as_slice())
+    }
+
+    /// Copies the frame that's currently selected to be shown to the local
+    /// buffer of `blinkt`.
+    ///
+    /// If the frame contains fewer pixels than `blinkt`, the remaining pixels are
+    /// left unchanged. If no frames are available, all pixels are cleared.
+    /// Returns `true` if a frame was copied.
+    pub fn apply(&mut self, blinkt: &mut Blinkt) -> bool {
+        match self.resolve() {
+            Some(frame) => {
+                for (pixel, value) in blinkt.pixels.iter_mut().zip(frame) {
+                    *pixel = *value;
+                }
+
+                blinkt.notify_change();
+
+                true
+            }
+            None => {
+                blinkt.clear();
+
+                false
+            }
+        }
+    }
+
+    fn active(&mut self) -> Option<&Submission> {
+        let now = Instant::now();
+        self.submissions
+            .retain(|submission| submission.expires.map_or(true, |expires| expires > now));
+
+        self.submissions
+            .iter()
+            .max_by_key(|submission| (submission.priority, submission.sequence))
+    }
+}
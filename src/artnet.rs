@@ -0,0 +1,212 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! An Art-Net receiver, to control the pixels from QLC+, MadMapper, Resolume
+//! and other lighting consoles that send DMX over UDP.
+//!
+//! [`Server`] receives ArtDmx packets, and maps the channels of consecutive
+//! universes onto the local buffer, starting at the universe selected with
+//! [`Server::set_start_universe()`]. Every pixel uses three channels for its
+//! red, green and blue values, so a universe of 512 channels controls 170
+//! pixels. Universe numbers combine the net, sub-net and universe of the
+//! Art-Net port address (`net << 8 | sub-net << 4 | universe`).
+//!
+//! `show()` is called after every ArtDmx packet. Once an ArtSync packet has
+//! been received, the server switches to synchronous mode, and only calls
+//! `show()` after every ArtSync packet, so all universes are updated at the
+//! same time.
+//!
+//! Art-Net doesn't support authentication, and ArtDmx packets are accepted from
+//! any host without a rate limit. Only bind to an address on a trusted lighting
+//! network, or restrict access with a firewall.
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! #
+//! # use blinkt::Blinkt;
+//! use blinkt::artnet::{Server, PORT};
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let mut blinkt = Blinkt::with_settings(23, 24, 300)?;
+//! let mut server = Server::bind(("0.0.0.0", PORT))?;
+//!
+//! loop {
+//!     server.receive(&mut blinkt)?;
+//! }
+//! # }
+//! ```
+//!
+//! [`Server`]: struct.Server.html
+//! [`Server::set_start_universe()`]: struct.Server.html#method.set_start_universe
+
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use crate::{dmx, Blinkt, Result};
+
+/// The UDP port used by Art-Net.
+pub const PORT: u16 = 6454;
+
+/// The number of pixels controlled by a single universe.
+pub const PIXELS_PER_UNIVERSE: usize = dmx::PIXELS_PER_UNIVERSE;
+
+const HEADER_ID: &[u8; 8] = b"Art-Net\0";
+const OP_DMX: u16 = 0x5000;
+const OP_SYNC: u16 = 0x5200;
+// ArtDmx header: ID, opcode, protocol version, sequence, physical, universe
+// and length.
+const DMX_HEADER_LEN: usize = 18;
+const MAX_PACKET_SIZE: usize = DMX_HEADER_LEN + 512;
+
+/// A packet received by the Art-Net [`Server`].
+///
+/// [`Server`]: struct.Server.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    /// DMX channel values for a universe.
+    Dmx {
+        /// The 15-bit port address.
+        universe: u16,
+        /// The sequence number, or `0` if sequencing is disabled.
+        sequence: u8,
+        /// The channel values, starting at channel 1.
+        data: Vec<u8>,
+    },
+    /// A request to show all previously received DMX data.
+    Sync,
+}
+
+/// Decodes an Art-Net packet. Returns `None` for unsupported or invalid packets.
+///
+/// ```rust
+/// use blinkt::artnet::{decode, Packet};
+///
+/// let mut packet = b"Art-Net\0".to_vec();
+/// // OpDmx, protocol version 14, sequence 1, physical port 0, universe 2 and
+/// // 3 channels.
+/// packet.extend_from_slice(&[0x00, 0x50, 0, 14, 1, 0, 2, 0, 0, 3]);
+/// packet.extend_from_slice(&[255, 128, 0]);
+///
+/// assert_eq!(
+///     decode(&packet),
+///     Some(Packet::Dmx {
+///         universe: 2,
+///         sequence: 1,
+///         data: vec![255, 128, 0],
+///     })
+/// );
+///
+/// // Packets that are shorter than their header or length are rejected.
+/// assert_eq!(decode(&packet[..12]), None);
+/// assert_eq!(decode(&packet[..packet.len() - 1]), None);
+/// ```
+pub fn decode(packet: &[u8]) -> Option<Packet> {
+    if packet.get(..8)? != HEADER_ID {
+        return None;
+    }
+
+    match u16::from_le_bytes([*packet.get(8)?, *packet.get(9)?]) {
+        OP_DMX => {
+            let header = packet.get(..DMX_HEADER_LEN)?;
+            let universe = u16::from_le_bytes([header[14], header[15]]) & 0x7fff;
+            let len = usize::from(u16::from_be_bytes([header[16], header[17]]));
+
+            Some(Packet::Dmx {
+                universe,
+                sequence: header[12],
+                data: packet.get(DMX_HEADER_LEN..DMX_HEADER_LEN + len)?.to_vec(),
+            })
+        }
+        OP_SYNC => Some(Packet::Sync),
+        _ => None,
+    }
+}
+
+/// An Art-Net server that receives DMX data over UDP.
+#[derive(Debug)]
+pub struct Server {
+    socket: UdpSocket,
+    start_universe: u16,
+    synchronous: bool,
+}
+
+impl Server {
+    /// Constructs a new `Server` listening on `address`.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(address)?,
+            start_universe: 0,
+            synchronous: false,
+        })
+    }
+
+    /// Returns the local address the server is listening on.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// When enabled, `receive()` returns an error of kind `WouldBlock` instead of
+    /// waiting for the next packet.
+    ///
+    /// By default, this is set to `false`.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> Result<()> {
+        Ok(self.socket.set_nonblocking(nonblocking)?)
+    }
+
+    /// Returns the universe mapped onto the first pixel.
+    pub fn start_universe(&self) -> u16 {
+        self.start_universe
+    }
+
+    /// Sets the universe mapped onto the first pixel. Every following universe
+    /// controls the next 170 pixels.
+    ///
+    /// By default, this is set to `0`.
+    pub fn set_start_universe(&mut self, universe: u16) {
+        self.start_universe = universe & 0x7fff;
+    }
+
+    /// Returns `true` if an ArtSync packet has been received, and `show()` is
+    /// only called after every ArtSync packet.
+    pub fn is_synchronous(&self) -> bool {
+        self.synchronous
+    }
+
+    /// Waits for the next packet, applies its DMX data to the local buffer of
+    /// `blinkt`, and calls `show()` when the pixels should be updated. Returns
+    /// `true` if `show()` was called.
+    pub fn receive(&mut self, blinkt: &mut Blinkt) -> Result<bool> {
+        let mut buffer = [0u8; MAX_PACKET_SIZE];
+        let (len, _) = self.socket.recv_from(&mut buffer)?;
+
+        match decode(&buffer[..len]) {
+            Some(Packet::Dmx { universe, data, .. }) => {
+                if !dmx::apply(blinkt, self.start_universe, universe, &data) || self.synchronous {
+                    return Ok(false);
+                }
+            }
+            Some(Packet::Sync) => self.synchronous = true,
+            None => return Ok(false),
+        }
+
+        blinkt.show()?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,324 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::path::Path;
+
+use crate::{
+    affinity, spi, Blinkt, BlinktSpi, ColorOrder, Error, Profile, Result, TimingStrategy, CLK, DAT,
+};
+
+// Highest BCM GPIO pin number available on the GPIO header.
+const MAX_HEADER_PIN: u8 = 27;
+// Highest SPI clock speed supported by the Raspberry Pi.
+const MAX_SPI_CLOCK_SPEED: u32 = 125_000_000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Interface {
+    Bitbang(u8, u8),
+    Spi(spi::Bus, spi::SlaveSelect, u32),
+    Auto(u8, u8),
+}
+
+/// Builds a [`Blinkt`] with custom settings.
+///
+/// `BlinktBuilder` is returned by [`Blinkt::builder()`]. All settings are
+/// optional. By default, the builder is set up for a Pimoroni Blinkt!, with 8
+/// pixels in bitbanging mode on data pin GPIO 23 (physical pin 16) and clock
+/// pin GPIO 24 (physical pin 18).
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// #
+/// # use blinkt::{spi, Blinkt};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::builder()
+///     .spi(spi::Bus::Spi0, 8_000_000)
+///     .pixels(144)
+///     .brightness(0.1)
+///     .clear_on_drop(false)
+///     .build()?;
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`Blinkt`]: struct.Blinkt.html
+/// [`Blinkt::builder()`]: struct.Blinkt.html#method.builder
+#[derive(Debug, Clone)]
+pub struct BlinktBuilder {
+    interface: Interface,
+    num_pixels: usize,
+    clear_on_drop: bool,
+    brightness: f32,
+    gamma: f32,
+    color_order: ColorOrder,
+    timing_strategy: TimingStrategy,
+    end_frame_len: Option<usize>,
+    cpu_affinity: Vec<usize>,
+}
+
+impl BlinktBuilder {
+    /// Constructs a new `BlinktBuilder` with the default settings.
+    pub fn new() -> Self {
+        Self {
+            interface: Interface::Bitbang(DAT, CLK),
+            num_pixels: 8,
+            clear_on_drop: true,
+            brightness: 1.0,
+            gamma: 1.0,
+            color_order: ColorOrder::default(),
+            timing_strategy: TimingStrategy::default(),
+            end_frame_len: None,
+            cpu_affinity: Vec::new(),
+        }
+    }
+
+    /// Sets the number of pixels.
+    pub fn pixels(mut self, num_pixels: usize) -> Self {
+        self.num_pixels = num_pixels;
+        self
+    }
+
+    /// Uses bitbanging mode on the specified data pin and clock pin. Pins should
+    /// be specified by their BCM GPIO pin numbers.
+    pub fn bitbang(mut self, pin_data: u8, pin_clock: u8) -> Self {
+        self.interface = Interface::Bitbang(pin_data, pin_clock);
+        self
+    }
+
+    /// Uses hardware SPI on the specified bus, with slave select `Ss0`, and a
+    /// clock speed of `clock_speed_hz`.
+    ///
+    /// See [`Blinkt::with_spi()`] for details on supported clock speeds.
+    ///
+    /// [`Blinkt::with_spi()`]: struct.Blinkt.html#method.with_spi
+    pub fn spi(mut self, bus: spi::Bus, clock_speed_hz: u32) -> Self {
+        self.interface = Interface::Spi(bus, spi::SlaveSelect::Ss0, clock_speed_hz);
+        self
+    }
+
+    /// Uses hardware SPI if possible, or bitbanging mode on the specified data
+    /// pin and clock pin otherwise.
+    ///
+    /// See [`Blinkt::auto_backend()`] for details.
+    ///
+    /// [`Blinkt::auto_backend()`]: struct.Blinkt.html#method.auto_backend
+    pub fn auto(mut self, pin_data: u8, pin_clock: u8) -> Self {
+        self.interface = Interface::Auto(pin_data, pin_clock);
+        self
+    }
+
+    /// Uses bitbanging mode with the pins and number of pixels specified by
+    /// `profile`.
+    pub fn profile(mut self, profile: &Profile) -> Self {
+        self.interface = Interface::Bitbang(profile.pin_data(), profile.pin_clock());
+        self.num_pixels = profile.num_pixels();
+        self
+    }
+
+    /// Sets `clear_on_drop`. See [`Blinkt::set_clear_on_drop()`] for details.
+    ///
+    /// By default, this is set to `true`.
+    ///
+    /// [`Blinkt::set_clear_on_drop()`]: struct.Blinkt.html#method.set_clear_on_drop
+    pub fn clear_on_drop(mut self, clear_on_drop: bool) -> Self {
+        self.clear_on_drop = clear_on_drop;
+        self
+    }
+
+    /// Sets the global brightness. See [`Blinkt::set_global_brightness()`] for
+    /// details.
+    ///
+    /// By default, this is set to `1.0`.
+    ///
+    /// [`Blinkt::set_global_brightness()`]: struct.Blinkt.html#method.set_global_brightness
+    pub fn brightness(mut self, brightness: f32) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    /// Sets the gamma value. See [`Blinkt::set_gamma()`] for details.
+    ///
+    /// By default, this is set to `1.0`.
+    ///
+    /// [`Blinkt::set_gamma()`]: struct.Blinkt.html#method.set_gamma
+    pub fn gamma(mut self, gamma: f32) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Sets the order in which the red, green and blue values are sent. See
+    /// [`Blinkt::set_color_order()`] for details.
+    ///
+    /// By default, this is set to `ColorOrder::Bgr`.
+    ///
+    /// [`Blinkt::set_color_order()`]: struct.Blinkt.html#method.set_color_order
+    pub fn color_order(mut self, color_order: ColorOrder) -> Self {
+        self.color_order = color_order;
+        self
+    }
+
+    /// Sets the timing strategy used in bitbanging mode. See
+    /// [`Blinkt::set_timing_strategy()`] for details.
+    ///
+    /// By default, this is set to `TimingStrategy::Sleep`.
+    ///
+    /// [`Blinkt::set_timing_strategy()`]: struct.Blinkt.html#method.set_timing_strategy
+    pub fn timing_strategy(mut self, timing_strategy: TimingStrategy) -> Self {
+        self.timing_strategy = timing_strategy;
+        self
+    }
+
+    /// Overrides the length in bytes of the end frame. See
+    /// [`Blinkt::set_end_frame_len()`] for details.
+    ///
+    /// By default, this is set to `None`.
+    ///
+    /// [`Blinkt::set_end_frame_len()`]: struct.Blinkt.html#method.set_end_frame_len
+    pub fn end_frame_len(mut self, end_frame_len: Option<usize>) -> Self {
+        self.end_frame_len = end_frame_len;
+        self
+    }
+
+    /// Restricts the thread that calls `build()` to run on any of the specified
+    /// CPU cores. See the [`affinity`] module for details.
+    ///
+    /// Call `build()` from the thread that calls `show()`. By default, the CPU
+    /// affinity isn't changed.
+    ///
+    /// [`affinity`]: affinity/index.html
+    pub fn cpu_affinity(mut self, cores: &[usize]) -> Self {
+        self.cpu_affinity = cores.to_vec();
+        self
+    }
+
+    /// Checks the configured settings for mistakes, without accessing any
+    /// hardware.
+    ///
+    /// `validate()` verifies the pin numbers, the SPI clock speed, the number of
+    /// pixels, and the brightness and gamma values. In hardware SPI mode, it also
+    /// checks whether the SPI device exists, which requires SPI to be enabled
+    /// through `raspi-config`. This catches configuration mistakes during
+    /// deployment, before `build()` is called on the target device. Returns
+    /// [`Error::InvalidConfig`] listing every problem that was found.
+    ///
+    /// Pins that are already in use by another process or peripheral can only be
+    /// detected by `build()`.
+    ///
+    /// [`Error::InvalidConfig`]: enum.Error.html#variant.InvalidConfig
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        match self.interface {
+            Interface::Bitbang(pin_data, pin_clock) | Interface::Auto(pin_data, pin_clock) => {
+                for (name, pin) in [("data", pin_data), ("clock", pin_clock)] {
+                    if pin > MAX_HEADER_PIN {
+                        problems.push(format!(
+                            "{} pin GPIO {} isn't available on the GPIO header",
+                            name, pin
+                        ));
+                    }
+                }
+
+                if pin_data == pin_clock {
+                    problems.push(format!("data pin and clock pin both use GPIO {}", pin_data));
+                }
+            }
+            Interface::Spi(bus, slave, clock_speed_hz) => {
+                if clock_speed_hz == 0 || clock_speed_hz > MAX_SPI_CLOCK_SPEED {
+                    problems.push(format!(
+                        "SPI clock speed {} Hz isn't between 1 Hz and {} Hz",
+                        clock_speed_hz, MAX_SPI_CLOCK_SPEED
+                    ));
+                }
+
+                let device = format!("/dev/spidev{}.{}", bus as u8, slave as u8);
+                if !Path::new(&device).exists() {
+                    problems.push(format!("SPI device {} doesn't exist", device));
+                }
+            }
+        }
+
+        if self.num_pixels == 0 {
+            problems.push("number of pixels is 0".to_owned());
+        }
+
+        if !(0.0..=1.0).contains(&self.brightness) {
+            problems.push(format!(
+                "brightness {} isn't between 0.0 and 1.0",
+                self.brightness
+            ));
+        }
+
+        if !self.gamma.is_finite() || self.gamma <= 0.0 {
+            problems.push(format!("gamma {} isn't a positive number", self.gamma));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidConfig(problems.join("; ")))
+        }
+    }
+
+    /// Constructs a new `Blinkt` with the configured settings.
+    pub fn build(&self) -> Result<Blinkt> {
+        if !self.cpu_affinity.is_empty() {
+            affinity::set_current_thread_affinity(&self.cpu_affinity)?;
+        }
+
+        let mut blinkt = match self.interface {
+            Interface::Bitbang(pin_data, pin_clock) => {
+                Blinkt::with_settings(pin_data, pin_clock, self.num_pixels)?
+            }
+            Interface::Spi(bus, slave, clock_speed_hz) => Blinkt::with_spi(
+                BlinktSpi::with_settings(bus, slave, clock_speed_hz, spi::Mode::Mode0)?,
+                self.num_pixels,
+            ),
+            Interface::Auto(pin_data, pin_clock) => {
+                Blinkt::auto_backend(pin_data, pin_clock, self.num_pixels)?
+            }
+        };
+
+        blinkt.set_clear_on_drop(self.clear_on_drop);
+        blinkt.set_global_brightness(self.brightness);
+        blinkt.set_gamma(self.gamma);
+        blinkt.set_color_order(self.color_order);
+        blinkt.set_timing_strategy(self.timing_strategy);
+        blinkt.set_end_frame_len(self.end_frame_len);
+
+        Ok(blinkt)
+    }
+}
+
+impl Default for BlinktBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Blinkt {
+    /// Returns a [`BlinktBuilder`] to construct a `Blinkt` with custom settings.
+    ///
+    /// [`BlinktBuilder`]: struct.BlinktBuilder.html
+    pub fn builder() -> BlinktBuilder {
+        BlinktBuilder::new()
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::checksum::crc32;
+use crate::driver::decode_frame;
+use crate::{ColorOrder, Driver, Result};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+// Maximum length of an uncompressed deflate block.
+const MAX_STORED_BLOCK: usize = 65_535;
+
+#[derive(Debug, Default)]
+struct Capture {
+    frames: Vec<Vec<(u8, u8, u8)>>,
+    frame: Vec<u8>,
+    color_order: ColorOrder,
+}
+
+/// A [`Driver`] that captures every frame, to save an animation as an image.
+///
+/// Every frame sent by `show()` is decoded, with the brightness of each pixel
+/// applied to its color. `save_png()` saves the captured frames as a PNG image,
+/// with one row for every frame, which shows the entire animation at a glance.
+/// Clones of a `CaptureOutput` share the same captured frames.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// # use std::time::Duration;
+/// #
+/// # use blinkt::{Blinkt, CaptureOutput};
+/// use blinkt::effect::Rainbow;
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let capture = CaptureOutput::new(100);
+/// let mut blinkt = Blinkt::with_driver(capture.clone(), 8);
+/// let mut effect = Rainbow::new();
+///
+/// for frame in 0..100 {
+///     blinkt.render(&mut effect, Duration::from_millis(frame * 100));
+///     blinkt.show()?;
+/// }
+///
+/// capture.save_png("rainbow.png", 4)?;
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`Driver`]: trait.Driver.html
+#[derive(Debug, Clone)]
+pub struct CaptureOutput {
+    capture: Arc<Mutex<Capture>>,
+    max_frames: usize,
+}
+
+impl CaptureOutput {
+    /// Constructs a new `CaptureOutput` that keeps the most recent `max_frames`
+    /// frames.
+    pub fn new(max_frames: usize) -> Self {
+        Self {
+            capture: Arc::new(Mutex::new(Capture::default())),
+            max_frames,
+        }
+    }
+
+    /// Returns the red, green and blue values of every pixel for all captured
+    /// frames, from oldest to newest.
+    pub fn frames(&self) -> Vec<Vec<(u8, u8, u8)>> {
+        self.lock().frames.clone()
+    }
+
+    /// Returns the number of captured frames.
+    pub fn len(&self) -> usize {
+        self.lock().frames.len()
+    }
+
+    /// Returns `true` if no frames have been captured.
+    pub fn is_empty(&self) -> bool {
+        self.lock().frames.is_empty()
+    }
+
+    /// Discards all captured frames.
+    pub fn clear(&self) {
+        self.lock().frames.clear();
+    }
+
+    /// Saves the captured frames as a PNG image, with one row of pixels for
+    /// every frame, and one column for every pixel.
+    ///
+    /// Every pixel is drawn as a square block of `scale` by `scale` image
+    /// pixels.
+    pub fn save_png<P: AsRef<Path>>(&self, path: P, scale: usize) -> Result<()> {
+        fs::write(path, self.to_png(scale))?;
+
+        Ok(())
+    }
+
+    /// Returns the captured frames encoded as a PNG image. See `save_png()`
+    /// for details.
+    pub fn to_png(&self, scale: usize) -> Vec<u8> {
+        let capture = self.lock();
+        let scale = scale.max(1);
+        let width = capture.frames.iter().map(Vec::len).max().unwrap_or(0) * scale;
+        let height = capture.frames.len() * scale;
+
+        // Every scanline starts with filter type 0 (None).
+        let mut image = Vec::with_capacity((width * 3 + 1) * height);
+        for frame in &capture.frames {
+            let mut scanline = vec![0u8; width * 3 + 1];
+            for (index, (red, green, blue)) in frame.iter().enumerate() {
+                for x in index * scale..(index + 1) * scale {
+                    scanline[1 + x * 3..4 + x * 3].copy_from_slice(&[*red, *green, *blue]);
+                }
+            }
+
+            for _ in 0..scale {
+                image.extend_from_slice(&scanline);
+            }
+        }
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(width as u32).to_be_bytes());
+        header.extend_from_slice(&(height as u32).to_be_bytes());
+        // Bit depth 8, color type 2 (RGB), default compression, filter and
+        // interlace methods.
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        let mut png = PNG_SIGNATURE.to_vec();
+        write_chunk(&mut png, b"IHDR", &header);
+        write_chunk(&mut png, b"IDAT", &zlib_stored(&image));
+        write_chunk(&mut png, b"IEND", &[]);
+
+        png
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Capture> {
+        self.capture
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Driver for CaptureOutput {
+    fn begin_frame(&mut self) -> Result<()> {
+        self.lock().frame.clear();
+
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.lock().frame.extend_from_slice(data);
+
+        Ok(())
+    }
+
+    fn end_frame(&mut self) -> Result<()> {
+        let max_frames = self.max_frames;
+        let mut capture = self.lock();
+
+        let frame = decode_frame(&capture.frame, capture.color_order);
+        capture.frames.push(frame);
+
+        if capture.frames.len() > max_frames {
+            let excess = capture.frames.len() - max_frames;
+            capture.frames.drain(..excess);
+        }
+
+        Ok(())
+    }
+
+    fn set_color_order(&mut self, color_order: ColorOrder) {
+        self.lock().color_order = color_order;
+    }
+}
+
+fn write_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = png.len();
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// Wraps `data` in a zlib stream containing uncompressed deflate blocks, which
+// avoids the need for a compression library.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut stream = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+
+    if blocks.peek().is_none() {
+        stream.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+
+        stream.push(u8::from(last));
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + u32::from(*byte)) % 65_521;
+        (a, (b + a) % 65_521)
+    });
+
+    (b << 16) | a
+}
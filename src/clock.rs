@@ -0,0 +1,217 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::{Duration, Instant};
+
+/// A clock that keeps track of the time between frames.
+///
+/// Call `tick()` once per frame, and use `elapsed()` or `delta()` to drive
+/// animations based on time rather than on the number of rendered frames,
+/// so they run at the same speed regardless of the frame rate.
+#[derive(Debug, Copy, Clone)]
+pub struct FrameClock {
+    start: Instant,
+    last: Instant,
+    delta: Duration,
+    frames: u64,
+}
+
+impl FrameClock {
+    /// Constructs a new `FrameClock`, starting at the current time.
+    pub fn new() -> Self {
+        let now = Instant::now();
+
+        Self {
+            start: now,
+            last: now,
+            delta: Duration::default(),
+            frames: 0,
+        }
+    }
+
+    /// Advances the clock to the current time, and returns the time that has
+    /// passed since the previous call to `tick()`.
+    pub fn tick(&mut self) -> Duration {
+        let now = Instant::now();
+
+        self.delta = now.duration_since(self.last);
+        self.last = now;
+        self.frames += 1;
+
+        self.delta
+    }
+
+    /// Returns the time between the start of the clock and the most recent
+    /// call to `tick()`.
+    ///
+    /// The value only changes when `tick()` is called, so every animation
+    /// rendered within the same frame sees the same time.
+    pub fn elapsed(&self) -> Duration {
+        self.last.duration_since(self.start)
+    }
+
+    /// Returns the time between the two most recent calls to `tick()`.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Returns the number of times `tick()` has been called.
+    pub fn frame_count(&self) -> u64 {
+        self.frames
+    }
+
+    /// Restarts the clock at the current time.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A clock that follows an external source of beats, such as a MIDI clock or
+/// a beat detector.
+///
+/// Call `pulse()` for every clock pulse received from the external source. A
+/// MIDI clock sends 24 pulses per beat. Sources that only report full beats
+/// can use a single pulse per beat. Between pulses, the position is advanced
+/// based on the current tempo, so animations remain smooth.
+///
+/// `beat_time()` converts the position to a `Duration` at a fixed rate of one
+/// second per beat, so a [`Timeline`] with keyframes placed at whole seconds
+/// changes on every beat, and follows any changes in tempo.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// # use std::time::Duration;
+/// #
+/// # use blinkt::{BeatClock, Blinkt, Easing, Playback, Timeline};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::new()?;
+/// let mut clock = BeatClock::new(24);
+/// let mut timeline = Timeline::new(Duration::from_secs(1));
+/// timeline.set_playback(Playback::Loop);
+///
+/// timeline.add_keyframe("brightness", Duration::from_secs(0), 1.0, Easing::EaseOut);
+/// timeline.add_keyframe("brightness", Duration::from_secs(1), 0.0, Easing::EaseOut);
+///
+/// loop {
+///     // Call clock.pulse() for every MIDI timing clock message (0xF8).
+///     let brightness = timeline.value("brightness", clock.beat_time()).unwrap_or(1.0);
+///
+///     blinkt.set_all_pixels_rgbb(255, 0, 0, brightness);
+///     blinkt.show()?;
+/// #   break;
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`Timeline`]: struct.Timeline.html
+#[derive(Debug, Copy, Clone)]
+pub struct BeatClock {
+    pulses_per_beat: u32,
+    pulses: u64,
+    last_pulse: Option<Instant>,
+    pulse_interval: Option<Duration>,
+}
+
+impl BeatClock {
+    /// Constructs a new `BeatClock` for a source that sends `pulses_per_beat`
+    /// pulses for every beat.
+    pub fn new(pulses_per_beat: u32) -> Self {
+        Self {
+            pulses_per_beat: pulses_per_beat.max(1),
+            pulses: 0,
+            last_pulse: None,
+            pulse_interval: None,
+        }
+    }
+
+    /// Registers a clock pulse, and returns `true` if the pulse starts a new beat.
+    pub fn pulse(&mut self) -> bool {
+        let now = Instant::now();
+
+        if let Some(last_pulse) = self.last_pulse {
+            let interval = now.duration_since(last_pulse);
+
+            // Smooth out any jitter in the pulses.
+            self.pulse_interval = Some(match self.pulse_interval {
+                Some(average) => (average * 7 + interval) / 8,
+                None => interval,
+            });
+        }
+
+        let started = self.pulses % u64::from(self.pulses_per_beat) == 0;
+
+        self.last_pulse = Some(now);
+        self.pulses += 1;
+
+        started
+    }
+
+    /// Returns the number of pulses per beat.
+    pub fn pulses_per_beat(&self) -> u32 {
+        self.pulses_per_beat
+    }
+
+    /// Returns the current tempo in beats per minute, or `None` if not enough
+    /// pulses were received to determine the tempo.
+    pub fn bpm(&self) -> Option<f32> {
+        self.pulse_interval
+            .filter(|interval| *interval > Duration::default())
+            .map(|interval| 60.0 / (interval.as_secs_f32() * self.pulses_per_beat as f32))
+    }
+
+    /// Returns the current position in beats, starting at `0.0` for the first
+    /// pulse.
+    ///
+    /// The position is advanced based on the current tempo until the next pulse
+    /// is due, but never moves past it.
+    pub fn beats(&self) -> f32 {
+        let last_pulse = match self.last_pulse {
+            Some(last_pulse) => last_pulse,
+            None => return 0.0,
+        };
+
+        let progress = self.pulse_interval.map_or(0.0, |interval| {
+            (last_pulse.elapsed().as_secs_f32() / interval.as_secs_f32()).min(1.0)
+        });
+
+        ((self.pulses - 1) as f32 + progress) / self.pulses_per_beat as f32
+    }
+
+    /// Returns the current position as a `Duration`, where every beat counts as
+    /// one second.
+    pub fn beat_time(&self) -> Duration {
+        Duration::from_secs_f32(self.beats())
+    }
+
+    /// Restarts the clock at the first beat, for instance when a MIDI start
+    /// message is received. The current tempo is kept.
+    pub fn reset(&mut self) {
+        self.pulses = 0;
+        self.last_pulse = None;
+    }
+}
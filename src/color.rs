@@ -0,0 +1,371 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Color difference functions suited for LED output.
+//!
+//! LEDs emit light linearly with their red, green and blue values, while the
+//! eye is much more sensitive to differences between dark colors than between
+//! bright colors. Before colors are compared, their values are converted to a
+//! perceptual scale with a gamma of `2.2`, after which the difference is
+//! calculated with a weighted Euclidean distance ("redmean"), a low-cost
+//! approximation of the CIE ΔE color difference.
+//!
+//! Colors in configuration files can be parsed with [`parse()`], which accepts
+//! hex notation (`#ff8000` or `#f80`), `rgb(255, 128, 0)`, `hsl(30, 100%, 50%)`
+//! and common color names such as `orange`. Numbers always use `.` as the
+//! decimal separator, regardless of the system locale.
+//!
+//! ```rust
+//! use blinkt::{color, Palette};
+//!
+//! let palette = Palette::new(&[(255, 0, 0), (0, 255, 0), (0, 0, 255)]);
+//!
+//! assert_eq!(color::nearest_palette_color(&palette, (200, 30, 20)), Some(0));
+//! assert!(color::distance((0, 0, 0), (4, 4, 4)) > color::distance((251, 251, 251), (255, 255, 255)));
+//! assert_eq!(color::parse("hsl(120, 100%, 50%)"), Ok((0, 255, 0)));
+//! ```
+//!
+//! [`parse()`]: fn.parse.html
+
+use std::cmp::Ordering;
+use std::error;
+use std::fmt;
+
+use crate::{Palette, Pixel};
+
+// Gamma used to convert linear LED intensities to a perceptual scale
+const PERCEPTUAL_GAMMA: f32 = 2.2;
+
+// Color names recognized by `parse()`, based on the CSS color keywords.
+const NAMED_COLORS: [(&str, (u8, u8, u8)); 24] = [
+    ("black", (0, 0, 0)),
+    ("blue", (0, 0, 255)),
+    ("brown", (165, 42, 42)),
+    ("cyan", (0, 255, 255)),
+    ("fuchsia", (255, 0, 255)),
+    ("gold", (255, 215, 0)),
+    ("gray", (128, 128, 128)),
+    ("green", (0, 128, 0)),
+    ("grey", (128, 128, 128)),
+    ("indigo", (75, 0, 130)),
+    ("lime", (0, 255, 0)),
+    ("magenta", (255, 0, 255)),
+    ("maroon", (128, 0, 0)),
+    ("navy", (0, 0, 128)),
+    ("olive", (128, 128, 0)),
+    ("orange", (255, 165, 0)),
+    ("pink", (255, 192, 203)),
+    ("purple", (128, 0, 128)),
+    ("red", (255, 0, 0)),
+    ("silver", (192, 192, 192)),
+    ("teal", (0, 128, 128)),
+    ("violet", (238, 130, 238)),
+    ("white", (255, 255, 255)),
+    ("yellow", (255, 255, 0)),
+];
+
+/// An error returned by [`parse()`] when a color can't be parsed.
+///
+/// The error message describes what's wrong with the value.
+///
+/// [`parse()`]: fn.parse.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError {
+    message: String,
+}
+
+impl ParseColorError {
+    fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for ParseColorError {}
+
+/// Parses a color, and returns its red, green and blue values as 8-bit values
+/// between `0` (0%) and `255` (100%).
+///
+/// The following notations are supported, ignoring case:
+///
+/// * Hex notation with a leading `#`, such as `#ff8000`, or the short form `#f80`.
+/// * `rgb(red, green, blue)`, with values between `0` and `255`, or percentages
+///   such as `rgb(100%, 50%, 0%)`.
+/// * `hsl(hue, saturation, lightness)`, with the hue in degrees, and the
+///   saturation and lightness as percentages, such as `hsl(30, 100%, 50%)`.
+/// * Common color names, such as `orange`, `teal` or `white`.
+///
+/// Values inside `rgb()` and `hsl()` can be separated by commas or spaces.
+pub fn parse(value: &str) -> Result<(u8, u8, u8), ParseColorError> {
+    let value = value.trim();
+    let lowercase = value.to_lowercase();
+
+    if let Some(hex) = lowercase.strip_prefix('#') {
+        return parse_hex(hex).ok_or_else(|| {
+            ParseColorError::new(format!(
+                "invalid hex color `{}`, expected #rgb or #rrggbb",
+                value
+            ))
+        });
+    }
+
+    if let Some(arguments) = function_arguments(&lowercase, "rgb") {
+        return parse_rgb(&arguments)
+            .ok_or_else(|| ParseColorError::new(format!("invalid rgb() color `{}`", value)));
+    }
+
+    if let Some(arguments) = function_arguments(&lowercase, "hsl") {
+        return parse_hsl(&arguments)
+            .ok_or_else(|| ParseColorError::new(format!("invalid hsl() color `{}`", value)));
+    }
+
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| *name == lowercase)
+        .map(|(_, color)| *color)
+        .ok_or_else(|| ParseColorError::new(format!("unknown color `{}`", value)))
+}
+
+/// Returns the perceived difference between two colors, containing the red,
+/// green and blue values specified as 8-bit values between `0` (0%) and `255`
+/// (100%).
+///
+/// The result is `0.0` for identical colors, and around `765.0` for black
+/// and white.
+pub fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> f32 {
+    distance_f(
+        [f32::from(a.0), f32::from(a.1), f32::from(a.2)],
+        [f32::from(b.0), f32::from(b.1), f32::from(b.2)],
+    )
+}
+
+/// Returns the perceived difference between two pixels, taking their
+/// brightness into account.
+///
+/// The red, green and blue values of both pixels are scaled by their
+/// brightness before they're compared, so two pixels that emit the same
+/// amount of light return a difference close to `0.0`.
+pub fn pixel_distance(a: &Pixel, b: &Pixel) -> f32 {
+    let scale = |pixel: &Pixel| {
+        let (red, green, blue, brightness) = pixel.rgbb();
+
+        [
+            f32::from(red) * brightness,
+            f32::from(green) * brightness,
+            f32::from(blue) * brightness,
+        ]
+    };
+
+    distance_f(scale(a), scale(b))
+}
+
+/// Returns the index of the color in `palette` that's perceived as closest
+/// to `color`, or `None` if the palette doesn't contain any colors.
+pub fn nearest_palette_color(palette: &Palette, color: (u8, u8, u8)) -> Option<usize> {
+    palette
+        .colors()
+        .iter()
+        .map(|candidate| distance(*candidate, color))
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .map(|(index, _)| index)
+}
+
+fn distance_f(a: [f32; 3], b: [f32; 3]) -> f32 {
+    let perceptual = |value: f32| (value / 255.0).powf(1.0 / PERCEPTUAL_GAMMA) * 255.0;
+    let (a, b) = (a.map(perceptual), b.map(perceptual));
+
+    let red_mean = (a[0] + b[0]) / 2.0;
+    let (red, green, blue) = (a[0] - b[0], a[1] - b[1], a[2] - b[2]);
+
+    ((2.0 + red_mean / 256.0) * red * red
+        + 4.0 * green * green
+        + (2.0 + (255.0 - red_mean) / 256.0) * blue * blue)
+        .sqrt()
+}
+
+fn parse_hex(hex: &str) -> Option<(u8, u8, u8)> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |index: usize, len: usize| {
+        let value = u8::from_str_radix(&hex[index * len..(index + 1) * len], 16).ok()?;
+
+        Some(if len == 1 { value * 17 } else { value })
+    };
+
+    let len = match hex.len() {
+        3 => 1,
+        6 => 2,
+        _ => return None,
+    };
+
+    Some((channel(0, len)?, channel(1, len)?, channel(2, len)?))
+}
+
+// Returns the arguments of `name(a, b, c)`, separated by commas or spaces.
+fn function_arguments<'a>(value: &'a str, name: &str) -> Option<Vec<&'a str>> {
+    let arguments = value
+        .strip_prefix(name)?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+
+    Some(
+        arguments
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|argument| !argument.is_empty())
+            .collect(),
+    )
+}
+
+// Parses a number, or a percentage scaled to `max`.
+fn parse_number(value: &str, max: f32) -> Option<f32> {
+    let number = match value.strip_suffix('%') {
+        Some(percentage) => percentage.parse::<f32>().ok()? / 100.0 * max,
+        None => value.parse::<f32>().ok()?,
+    };
+
+    if number.is_finite() {
+        Some(number)
+    } else {
+        None
+    }
+}
+
+fn parse_rgb(arguments: &[&str]) -> Option<(u8, u8, u8)> {
+    let channel = |value: &str| {
+        let value = parse_number(value, 255.0)?;
+
+        if (0.0..=255.0).contains(&value) {
+            Some(value.round() as u8)
+        } else {
+            None
+        }
+    };
+
+    match arguments {
+        [red, green, blue] => Some((channel(red)?, channel(green)?, channel(blue)?)),
+        _ => None,
+    }
+}
+
+fn parse_hsl(arguments: &[&str]) -> Option<(u8, u8, u8)> {
+    let (hue, saturation, lightness) = match arguments {
+        [hue, saturation, lightness] => (
+            parse_number(hue.trim_end_matches("deg"), 360.0)?,
+            parse_number(saturation, 1.0)?,
+            parse_number(lightness, 1.0)?,
+        ),
+        _ => return None,
+    };
+
+    // Saturation and lightness must be percentages.
+    if !arguments[1].ends_with('%')
+        || !arguments[2].ends_with('%')
+        || !(0.0..=1.0).contains(&saturation)
+        || !(0.0..=1.0).contains(&lightness)
+    {
+        return None;
+    }
+
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (red, green, blue) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let offset = lightness - chroma / 2.0;
+    let channel = |value: f32| ((value + offset) * 255.0).round() as u8;
+
+    Some((channel(red), channel(green), channel(blue)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex() {
+        assert_eq!(parse("#ff8000").unwrap(), (255, 128, 0));
+        assert_eq!(parse("#F80").unwrap(), (255, 136, 0));
+        assert_eq!(parse("  #00ff00 ").unwrap(), (0, 255, 0));
+
+        assert!(parse("#ff80").is_err());
+        assert!(parse("#ff800g").is_err());
+        assert!(parse("#").is_err());
+    }
+
+    #[test]
+    fn parse_rgb() {
+        assert_eq!(parse("rgb(255, 128, 0)").unwrap(), (255, 128, 0));
+        assert_eq!(parse("RGB(100%, 50%, 0%)").unwrap(), (255, 128, 0));
+        assert_eq!(parse("rgb(255 128,0)").unwrap(), (255, 128, 0));
+        assert_eq!(parse("rgb (1 2 3)").unwrap(), (1, 2, 3));
+
+        assert!(parse("rgb(256, 0, 0)").is_err());
+        assert!(parse("rgb(-1, 0, 0)").is_err());
+        assert!(parse("rgb(101%, 0%, 0%)").is_err());
+        assert!(parse("rgb(0, 0)").is_err());
+        assert!(parse("rgb(0, 0, 0, 0)").is_err());
+        assert!(parse("rgb(0, 0, inf)").is_err());
+        assert!(parse("rgb(0, 0, 0").is_err());
+    }
+
+    #[test]
+    fn parse_hsl() {
+        assert_eq!(parse("hsl(30, 100%, 50%)").unwrap(), (255, 128, 0));
+        assert_eq!(parse("hsl(30deg 100% 50%)").unwrap(), (255, 128, 0));
+        assert_eq!(parse("hsl(0, 0%, 100%)").unwrap(), (255, 255, 255));
+        assert_eq!(parse("hsl(120, 100%, 25%)").unwrap(), (0, 128, 0));
+
+        // Hues wrap around.
+        assert_eq!(parse("hsl(-90, 100%, 50%)").unwrap(), (128, 0, 255));
+        assert_eq!(parse("hsl(390, 100%, 50%)").unwrap(), (255, 128, 0));
+        assert_eq!(parse("hsl(360, 100%, 50%)").unwrap(), (255, 0, 0));
+
+        assert!(parse("hsl(30, 100, 50)").is_err());
+        assert!(parse("hsl(30, 100%, 50)").is_err());
+        assert!(parse("hsl(30, 101%, 50%)").is_err());
+        assert!(parse("hsl(30, 100%, -1%)").is_err());
+        assert!(parse("hsl(30, 100%)").is_err());
+    }
+
+    #[test]
+    fn parse_named() {
+        assert_eq!(parse("Orange").unwrap(), (255, 165, 0));
+        assert_eq!(parse(" white ").unwrap(), (255, 255, 255));
+
+        assert!(parse("").is_err());
+        assert!(parse("not a color").is_err());
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::{Pixel, WirePixel};
+
+/// Color correction applied to pixels when they're sent by `show()`.
+///
+/// A `Correction` combines gamma correction, a per-channel calibration and a
+/// maximum brightness. Corrections are only applied when the local buffer is
+/// sent to the pixels, and never change the stored pixel values.
+///
+/// Different corrections can be assigned to different segments of an LED strip
+/// with [`Blinkt::set_segment_correction()`], which is useful when a strip
+/// consists of several runs of LEDs that don't look the same, for instance an
+/// SK9822 strip spliced to an APA102 strip.
+///
+/// [`Blinkt::set_segment_correction()`]: struct.Blinkt.html#method.set_segment_correction
+#[derive(Debug, Clone)]
+pub struct Correction {
+    gamma: f32,
+    calibration: (f32, f32, f32),
+    max_brightness: f32,
+    lut: [[u8; 256]; 3], // Red, green, blue
+}
+
+impl Correction {
+    /// Constructs a new `Correction` that leaves all values unchanged.
+    pub fn new() -> Self {
+        let mut correction = Self {
+            gamma: 1.0,
+            calibration: (1.0, 1.0, 1.0),
+            max_brightness: 1.0,
+            lut: [[0; 256]; 3],
+        };
+
+        correction.update_lut();
+        correction
+    }
+
+    /// Returns the gamma value.
+    pub fn gamma(&self) -> f32 {
+        self.gamma
+    }
+
+    /// Sets the gamma value.
+    ///
+    /// A `gamma` of `1.0` leaves the red, green and blue values unchanged. Values
+    /// around `2.2` compensate for the non-linear way colors are perceived, which
+    /// results in smoother fades and less washed out colors.
+    pub fn set_gamma(&mut self, gamma: f32) {
+        self.gamma = gamma.max(f32::EPSILON);
+        self.update_lut();
+    }
+
+    /// Returns a tuple containing the calibration values for red, green and blue.
+    pub fn calibration(&self) -> (f32, f32, f32) {
+        self.calibration
+    }
+
+    /// Sets the calibration values for red, green and blue, which are multiplied
+    /// with their respective color values after gamma correction.
+    ///
+    /// `red`, `green` and `blue` are specified as floating point values between `0.0` (0%) and `1.0` (100%).
+    pub fn set_calibration(&mut self, red: f32, green: f32, blue: f32) {
+        self.calibration = (
+            red.clamp(0.0, 1.0),
+            green.clamp(0.0, 1.0),
+            blue.clamp(0.0, 1.0),
+        );
+        self.update_lut();
+    }
+
+    /// Returns the maximum brightness value.
+    pub fn max_brightness(&self) -> f32 {
+        self.max_brightness
+    }
+
+    /// Sets the maximum brightness value. Pixels with a higher brightness are
+    /// limited to this value.
+    ///
+    /// `max_brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%).
+    pub fn set_max_brightness(&mut self, max_brightness: f32) {
+        self.max_brightness = max_brightness.clamp(0.0, 1.0);
+    }
+
+    pub(crate) fn apply(&self, pixel: &Pixel) -> WirePixel {
+        let (red, green, blue) = pixel.rgb();
+        let max_brightness = (31.0 * self.max_brightness) as u8;
+
+        WirePixel::new(
+            pixel.brightness_bits().min(max_brightness),
+            self.lut[0][usize::from(red)],
+            self.lut[1][usize::from(green)],
+            self.lut[2][usize::from(blue)],
+        )
+        .with_white(pixel.white())
+        .with_low(pixel.low_bytes())
+    }
+
+    // Applies gamma correction and calibration to the 16-bit values of a pixel,
+    // without the loss of precision caused by the 8-bit lookup table.
+    pub(crate) fn apply_rgb16(&self, pixel: &Pixel) -> (u16, u16, u16) {
+        let (red, green, blue) = pixel.rgb16();
+        let channel = |value: u16, scale: f32| {
+            ((f32::from(value) / 65535.0).powf(self.gamma) * scale * 65535.0).round() as u16
+        };
+
+        (
+            channel(red, self.calibration.0),
+            channel(green, self.calibration.1),
+            channel(blue, self.calibration.2),
+        )
+    }
+
+    fn update_lut(&mut self) {
+        let calibration = [self.calibration.0, self.calibration.1, self.calibration.2];
+
+        for (lut, scale) in self.lut.iter_mut().zip(&calibration) {
+            for (value, entry) in lut.iter_mut().enumerate() {
+                let corrected = (value as f32 / 255.0).powf(self.gamma) * scale;
+                *entry = (corrected * 255.0).round() as u8;
+            }
+        }
+    }
+}
+
+impl Default for Correction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
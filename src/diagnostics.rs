@@ -0,0 +1,460 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::mem;
+use std::thread;
+use std::time::Duration;
+
+use crate::{Blinkt, Pixel, Result};
+
+// Alternating bit patterns, which cause the most transitions on the data line.
+const FLICKER_PATTERNS: [(u8, u8, u8); 2] = [(0xaa, 0x55, 0xaa), (0x55, 0xaa, 0x55)];
+
+/// The result of [`Blinkt::estimate_length()`].
+///
+/// [`Blinkt::estimate_length()`]: struct.Blinkt.html#method.estimate_length
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LengthEstimate {
+    length: usize,
+    dead_pixels: Vec<usize>,
+}
+
+impl LengthEstimate {
+    /// Returns the estimated number of pixels, up to and including the last
+    /// pixel that was confirmed to be visible.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Returns the pixels that weren't visible, but are followed by at least one
+    /// pixel that was.
+    pub fn dead_pixels(&self) -> &[usize] {
+        &self.dead_pixels
+    }
+}
+
+/// Test patterns used to diagnose wiring issues, incompatible pixels and
+/// signal integrity problems.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TestPattern {
+    /// Lights up pixels whose index has the specified bit set in white, and all
+    /// other pixels in dim blue. Showing the pattern for every bit in sequence
+    /// identifies each pixel by its index in binary.
+    IndexBit(u8),
+    /// Sets all pixels to full red.
+    Red,
+    /// Sets all pixels to full green.
+    Green,
+    /// Sets all pixels to full blue.
+    Blue,
+    /// Sets all pixels to full white.
+    White,
+    /// Sets the pixels to a white gradient, ranging from off on the first pixel
+    /// to full white on the last pixel.
+    Gradient,
+    /// Sets the pixels to a fixed pattern of alternating bits, which result
+    /// in the largest number of transitions on the data line. On a reliable
+    /// connection, the colors remain stable. Any flicker or random colors
+    /// indicate data corruption, which can often be resolved by lowering the
+    /// clock speed with `Blinkt::set_clock_speed()`.
+    Flicker,
+}
+
+impl Blinkt {
+    /// Sets the local buffer to a test pattern, and sends it to the pixels.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// # use std::thread;
+    /// # use std::time::Duration;
+    /// #
+    /// # use blinkt::{Blinkt, BlinktSpi, TestPattern};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mut blinkt = Blinkt::with_spi(BlinktSpi::default(), 144);
+    ///
+    /// // Check for flicker at increasing clock speeds.
+    /// for &clock_speed in &[1_000_000, 4_000_000, 8_000_000, 16_000_000] {
+    ///     blinkt.set_clock_speed(clock_speed)?;
+    ///
+    ///     for _ in 0..100 {
+    ///         blinkt.show_test_pattern(TestPattern::Flicker)?;
+    ///         thread::sleep(Duration::from_millis(20));
+    ///     }
+    /// }
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn show_test_pattern(&mut self, pattern: TestPattern) -> Result<()> {
+        let frame = self.test_pattern_frame(pattern);
+
+        self.transaction(|tx| {
+            tx.iter_mut()
+                .zip(frame)
+                .for_each(|(pixel, target)| *pixel = target);
+            tx.show();
+        })
+    }
+
+    fn test_pattern_frame(&self, pattern: TestPattern) -> Vec<Pixel> {
+        let num_pixels = self.pixels.len();
+        let mut frame = vec![Pixel::default(); num_pixels];
+
+        for (index, pixel) in frame.iter_mut().enumerate() {
+            match pattern {
+                TestPattern::IndexBit(bit) => {
+                    if bit < usize::BITS as u8 && index & (1 << bit) != 0 {
+                        pixel.set_rgbb(255, 255, 255, 0.2);
+                    } else {
+                        pixel.set_rgbb(0, 0, 32, 0.2);
+                    }
+                }
+                TestPattern::Red => pixel.set_rgbb(255, 0, 0, 0.2),
+                TestPattern::Green => pixel.set_rgbb(0, 255, 0, 0.2),
+                TestPattern::Blue => pixel.set_rgbb(0, 0, 255, 0.2),
+                TestPattern::White => pixel.set_rgbb(255, 255, 255, 0.2),
+                TestPattern::Gradient => {
+                    let value = (index * 255 / num_pixels.saturating_sub(1).max(1)) as u8;
+                    pixel.set_rgbb(value, value, value, 0.2);
+                }
+                TestPattern::Flicker => {
+                    let (red, green, blue) = FLICKER_PATTERNS[index % 2];
+                    pixel.set_rgbb(red, green, blue, 0.2);
+                }
+            }
+        }
+
+        frame
+    }
+
+    /// Estimates the actual number of pixels on an LED strip by lighting up one
+    /// pixel at a time.
+    ///
+    /// For each pixel in the local buffer, a frame is sent with only that pixel
+    /// lit, and `visible` is called with its index. `visible` should return
+    /// `true` if the pixel lights up, for instance after asking the user for
+    /// confirmation. Set up `Blinkt` with more pixels than expected to make sure
+    /// the entire strip is checked.
+    ///
+    /// The frames are sent without any of the processing applied by `show()`,
+    /// including the maximum frame rate, smoothing, persistence,
+    /// post-processing, soft start and global brightness, and are sent even if
+    /// the pixels are disabled. The local buffer isn't changed, and isn't sent
+    /// once all pixels are checked.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// # use std::io;
+    /// #
+    /// # use blinkt::{Blinkt, Profile};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mut profile = Profile::new("Cut strip", 23, 24, 300);
+    /// let mut blinkt = Blinkt::with_profile(&profile)?;
+    ///
+    /// let estimate = blinkt.estimate_length(|index| {
+    ///     println!("Is pixel {} lit? [y/n]", index);
+    ///
+    ///     let mut answer = String::new();
+    ///     io::stdin().read_line(&mut answer).is_ok() && answer.trim() == "y"
+    /// })?;
+    ///
+    /// profile.set_num_pixels(estimate.length());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn estimate_length<F>(&mut self, mut visible: F) -> Result<LengthEstimate>
+    where
+        F: FnMut(usize) -> bool,
+    {
+        let mut missing = Vec::new();
+        let mut estimate = LengthEstimate {
+            length: 0,
+            dead_pixels: Vec::new(),
+        };
+
+        for index in 0..self.pixels.len() {
+            self.show_single_pixel(Some(index))?;
+
+            if visible(index) {
+                estimate.dead_pixels.append(&mut missing);
+                estimate.length = index + 1;
+            } else {
+                missing.push(index);
+            }
+        }
+
+        Ok(estimate)
+    }
+
+    /// Blinks a single pixel to identify its physical location.
+    ///
+    /// The pixel at `index` is turned on and off `times` times, with `interval`
+    /// between every change, while all other pixels are turned off. The frames
+    /// are sent without any of the processing applied by `show()`, the same way
+    /// as for [`estimate_length()`]. The local buffer isn't changed, and is
+    /// sent with `show()` once the pixel is done blinking.
+    ///
+    /// [`estimate_length()`]: #method.estimate_length
+    pub fn blink_pixel(&mut self, index: usize, times: u32, interval: Duration) -> Result<()> {
+        for _ in 0..times {
+            for lit in [true, false] {
+                self.show_single_pixel(Some(index).filter(|_| lit))?;
+                thread::sleep(interval);
+            }
+        }
+
+        self.show()
+    }
+
+    /// Verifies the wiring of an installation by lighting up one pixel at a time,
+    /// and records the physical position of every pixel.
+    ///
+    /// For each pixel in the local buffer, a frame is sent with only that pixel
+    /// lit, and `position` is called with its index. `position` should return
+    /// the physical position of the lit pixel, for instance after asking the
+    /// installer, or `None` if the pixel isn't visible. The frames are sent
+    /// without any of the processing applied by `show()`, the same way as for
+    /// [`estimate_length()`].
+    ///
+    /// Returns a table containing the physical position for every index, which
+    /// can be stored to map a layout onto the pixels when they aren't wired in
+    /// order. The local buffer isn't changed, and isn't sent once all pixels are
+    /// checked.
+    ///
+    /// [`estimate_length()`]: #method.estimate_length
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// # use std::io;
+    /// #
+    /// # use blinkt::{Blinkt, BlinktSpi};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mut blinkt = Blinkt::with_spi(BlinktSpi::default(), 64);
+    ///
+    /// let table = blinkt.identify_pixels(|index| {
+    ///     println!("Which position is pixel {} at?", index);
+    ///
+    ///     let mut answer = String::new();
+    ///     io::stdin().read_line(&mut answer).ok()?;
+    ///     answer.trim().parse().ok()
+    /// })?;
+    ///
+    /// for (index, position) in table.iter().enumerate() {
+    ///     println!("{} = {:?}", index, position);
+    /// }
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn identify_pixels<F>(&mut self, mut position: F) -> Result<Vec<Option<usize>>>
+    where
+        F: FnMut(usize) -> Option<usize>,
+    {
+        let mut table = Vec::with_capacity(self.pixels.len());
+
+        for index in 0..self.pixels.len() {
+            self.show_single_pixel(Some(index))?;
+
+            table.push(position(index));
+        }
+
+        Ok(table)
+    }
+
+    /// Finds the highest reliable clock speed by sending a test pattern at each of
+    /// the specified clock speeds.
+    ///
+    /// `clock_speeds` are tried in ascending order. For each clock speed, the
+    /// [`TestPattern::Flicker`] pattern is sent repeatedly for one second, after
+    /// which `reliable` is called with the clock speed. `reliable` should return
+    /// `true` if the pattern remained stable, for instance after asking the user
+    /// for confirmation. The sweep ends at the first clock speed that isn't
+    /// reliable. The pattern is sent without any of the processing applied by
+    /// `show()`, the same way as for [`estimate_length()`], and the local buffer
+    /// isn't changed.
+    ///
+    /// Returns the highest reliable clock speed, which is also set as the new
+    /// clock speed for all outputs. If none of the clock speeds are reliable, the
+    /// original clock speed is restored, and `None` is returned. If an error
+    /// occurs, the highest reliable clock speed found so far, or the original
+    /// clock speed, is set before the error is returned.
+    ///
+    /// [`TestPattern::Flicker`]: enum.TestPattern.html#variant.Flicker
+    /// [`estimate_length()`]: #method.estimate_length
+    pub fn sweep_clock_speed<F>(
+        &mut self,
+        clock_speeds: &[u32],
+        mut reliable: F,
+    ) -> Result<Option<u32>>
+    where
+        F: FnMut(u32) -> bool,
+    {
+        let original = self.clock_speed();
+        let frame = self.test_pattern_frame(TestPattern::Flicker);
+
+        let mut clock_speeds = clock_speeds.to_vec();
+        clock_speeds.sort_unstable();
+
+        let mut highest = None;
+        let sweep = || -> Result<()> {
+            for clock_speed in clock_speeds {
+                self.set_clock_speed(clock_speed)?;
+
+                for _ in 0..50 {
+                    self.show_raw(&frame)?;
+                    thread::sleep(Duration::from_millis(20));
+                }
+
+                if !reliable(clock_speed) {
+                    return Ok(());
+                }
+
+                highest = Some(clock_speed);
+            }
+
+            Ok(())
+        };
+
+        let result = sweep();
+        self.set_clock_speed(highest.unwrap_or(original))?;
+
+        result.map(|_| highest)
+    }
+
+    // Sends a frame with only the pixel at `index` lit, or all pixels off.
+    fn show_single_pixel(&mut self, index: Option<usize>) -> Result<()> {
+        let mut frame = vec![Pixel::default(); self.pixels.len()];
+        if let Some(pixel) = index.and_then(|index| frame.get_mut(index)) {
+            pixel.set_rgbb(255, 255, 255, 0.2);
+        }
+
+        self.show_raw(&frame)
+    }
+
+    // Sends `frame` to the pixels, regardless of the maximum frame rate, and
+    // without smoothing, persistence, post-processing, soft start or global
+    // brightness, so diagnostic patterns are shown exactly as intended. Color
+    // correction is still applied, and the local buffer isn't changed.
+    fn show_raw(&mut self, frame: &[Pixel]) -> Result<()> {
+        self.frame.clear();
+        self.frame.extend_from_slice(frame);
+
+        let global_brightness = mem::replace(&mut self.global_brightness, 1.0);
+        let result = self.send_frame();
+        if result.is_ok() {
+            self.shown_current_ma = self.frame_current_ma();
+        }
+        self.global_brightness = global_brightness;
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockOutput;
+
+    #[test]
+    fn estimate_length_bypasses_processing() {
+        let mock = MockOutput::new();
+        let mut blinkt = Blinkt::with_driver(mock.clone(), 3);
+        blinkt.set_all_pixels(1, 2, 3);
+        blinkt.set_max_frame_rate(Some(1));
+        blinkt.set_global_brightness(0.0);
+        blinkt.set_enabled(false);
+        blinkt.set_post_process(|frame| frame.iter_mut().for_each(Pixel::clear));
+
+        let estimate = blinkt.estimate_length(|index| index != 1).unwrap();
+        assert_eq!(estimate.length(), 3);
+        assert_eq!(estimate.dead_pixels(), [1]);
+
+        // One frame for every pixel, with only that pixel lit.
+        let frames = mock.frames();
+        assert_eq!(frames.len(), 3);
+        for (index, frame) in frames.iter().enumerate() {
+            for pixel in 0..3 {
+                let led = &frame[4 + pixel * 4..8 + pixel * 4];
+                assert_eq!(led[1..] == [255, 255, 255], pixel == index);
+            }
+        }
+
+        assert!(blinkt.iter_mut().all(|pixel| pixel.rgb() == (1, 2, 3)));
+    }
+
+    #[test]
+    fn blink_pixel_restores_buffer() {
+        let mock = MockOutput::new();
+        let mut blinkt = Blinkt::with_driver(mock.clone(), 2);
+        blinkt.set_all_pixels(1, 2, 3);
+
+        blinkt.blink_pixel(1, 2, Duration::from_millis(0)).unwrap();
+
+        // Lit, off, lit, off, followed by the local buffer.
+        let frames = mock.frames();
+        assert_eq!(frames.len(), 5);
+        for (frame, lit) in frames.iter().zip([true, false, true, false]) {
+            assert_eq!(frame[4..8][1..], [0, 0, 0]);
+            assert_eq!(frame[8..12][1..] == [255, 255, 255], lit);
+        }
+
+        assert_eq!(frames[4][5..8], [3, 2, 1]);
+        assert!(blinkt.iter_mut().all(|pixel| pixel.rgb() == (1, 2, 3)));
+    }
+
+    #[test]
+    fn identify_pixels_records_positions() {
+        let mock = MockOutput::new();
+        let mut blinkt = Blinkt::with_driver(mock.clone(), 3);
+        blinkt.set_all_pixels(1, 2, 3);
+
+        let table = blinkt
+            .identify_pixels(|index| (index != 2).then(|| 2 - index))
+            .unwrap();
+        assert_eq!(table, [Some(2), Some(1), None]);
+        assert_eq!(mock.len(), 3);
+        assert!(blinkt.iter_mut().all(|pixel| pixel.rgb() == (1, 2, 3)));
+    }
+    #[test]
+    fn sweep_clock_speed_sends_flicker_pattern() {
+        let mock = MockOutput::new();
+        let mut blinkt = Blinkt::with_driver(mock.clone(), 2);
+        blinkt.set_all_pixels(1, 2, 3);
+        blinkt.set_global_brightness(0.0);
+
+        let mut tried = Vec::new();
+        let highest = blinkt
+            .sweep_clock_speed(&[2_000_000, 1_000_000, 4_000_000], |clock_speed| {
+                tried.push(clock_speed);
+                clock_speed < 4_000_000
+            })
+            .unwrap();
+
+        assert_eq!(highest, Some(2_000_000));
+        assert_eq!(tried, [1_000_000, 2_000_000, 4_000_000]);
+        assert_eq!(mock.len(), 150);
+        assert_eq!(
+            mock.last_frame().unwrap()[4..12],
+            [0xe6, 0xaa, 0x55, 0xaa, 0xe6, 0x55, 0xaa, 0x55]
+        );
+        assert!(blinkt.iter_mut().all(|pixel| pixel.rgb() == (1, 2, 3)));
+    }
+}
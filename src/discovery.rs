@@ -0,0 +1,310 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{Blinkt, Result, TcpOutput, TcpReceiver};
+
+const REQUEST: &[u8; 8] = b"BLINKT1?";
+const REPLY: &[u8; 8] = b"BLINKT1!";
+// Reply: magic, TCP port, number of pixels and name.
+const REPLY_HEADER_LEN: usize = 14;
+const MAX_NAME_LEN: usize = 255;
+// Replies come from untrusted sources, so the number of pixels is limited to
+// keep a bogus reply from allocating an enormous buffer.
+const MAX_NUM_PIXELS: usize = 65_536;
+// Interval at which the responder thread checks whether it should stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A [`TcpReceiver`] found on the local network by `discover()`.
+///
+/// [`TcpReceiver`]: struct.TcpReceiver.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredReceiver {
+    address: SocketAddr,
+    name: String,
+    num_pixels: usize,
+}
+
+impl DiscoveredReceiver {
+    /// Sends a discovery request to `address`, and returns all receivers that
+    /// reply within `timeout`.
+    ///
+    /// `address` is usually the broadcast address of the local network, with
+    /// port [`DiscoveryResponder::PORT`]. The receivers are sorted by name and
+    /// address, so they're returned in a consistent order.
+    ///
+    /// [`DiscoveryResponder::PORT`]: struct.DiscoveryResponder.html#associatedconstant.PORT
+    pub fn discover<A: ToSocketAddrs>(address: A, timeout: Duration) -> Result<Vec<Self>> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        socket.set_broadcast(true)?;
+        socket.send_to(REQUEST, address)?;
+
+        let deadline = Instant::now() + timeout;
+        let mut buffer = [0u8; REPLY_HEADER_LEN + MAX_NAME_LEN];
+        let mut receivers: Vec<Self> = Vec::new();
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+
+            socket.set_read_timeout(Some(remaining))?;
+
+            let (len, from) = match socket.recv_from(&mut buffer) {
+                Ok(reply) => reply,
+                Err(ref err)
+                    if err.kind() == io::ErrorKind::WouldBlock
+                        || err.kind() == io::ErrorKind::TimedOut =>
+                {
+                    break
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            if let Some(receiver) = decode_reply(&buffer[..len], from) {
+                if !receivers
+                    .iter()
+                    .any(|known| known.address == receiver.address)
+                {
+                    receivers.push(receiver);
+                }
+            }
+        }
+
+        receivers.sort_by(|a, b| (&a.name, a.address).cmp(&(&b.name, b.address)));
+
+        Ok(receivers)
+    }
+
+    /// Returns the address the receiver is listening on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns the name of the receiver.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of pixels connected to the receiver.
+    pub fn num_pixels(&self) -> usize {
+        self.num_pixels
+    }
+}
+
+/// Answers discovery requests on behalf of a [`TcpReceiver`], so senders can
+/// find it with [`DiscoveredReceiver::discover()`].
+///
+/// Every reply contains the TCP port of the receiver, the number of pixels it
+/// controls, and its name. Requests are answered by a background thread, which
+/// stops when the `DiscoveryResponder` goes out of scope.
+///
+/// Together with [`Blinkt::with_receivers()`], this assigns a range of pixels
+/// to every receiver on the local network, for instance to build a video wall
+/// out of multiple Raspberry Pis.
+///
+/// Requests aren't authenticated, so every host that can reach the responder
+/// receives the port, number of pixels and name of the receiver.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// #
+/// # use blinkt::{Blinkt, BlinktSpi, DiscoveryResponder, TcpReceiver};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::with_spi(BlinktSpi::default(), 144);
+/// let receiver = TcpReceiver::bind("0.0.0.0:7890")?;
+/// let _responder = DiscoveryResponder::spawn(
+///     ("0.0.0.0", DiscoveryResponder::PORT),
+///     &receiver,
+///     "wall-1",
+///     144,
+/// )?;
+///
+/// receiver.run(&mut blinkt)?;
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`TcpReceiver`]: struct.TcpReceiver.html
+/// [`DiscoveredReceiver::discover()`]: struct.DiscoveredReceiver.html#method.discover
+/// [`Blinkt::with_receivers()`]: struct.Blinkt.html#method.with_receivers
+#[derive(Debug)]
+pub struct DiscoveryResponder {
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DiscoveryResponder {
+    /// The UDP port used for discovery requests.
+    pub const PORT: u16 = 7891;
+
+    /// Starts a background thread that listens for discovery requests on
+    /// `address`, and answers them on behalf of `receiver`.
+    ///
+    /// `name` identifies the receiver, and is truncated to 255 bytes.
+    /// `num_pixels` is the number of pixels connected to the receiver, and
+    /// should be between 1 and 65,536. Senders ignore replies outside that range.
+    pub fn spawn<A: ToSocketAddrs>(
+        address: A,
+        receiver: &TcpReceiver,
+        name: &str,
+        num_pixels: usize,
+    ) -> Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_read_timeout(Some(POLL_INTERVAL))?;
+
+        let mut name_len = name.len().min(MAX_NAME_LEN);
+        while !name.is_char_boundary(name_len) {
+            name_len -= 1;
+        }
+
+        let mut reply = REPLY.to_vec();
+        reply.extend_from_slice(&receiver.local_addr()?.port().to_be_bytes());
+        reply.extend_from_slice(&(num_pixels.min(u32::MAX as usize) as u32).to_be_bytes());
+        reply.extend_from_slice(&name.as_bytes()[..name_len]);
+
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let thread = thread::spawn(move || {
+            let mut buffer = [0u8; REQUEST.len()];
+
+            while thread_running.load(Ordering::SeqCst) {
+                if let Ok((len, from)) = socket.recv_from(&mut buffer) {
+                    if buffer[..len] == REQUEST[..] {
+                        let _ = socket.send_to(&reply, from);
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    /// Stops the background thread.
+    pub fn stop(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+
+        if let Some(Err(err)) = self.thread.take().map(JoinHandle::join) {
+            panic::resume_unwind(err);
+        }
+    }
+}
+
+impl Drop for DiscoveryResponder {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.join();
+        }
+    }
+}
+
+impl Blinkt {
+    /// Constructs a new `Blinkt` that sends its pixels to the specified
+    /// receivers through a [`TcpOutput`] for each receiver.
+    ///
+    /// Every receiver is assigned the next range of pixels in the local
+    /// buffer, based on the number of pixels it controls, in the order of
+    /// `receivers`. Returns an error if `receivers` is empty, a receiver
+    /// controls no pixels or more than 65,536 pixels, or a receiver can't be
+    /// reached.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// # use std::time::Duration;
+    /// #
+    /// # use blinkt::{Blinkt, DiscoveredReceiver, DiscoveryResponder};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let receivers = DiscoveredReceiver::discover(
+    ///     ("255.255.255.255", DiscoveryResponder::PORT),
+    ///     Duration::from_secs(1),
+    /// )?;
+    /// let mut wall = Blinkt::with_receivers(&receivers)?;
+    ///
+    /// wall.set_all_pixels(255, 0, 0);
+    /// wall.show()?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`TcpOutput`]: struct.TcpOutput.html
+    pub fn with_receivers(receivers: &[DiscoveredReceiver]) -> Result<Blinkt> {
+        let (first, rest) = receivers.split_first().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "no receivers were specified")
+        })?;
+
+        if let Some(receiver) = receivers
+            .iter()
+            .find(|receiver| receiver.num_pixels == 0 || receiver.num_pixels > MAX_NUM_PIXELS)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "receiver {} has an invalid number of pixels: {}",
+                    receiver.address, receiver.num_pixels
+                ),
+            )
+            .into());
+        }
+
+        let mut blinkt = Blinkt::with_driver(TcpOutput::connect(first.address)?, first.num_pixels);
+
+        for receiver in rest {
+            blinkt.chain_driver(TcpOutput::connect(receiver.address)?, receiver.num_pixels);
+        }
+
+        Ok(blinkt)
+    }
+}
+
+fn decode_reply(reply: &[u8], from: SocketAddr) -> Option<DiscoveredReceiver> {
+    if reply.len() < REPLY_HEADER_LEN || reply[..REPLY.len()] != REPLY[..] {
+        return None;
+    }
+
+    let port = u16::from_be_bytes([reply[8], reply[9]]);
+    let num_pixels = u32::from_be_bytes([reply[10], reply[11], reply[12], reply[13]]) as usize;
+    if num_pixels == 0 || num_pixels > MAX_NUM_PIXELS {
+        return None;
+    }
+
+    Some(DiscoveredReceiver {
+        address: SocketAddr::new(from.ip(), port),
+        name: String::from_utf8_lossy(&reply[REPLY_HEADER_LEN..]).into_owned(),
+        num_pixels,
+    })
+}
@@ -0,0 +1,45 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::Blinkt;
+
+// Every pixel uses three DMX channels for its red, green and blue values, so a
+// universe of 512 channels controls 170 pixels.
+pub(crate) const PIXELS_PER_UNIVERSE: usize = 170;
+
+// Copies the channel values of a universe to the pixels it's mapped onto, with
+// `start_universe` mapped onto the first pixel. Returns `false` if the universe
+// isn't mapped onto any pixels.
+pub(crate) fn apply(blinkt: &mut Blinkt, start_universe: u16, universe: u16, data: &[u8]) -> bool {
+    let offset = match universe.checked_sub(start_universe) {
+        Some(offset) => usize::from(offset) * PIXELS_PER_UNIVERSE,
+        None => return false,
+    };
+
+    if offset >= blinkt.pixels.len() {
+        return false;
+    }
+
+    for (index, rgb) in data.chunks_exact(3).take(PIXELS_PER_UNIVERSE).enumerate() {
+        blinkt.set_pixel(offset + index, rgb[0], rgb[1], rgb[2]);
+    }
+
+    true
+}
@@ -0,0 +1,426 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::{Backend, Blinkt, ColorOrder, Protocol, Result, SerialOutput};
+
+/// A custom output that sends the encoded APA102 frames to the pixels.
+///
+/// Implement `Driver` to send frames through an interface that isn't supported
+/// by this crate, or to capture frames for testing with [`MockOutput`]. Every
+/// frame sent by `show()` starts with a call to `begin_frame()`, followed by
+/// one or more calls to `write()` containing the start frame, LED frames and
+/// end frame, and ends with a call to `end_frame()`.
+///
+/// [`MockOutput`]: struct.MockOutput.html
+pub trait Driver {
+    /// Called before a frame is sent.
+    fn begin_frame(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Sends `data` to the pixels.
+    fn write(&mut self, data: &[u8]) -> Result<()>;
+
+    /// Called after a frame is sent.
+    fn end_frame(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the clock speed in Hz.
+    ///
+    /// By default, this returns `0`.
+    fn clock_speed(&self) -> u32 {
+        0
+    }
+
+    /// Sets the clock speed in Hz.
+    ///
+    /// By default, this is ignored.
+    fn set_clock_speed(&mut self, _clock_speed_hz: u32) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when the driver is added to a `Blinkt`, and whenever its color
+    /// order is changed, for drivers that decode the LED frames.
+    ///
+    /// By default, this is ignored.
+    fn set_color_order(&mut self, _color_order: ColorOrder) {}
+}
+
+// Decodes the LED frames of an APA102 frame into red, green and blue values,
+// with the brightness applied.
+pub(crate) fn decode_frame(frame: &[u8], color_order: ColorOrder) -> Vec<(u8, u8, u8)> {
+    // Skip the start frame (32*0), and decode LED frames until the end frame.
+    frame
+        .get(4..)
+        .unwrap_or_default()
+        .chunks_exact(4)
+        .take_while(|led| led[0] & 0b1110_0000 == 0b1110_0000)
+        .map(|led| {
+            let brightness = u16::from(led[0] & 0b0001_1111);
+
+            // Position of the red, green and blue values in the LED frame.
+            let mut rgb = [0u8; 3];
+            for (value, color) in led[1..].iter().zip(&color_order.arrange(0, 1, 2)) {
+                rgb[*color] = (u16::from(*value) * brightness / 31) as u8;
+            }
+
+            (rgb[0], rgb[1], rgb[2])
+        })
+        .collect()
+}
+
+// Adapts a custom driver to the internal output interface.
+pub(crate) struct DriverOutput<D> {
+    driver: D,
+    protocol: Protocol,
+}
+
+impl<D> DriverOutput<D> {
+    pub(crate) fn new(driver: D, protocol: Protocol) -> Self {
+        Self { driver, protocol }
+    }
+}
+
+impl<D: Driver> SerialOutput for DriverOutput<D> {
+    fn acquire(&mut self) -> Result<()> {
+        self.driver.begin_frame()
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.driver.write(data)
+    }
+
+    fn release(&mut self) -> Result<()> {
+        self.driver.end_frame()
+    }
+
+    fn clock_speed(&self) -> u32 {
+        self.driver.clock_speed()
+    }
+
+    fn set_clock_speed(&mut self, clock_speed_hz: u32) -> Result<()> {
+        self.driver.set_clock_speed(clock_speed_hz)
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Custom
+    }
+
+    fn set_color_order(&mut self, color_order: ColorOrder) {
+        self.driver.set_color_order(color_order);
+    }
+
+    fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+}
+
+/// An in-memory [`Driver`] that records every frame, to test animations without
+/// any hardware.
+///
+/// Clones of a `MockOutput` share the same recorded frames, so a clone can be
+/// used to inspect the frames after the original is passed to
+/// [`Blinkt::with_driver()`].
+///
+/// ```rust
+/// # use std::error::Error;
+/// #
+/// # use blinkt::{Blinkt, MockOutput};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mock = MockOutput::new();
+/// let mut blinkt = Blinkt::with_driver(mock.clone(), 2);
+///
+/// blinkt.set_pixel_rgbb(0, 255, 0, 0, 1.0);
+/// blinkt.show()?;
+///
+/// let frame = mock.last_frame().unwrap();
+/// // Start frame, followed by the LED frame of the first pixel.
+/// assert_eq!(frame[..8], [0, 0, 0, 0, 0xff, 0, 0, 255]);
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`Driver`]: trait.Driver.html
+/// [`Blinkt::with_driver()`]: struct.Blinkt.html#method.with_driver
+#[derive(Debug, Clone, Default)]
+pub struct MockOutput {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    frames: Vec<Vec<u8>>,
+    faults: Vec<(usize, MockFault)>,
+}
+
+/// A fault injected into a frame sent to a [`MockOutput`], to simulate wiring
+/// problems.
+///
+/// [`MockOutput`]: struct.MockOutput.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MockFault {
+    /// Removes the specified number of bytes from the recorded frame, starting
+    /// at the specified offset, as if they never arrived.
+    DropBytes(usize, usize),
+    /// Inverts a bit of the recorded frame, specified by the offset of the byte,
+    /// and the bit number (`0` for the least significant bit).
+    FlipBit(usize, u8),
+    /// Waits for the specified duration before the frame is sent.
+    Delay(Duration),
+    /// Fails every write of the frame with an I/O error.
+    Error,
+}
+
+impl MockOutput {
+    /// Constructs a new `MockOutput` without any recorded frames.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns all recorded frames, from oldest to newest.
+    pub fn frames(&self) -> Vec<Vec<u8>> {
+        self.lock().frames.clone()
+    }
+
+    /// Returns the most recently recorded frame.
+    pub fn last_frame(&self) -> Option<Vec<u8>> {
+        self.lock().frames.last().cloned()
+    }
+
+    /// Returns the number of recorded frames.
+    pub fn len(&self) -> usize {
+        self.lock().frames.len()
+    }
+
+    /// Returns `true` if no frames have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.lock().frames.is_empty()
+    }
+
+    /// Discards all recorded frames, and any injected faults.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.frames.clear();
+        state.faults.clear();
+    }
+
+    /// Injects a fault into the frame that will be stored at index `frame` in
+    /// `frames()`.
+    ///
+    /// Every attempt to send a frame is recorded, including frames that fail
+    /// because of an injected error, and frames that are retried by an error
+    /// handler, so faults can be targeted at specific attempts. Multiple faults
+    /// can be injected into the same frame. Faults are applied in the order they
+    /// were injected.
+    ///
+    /// ```rust
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::{Blinkt, ErrorAction, MockFault, MockOutput};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mock = MockOutput::new();
+    /// let mut blinkt = Blinkt::with_driver(mock.clone(), 1);
+    /// blinkt.set_error_handler(|_| ErrorAction::Retry);
+    ///
+    /// // The first attempt fails, and the retry has a corrupted red value.
+    /// mock.inject_fault(0, MockFault::Error);
+    /// mock.inject_fault(1, MockFault::FlipBit(7, 0));
+    ///
+    /// blinkt.set_pixel_rgbb(0, 254, 0, 0, 1.0);
+    /// blinkt.show()?;
+    ///
+    /// assert_eq!(mock.len(), 2);
+    /// assert_eq!(mock.last_frame().unwrap()[4..8], [0xff, 0, 0, 255]);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn inject_fault(&self, frame: usize, fault: MockFault) {
+        self.lock().faults.push((frame, fault));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        // A panic while recording can't leave the frames in an invalid state.
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl MockState {
+    // Returns the faults injected into the frame that's currently recorded.
+    fn faults(&self) -> impl Iterator<Item = MockFault> + '_ {
+        let frame = self.frames.len().wrapping_sub(1);
+
+        self.faults
+            .iter()
+            .filter(move |(index, _)| *index == frame)
+            .map(|(_, fault)| *fault)
+    }
+}
+
+impl Driver for MockOutput {
+    fn begin_frame(&mut self) -> Result<()> {
+        let delay = {
+            let mut state = self.lock();
+            state.frames.push(Vec::new());
+
+            // Faults for earlier frames no longer apply.
+            let frame = state.frames.len() - 1;
+            state.faults.retain(|(index, _)| *index >= frame);
+
+            state
+                .faults()
+                .map(|fault| match fault {
+                    MockFault::Delay(delay) => delay,
+                    _ => Duration::default(),
+                })
+                .sum()
+        };
+
+        thread::sleep(delay);
+
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        let mut state = self.lock();
+        if state.frames.is_empty() {
+            state.frames.push(Vec::new());
+        }
+
+        if state.faults().any(|fault| fault == MockFault::Error) {
+            return Err(io::Error::new(io::ErrorKind::Other, "injected fault").into());
+        }
+
+        if let Some(frame) = state.frames.last_mut() {
+            frame.extend_from_slice(data);
+        }
+
+        Ok(())
+    }
+
+    fn end_frame(&mut self) -> Result<()> {
+        let mut state = self.lock();
+        let faults: Vec<MockFault> = state.faults().collect();
+        let frame = match state.frames.last_mut() {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+
+        for fault in faults {
+            match fault {
+                MockFault::DropBytes(offset, len) => {
+                    let start = offset.min(frame.len());
+                    frame.drain(start..offset.saturating_add(len).min(frame.len()));
+                }
+                MockFault::FlipBit(offset, bit) => {
+                    if let Some(byte) = frame.get_mut(offset) {
+                        *byte ^= 1u8.checked_shl(u32::from(bit)).unwrap_or(0);
+                    }
+                }
+                MockFault::Delay(_) | MockFault::Error => (),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Blinkt {
+    /// Constructs a new `Blinkt` using a custom [`Driver`], with the specified
+    /// number of pixels.
+    ///
+    /// [`Driver`]: trait.Driver.html
+    pub fn with_driver<D: Driver + Send + 'static>(driver: D, num_pixels: usize) -> Self {
+        Self::with_output(
+            Box::new(DriverOutput::new(driver, Protocol::Apa102)),
+            num_pixels,
+        )
+    }
+
+    /// Adds pixels to the end of the local buffer, which are sent through a
+    /// custom [`Driver`].
+    ///
+    /// See `chain_gpio()` for details on chaining.
+    ///
+    /// [`Driver`]: trait.Driver.html
+    pub fn chain_driver<D: Driver + Send + 'static>(&mut self, driver: D, num_pixels: usize) {
+        self.chain_output(
+            Box::new(DriverOutput::new(driver, Protocol::Apa102)),
+            num_pixels,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    fn send(mock: &mut MockOutput, data: &[u8]) -> Result<()> {
+        mock.begin_frame()?;
+        mock.write(data)?;
+        mock.end_frame()
+    }
+
+    #[test]
+    fn mock_faults_apply_to_their_frame() {
+        let mut mock = MockOutput::new();
+        mock.inject_fault(0, MockFault::DropBytes(1, 2));
+        mock.inject_fault(1, MockFault::FlipBit(0, 7));
+        mock.inject_fault(1, MockFault::DropBytes(3, 10));
+        mock.inject_fault(2, MockFault::Error);
+
+        send(&mut mock, &[1, 2, 3, 4]).unwrap();
+        send(&mut mock, &[1, 2, 3, 4]).unwrap();
+        assert!(send(&mut mock, &[1, 2, 3, 4]).is_err());
+        send(&mut mock, &[1, 2, 3, 4]).unwrap();
+
+        assert_eq!(
+            mock.frames(),
+            [vec![1, 4], vec![0x81, 2, 3], vec![], vec![1, 2, 3, 4]]
+        );
+    }
+
+    #[test]
+    fn mock_delay() {
+        let mut mock = MockOutput::new();
+        mock.inject_fault(0, MockFault::Delay(Duration::from_millis(20)));
+
+        let started = Instant::now();
+        send(&mut mock, &[0]).unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(20));
+
+        // Faults are discarded by clear().
+        mock.clear();
+        let started = Instant::now();
+        send(&mut mock, &[0]).unwrap();
+        assert!(started.elapsed() < Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,1076 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Effects that render animations to the local buffer, and expose their
+//! tunable parameters.
+//!
+//! Every [`Effect`] describes its parameters through [`Parameter`], which
+//! contains the name, current [`Value`], and for numeric parameters the
+//! supported range. This allows control surfaces to build a user interface
+//! for any effect without knowing its type, and to change parameters by name
+//! through `set_parameter()`.
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! #
+//! # use blinkt::{Blinkt, FrameClock};
+//! use blinkt::effect::{Effect, Rainbow, Value};
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let mut blinkt = Blinkt::new()?;
+//! let mut clock = FrameClock::new();
+//! let mut effect = Rainbow::new();
+//!
+//! for parameter in effect.parameters() {
+//!     println!("{}: {:?} {:?}", parameter.name(), parameter.value(), parameter.range());
+//! }
+//!
+//! effect.set_parameter("speed", Value::Float(0.5));
+//!
+//! loop {
+//!     clock.tick();
+//!
+//!     blinkt.render(&mut effect, clock.elapsed());
+//!     blinkt.show()?;
+//! }
+//! # }
+//! ```
+
+use std::ops::Range;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{morse, wave, Blinkt, Pixel, Result};
+
+/// The value of an effect parameter.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Value {
+    /// A floating point value.
+    Float(f32),
+    /// An on/off value.
+    Bool(bool),
+    /// A tuple containing red, green and blue values, specified as 8-bit values
+    /// between `0` (0%) and `255` (100%).
+    Color(u8, u8, u8),
+}
+
+impl Value {
+    /// Returns the floating point value, or `None` if this isn't a `Float`.
+    pub fn as_float(&self) -> Option<f32> {
+        match *self {
+            Value::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the on/off value, or `None` if this isn't a `Bool`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a tuple containing the red, green and blue values, or `None` if
+    /// this isn't a `Color`.
+    pub fn as_color(&self) -> Option<(u8, u8, u8)> {
+        match *self {
+            Value::Color(red, green, blue) => Some((red, green, blue)),
+            _ => None,
+        }
+    }
+}
+
+/// A description of a tunable effect parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    name: String,
+    value: Value,
+    range: Option<(f32, f32)>,
+}
+
+impl Parameter {
+    /// Constructs a new floating point `Parameter`, with a value between `min`
+    /// and `max`.
+    pub fn float(name: &str, value: f32, min: f32, max: f32) -> Self {
+        Self {
+            name: name.to_owned(),
+            value: Value::Float(value),
+            range: Some((min, max)),
+        }
+    }
+
+    /// Constructs a new on/off `Parameter`.
+    pub fn bool(name: &str, value: bool) -> Self {
+        Self {
+            name: name.to_owned(),
+            value: Value::Bool(value),
+            range: None,
+        }
+    }
+
+    /// Constructs a new color `Parameter`.
+    pub fn color(name: &str, red: u8, green: u8, blue: u8) -> Self {
+        Self {
+            name: name.to_owned(),
+            value: Value::Color(red, green, blue),
+            range: None,
+        }
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the current value.
+    pub fn value(&self) -> Value {
+        self.value
+    }
+
+    /// Returns a tuple containing the minimum and maximum value of a floating
+    /// point parameter, or `None` for other parameters.
+    pub fn range(&self) -> Option<(f32, f32)> {
+        self.range
+    }
+}
+
+/// An animation that renders to a range of pixels.
+pub trait Effect {
+    /// Returns the name of the effect.
+    fn name(&self) -> &str;
+
+    /// Renders the effect at the specified elapsed time, usually provided by
+    /// [`FrameClock::elapsed()`].
+    ///
+    /// [`FrameClock::elapsed()`]: ../struct.FrameClock.html#method.elapsed
+    fn render(&mut self, elapsed: Duration, pixels: &mut [Pixel]);
+
+    /// Returns the tunable parameters and their current values.
+    ///
+    /// By default, an effect doesn't have any parameters.
+    fn parameters(&self) -> Vec<Parameter> {
+        Vec::new()
+    }
+
+    /// Sets the parameter named `name` to `value`. Floating point values are
+    /// clamped to the range of the parameter. Returns `true` if the parameter
+    /// exists and `value` has the correct type.
+    fn set_parameter(&mut self, _name: &str, _value: Value) -> bool {
+        false
+    }
+}
+
+/// Sets all pixels to the same color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solid {
+    color: (u8, u8, u8),
+    brightness: f32,
+}
+
+impl Solid {
+    /// Constructs a new `Solid` effect with the specified color.
+    pub fn new(red: u8, green: u8, blue: u8) -> Self {
+        Self {
+            color: (red, green, blue),
+            brightness: 1.0,
+        }
+    }
+}
+
+impl Default for Solid {
+    fn default() -> Self {
+        Self::new(255, 255, 255)
+    }
+}
+
+impl Effect for Solid {
+    fn name(&self) -> &str {
+        "solid"
+    }
+
+    fn render(&mut self, _elapsed: Duration, pixels: &mut [Pixel]) {
+        let (red, green, blue) = self.color;
+
+        for pixel in pixels {
+            pixel.set_rgbb(red, green, blue, self.brightness);
+        }
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let (red, green, blue) = self.color;
+
+        vec![
+            Parameter::color("color", red, green, blue),
+            Parameter::float("brightness", self.brightness, 0.0, 1.0),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: Value) -> bool {
+        match (name, value) {
+            ("color", Value::Color(red, green, blue)) => self.color = (red, green, blue),
+            ("brightness", Value::Float(brightness)) => {
+                self.brightness = brightness.clamp(0.0, 1.0)
+            }
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+/// Cycles all pixels through the hues of a rainbow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rainbow {
+    speed: f32,
+    spread: f32,
+    brightness: f32,
+}
+
+impl Rainbow {
+    /// Constructs a new `Rainbow` effect, cycling through all hues once every
+    /// ten seconds, with a single rainbow spread out over all pixels.
+    pub fn new() -> Self {
+        Self {
+            speed: 0.1,
+            spread: 1.0,
+            brightness: 1.0,
+        }
+    }
+}
+
+impl Default for Rainbow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for Rainbow {
+    fn name(&self) -> &str {
+        "rainbow"
+    }
+
+    fn render(&mut self, elapsed: Duration, pixels: &mut [Pixel]) {
+        let offset = elapsed.as_secs_f32() * self.speed * 360.0;
+        let len = pixels.len().max(1) as f32;
+
+        for (index, pixel) in pixels.iter_mut().enumerate() {
+            let hue = offset + index as f32 / len * self.spread * 360.0;
+
+            pixel.set_hsv(hue, 1.0, 1.0);
+            pixel.set_brightness(self.brightness);
+        }
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![
+            Parameter::float("speed", self.speed, 0.0, 10.0),
+            Parameter::float("spread", self.spread, 0.0, 10.0),
+            Parameter::float("brightness", self.brightness, 0.0, 1.0),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: Value) -> bool {
+        match (name, value) {
+            ("speed", Value::Float(speed)) => self.speed = speed.clamp(0.0, 10.0),
+            ("spread", Value::Float(spread)) => self.spread = spread.clamp(0.0, 10.0),
+            ("brightness", Value::Float(brightness)) => {
+                self.brightness = brightness.clamp(0.0, 1.0)
+            }
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+/// Fades all pixels in and out with a smooth sine wave.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breathe {
+    color: (u8, u8, u8),
+    period: f32,
+    min_brightness: f32,
+}
+
+impl Breathe {
+    /// Constructs a new `Breathe` effect with the specified color, and a period
+    /// of four seconds.
+    pub fn new(red: u8, green: u8, blue: u8) -> Self {
+        Self {
+            color: (red, green, blue),
+            period: 4.0,
+            min_brightness: 0.0,
+        }
+    }
+}
+
+impl Default for Breathe {
+    fn default() -> Self {
+        Self::new(255, 255, 255)
+    }
+}
+
+impl Effect for Breathe {
+    fn name(&self) -> &str {
+        "breathe"
+    }
+
+    fn render(&mut self, elapsed: Duration, pixels: &mut [Pixel]) {
+        let (red, green, blue) = self.color;
+        let level = wave::sine(elapsed, 1.0 / self.period, 0.0);
+        let brightness = self.min_brightness + (1.0 - self.min_brightness) * level;
+
+        for pixel in pixels {
+            pixel.set_rgbb(red, green, blue, brightness);
+        }
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let (red, green, blue) = self.color;
+
+        vec![
+            Parameter::color("color", red, green, blue),
+            Parameter::float("period", self.period, 0.1, 60.0),
+            Parameter::float("min_brightness", self.min_brightness, 0.0, 1.0),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: Value) -> bool {
+        match (name, value) {
+            ("color", Value::Color(red, green, blue)) => self.color = (red, green, blue),
+            ("period", Value::Float(period)) => self.period = period.clamp(0.1, 60.0),
+            ("min_brightness", Value::Float(brightness)) => {
+                self.min_brightness = brightness.clamp(0.0, 1.0)
+            }
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+type PlaylistEntry = (Box<dyn Effect + Send>, Duration);
+
+/// Cycles through a list of effects, with a crossfade between effects.
+///
+/// Every effect is shown for its own duration, either in the order they were
+/// added, or in random order when shuffle is enabled. `Playlist` is an
+/// [`Effect`] itself, and forwards `parameters()` and `set_parameter()` to the
+/// effect that's currently shown.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// # use std::time::Duration;
+/// #
+/// # use blinkt::{Blinkt, FrameClock};
+/// use blinkt::effect::{Breathe, Playlist, Rainbow};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::new()?;
+/// let mut clock = FrameClock::new();
+/// let mut playlist = Playlist::new();
+///
+/// playlist.add(Box::new(Rainbow::new()), Duration::from_secs(30));
+/// playlist.add(Box::new(Breathe::new(255, 0, 128)), Duration::from_secs(10));
+/// playlist.set_crossfade(Duration::from_secs(2));
+/// playlist.set_shuffle(true);
+///
+/// loop {
+///     clock.tick();
+///
+///     blinkt.render(&mut playlist, clock.elapsed());
+///     blinkt.show()?;
+/// }
+/// # }
+/// ```
+pub struct Playlist {
+    entries: Vec<PlaylistEntry>,
+    crossfade: Duration,
+    shuffle: bool,
+    current: usize,
+    next: usize,
+    started: Option<Duration>,
+    skip: bool,
+    rng: u64,
+    buffer: Vec<Pixel>,
+}
+
+impl Playlist {
+    /// Constructs a new, empty `Playlist`.
+    pub fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+
+        Self {
+            entries: Vec::new(),
+            crossfade: Duration::from_secs(1),
+            shuffle: false,
+            current: 0,
+            next: 0,
+            started: None,
+            skip: false,
+            rng: seed | 1,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Adds `effect` to the end of the playlist, shown for the specified duration.
+    pub fn add(&mut self, effect: Box<dyn Effect + Send>, duration: Duration) {
+        self.entries.push((effect, duration));
+        self.next = self.pick_next();
+    }
+
+    /// Returns the number of effects.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the playlist doesn't contain any effects.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the index of the effect that's currently shown, or `None` if the
+    /// playlist is empty.
+    pub fn current(&self) -> Option<usize> {
+        if self.entries.is_empty() {
+            None
+        } else {
+            Some(self.current)
+        }
+    }
+
+    /// Returns the duration of the crossfade between two effects.
+    pub fn crossfade(&self) -> Duration {
+        self.crossfade
+    }
+
+    /// Sets the duration of the crossfade between two effects. The crossfade
+    /// starts before the end of the current effect, and is limited to its
+    /// duration.
+    ///
+    /// By default, this is set to 1 second.
+    pub fn set_crossfade(&mut self, crossfade: Duration) {
+        self.crossfade = crossfade;
+    }
+
+    /// Returns `true` if shuffle is enabled.
+    pub fn is_shuffle(&self) -> bool {
+        self.shuffle
+    }
+
+    /// When enabled, effects are shown in random order. The same effect is never
+    /// shown twice in a row, unless it's the only effect.
+    ///
+    /// By default, this is set to `false`.
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+        self.next = self.pick_next();
+    }
+
+    /// Switches to the next effect on the next call to `render()`, without a
+    /// crossfade.
+    pub fn skip(&mut self) {
+        self.skip = true;
+    }
+
+    fn advance(&mut self) {
+        self.current = self.next;
+        self.next = self.pick_next();
+    }
+
+    fn pick_next(&mut self) -> usize {
+        let len = self.entries.len();
+
+        if len < 2 {
+            0
+        } else if self.shuffle {
+            // xorshift64
+            self.rng ^= self.rng << 13;
+            self.rng ^= self.rng >> 7;
+            self.rng ^= self.rng << 17;
+
+            (self.current + 1 + (self.rng % (len as u64 - 1)) as usize) % len
+        } else {
+            (self.current + 1) % len
+        }
+    }
+}
+
+impl Default for Playlist {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for Playlist {
+    fn name(&self) -> &str {
+        "playlist"
+    }
+
+    fn render(&mut self, elapsed: Duration, pixels: &mut [Pixel]) {
+        if self.entries.is_empty() {
+            return;
+        }
+
+        let mut started = *self.started.get_or_insert(elapsed);
+        if self.skip {
+            self.skip = false;
+            self.advance();
+            started = elapsed;
+        }
+
+        // Skip any effects that ended since the last frame. If all effects have
+        // a zero duration, the current effect is shown indefinitely.
+        let total: Duration = self.entries.iter().map(|(_, duration)| *duration).sum();
+        while total > Duration::default()
+            && elapsed.saturating_sub(started) >= self.entries[self.current].1
+        {
+            started += self.entries[self.current].1;
+            self.advance();
+        }
+        self.started = Some(started);
+
+        let duration = self.entries[self.current].1;
+        let remaining = duration.saturating_sub(elapsed.saturating_sub(started));
+        let crossfade = self.crossfade.min(duration);
+
+        self.entries[self.current].0.render(elapsed, pixels);
+
+        if remaining >= crossfade || self.next == self.current {
+            return;
+        }
+
+        let amount = 1.0 - remaining.as_secs_f32() / crossfade.as_secs_f32();
+        self.buffer.clear();
+        self.buffer.extend_from_slice(pixels);
+        self.entries[self.next].0.render(elapsed, &mut self.buffer);
+
+        for (pixel, next) in pixels.iter_mut().zip(&self.buffer) {
+            let (red, green, blue, brightness) = pixel.rgbb();
+            let (next_red, next_green, next_blue, next_brightness) = next.rgbb();
+            let mix = |from: u8, to: u8| {
+                (f32::from(from) + (f32::from(to) - f32::from(from)) * amount).round() as u8
+            };
+
+            pixel.set_rgbb(
+                mix(red, next_red),
+                mix(green, next_green),
+                mix(blue, next_blue),
+                brightness + (next_brightness - brightness) * amount,
+            );
+        }
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        self.entries
+            .get(self.current)
+            .map_or_else(Vec::new, |(effect, _)| effect.parameters())
+    }
+
+    fn set_parameter(&mut self, name: &str, value: Value) -> bool {
+        self.entries
+            .get_mut(self.current)
+            .map_or(false, |(effect, _)| effect.set_parameter(name, value))
+    }
+}
+
+type SegmentEntry = (Range<usize>, Box<dyn Effect + Send>);
+
+/// Renders different effects to different segments of the local buffer.
+///
+/// Every segment covers a range of pixels, and runs its own effect. Segments
+/// are rendered in the order they were added, so later segments take priority
+/// where segments overlap. Pixels that aren't covered by any segment are left
+/// unchanged.
+///
+/// `Segments` is an [`Effect`] itself. The parameters of each effect are
+/// exposed with the index of their segment as a prefix, so `0/speed` refers to
+/// the `speed` parameter of the effect in the first segment.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// #
+/// # use blinkt::{Blinkt, FrameClock};
+/// use blinkt::effect::{Breathe, Rainbow, Segments};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::new()?;
+/// let mut clock = FrameClock::new();
+/// let mut segments = Segments::new();
+///
+/// segments.add(0..4, Box::new(Breathe::new(0, 0, 255)));
+/// segments.add(4..8, Box::new(Rainbow::new()));
+///
+/// loop {
+///     clock.tick();
+///
+///     blinkt.render(&mut segments, clock.elapsed());
+///     blinkt.show()?;
+/// }
+/// # }
+/// ```
+#[derive(Default)]
+pub struct Segments {
+    segments: Vec<SegmentEntry>,
+}
+
+impl Segments {
+    /// Constructs a new `Segments` effect without any segments.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a segment that renders `effect` to the specified range of pixels.
+    /// Returns the index of the segment.
+    pub fn add(&mut self, pixels: Range<usize>, effect: Box<dyn Effect + Send>) -> usize {
+        self.segments.push((pixels, effect));
+        self.segments.len() - 1
+    }
+
+    /// Replaces the effect of the segment at `index`. Returns `false` if the
+    /// segment doesn't exist.
+    pub fn set_effect(&mut self, index: usize, effect: Box<dyn Effect + Send>) -> bool {
+        match self.segments.get_mut(index) {
+            Some(segment) => {
+                segment.1 = effect;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the number of segments.
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns `true` if there aren't any segments.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl Effect for Segments {
+    fn name(&self) -> &str {
+        "segments"
+    }
+
+    fn render(&mut self, elapsed: Duration, pixels: &mut [Pixel]) {
+        for (range, effect) in &mut self.segments {
+            let end = range.end.min(pixels.len());
+            let start = range.start.min(end);
+
+            effect.render(elapsed, &mut pixels[start..end]);
+        }
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let mut parameters = Vec::new();
+
+        for (index, (_, effect)) in self.segments.iter().enumerate() {
+            parameters.extend(effect.parameters().into_iter().map(|mut parameter| {
+                parameter.name = format!("{}/{}", index, parameter.name);
+                parameter
+            }));
+        }
+
+        parameters
+    }
+
+    fn set_parameter(&mut self, name: &str, value: Value) -> bool {
+        let (index, name) = match name.split_once('/') {
+            Some((index, name)) => (index, name),
+            None => return false,
+        };
+
+        match index
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| self.segments.get_mut(index))
+        {
+            Some((_, effect)) => effect.set_parameter(name, value),
+            None => false,
+        }
+    }
+}
+
+/// The way text is encoded by a [`Ticker`].
+///
+/// [`Ticker`]: struct.Ticker.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TickerEncoding {
+    /// Encodes text as Morse code, with one pixel for every unit. A dot is a
+    /// single lit pixel, and a dash three lit pixels.
+    Morse,
+    /// Encodes every letter as a single pixel with its own color. Letters are
+    /// spread out over the hues of a rainbow, from red for `A` to purple for `Z`.
+    /// Digits use the same hues at half saturation, from red for `0` to purple
+    /// for `9`.
+    Color,
+}
+
+/// Scrolls text along the strip, encoded as Morse code or color codes.
+///
+/// The text enters at the last pixel and scrolls towards the first pixel,
+/// followed by a blank strip before it starts over.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// #
+/// # use blinkt::{Blinkt, FrameClock};
+/// use blinkt::effect::{Ticker, TickerEncoding};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::new()?;
+/// let mut clock = FrameClock::new();
+/// let mut ticker = Ticker::new("Disk full", TickerEncoding::Morse);
+///
+/// loop {
+///     clock.tick();
+///
+///     blinkt.render(&mut ticker, clock.elapsed());
+///     blinkt.show()?;
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ticker {
+    encoding: TickerEncoding,
+    pattern: Vec<Option<(u8, u8, u8)>>,
+    color: (u8, u8, u8),
+    speed: f32,
+    brightness: f32,
+}
+
+impl Ticker {
+    /// Constructs a new `Ticker` for `text`, scrolling at 4 pixels per second.
+    ///
+    /// Unsupported characters are skipped. Morse code is shown in white, which
+    /// can be changed through the `color` parameter. The color has no effect on
+    /// color codes.
+    pub fn new(text: &str, encoding: TickerEncoding) -> Self {
+        let mut ticker = Self {
+            encoding,
+            pattern: Vec::new(),
+            color: (255, 255, 255),
+            speed: 4.0,
+            brightness: 1.0,
+        };
+
+        ticker.set_text(text);
+        ticker
+    }
+
+    /// Replaces the text.
+    pub fn set_text(&mut self, text: &str) {
+        self.pattern = match self.encoding {
+            TickerEncoding::Morse => morse::encode(text)
+                .into_iter()
+                .map(|on| if on { Some(self.color) } else { None })
+                .collect(),
+            TickerEncoding::Color => color_codes(text),
+        };
+    }
+
+    /// Returns the number of pixels taken up by the encoded text.
+    pub fn len(&self) -> usize {
+        self.pattern.len()
+    }
+
+    /// Returns `true` if the text doesn't contain any supported characters.
+    pub fn is_empty(&self) -> bool {
+        self.pattern.is_empty()
+    }
+}
+
+// Encodes every letter and digit as a single pixel, separated by one blank
+// pixel, with three blank pixels between words.
+fn color_codes(text: &str) -> Vec<Option<(u8, u8, u8)>> {
+    let mut pattern = Vec::new();
+    let mut gap = 0;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !pattern.is_empty() {
+                gap = 3;
+            }
+
+            continue;
+        }
+
+        let c = c.to_ascii_uppercase();
+        let (hue, saturation) = match c {
+            'A'..='Z' => (f32::from(c as u8 - b'A') * 300.0 / 25.0, 1.0),
+            '0'..='9' => (f32::from(c as u8 - b'0') * 300.0 / 9.0, 0.5),
+            _ => continue,
+        };
+
+        let mut pixel = Pixel::default();
+        pixel.set_hsv(hue, saturation, 1.0);
+
+        pattern.resize(pattern.len() + gap, None);
+        pattern.push(Some(pixel.rgb()));
+        gap = 1;
+    }
+
+    pattern
+}
+
+impl Effect for Ticker {
+    fn name(&self) -> &str {
+        "ticker"
+    }
+
+    fn render(&mut self, elapsed: Duration, pixels: &mut [Pixel]) {
+        let len = pixels.len();
+        let cycle = (self.pattern.len() + len) as u64;
+        let offset = (elapsed.as_secs_f64() * f64::from(self.speed)) as u64 % cycle.max(1);
+
+        for (index, pixel) in pixels.iter_mut().enumerate() {
+            let position = ((index as u64 + offset) % cycle.max(1)) as usize;
+            let color = position
+                .checked_sub(len)
+                .and_then(|position| self.pattern[position]);
+
+            // Morse code is always shown in the current color.
+            match (color, self.encoding) {
+                (Some(_), TickerEncoding::Morse) => {
+                    let (red, green, blue) = self.color;
+                    pixel.set_rgbb(red, green, blue, self.brightness);
+                }
+                (Some((red, green, blue)), _) => pixel.set_rgbb(red, green, blue, self.brightness),
+                (None, _) => pixel.clear(),
+            }
+        }
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let (red, green, blue) = self.color;
+
+        vec![
+            Parameter::color("color", red, green, blue),
+            Parameter::float("speed", self.speed, 0.0, 100.0),
+            Parameter::float("brightness", self.brightness, 0.0, 1.0),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: Value) -> bool {
+        match (name, value) {
+            ("color", Value::Color(red, green, blue)) => self.color = (red, green, blue),
+            ("speed", Value::Float(speed)) => self.speed = speed.clamp(0.0, 100.0),
+            ("brightness", Value::Float(brightness)) => {
+                self.brightness = brightness.clamp(0.0, 1.0)
+            }
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+/// Blinks all pixels in Morse code.
+///
+/// The timing is based on the standard word "PARIS", so a dot lasts
+/// `1.2 / wpm` seconds at the specified number of words per minute. When
+/// repeat is enabled, the message starts over after a gap of seven units.
+/// See [`Blinkt::play_morse()`] to play a message once without setting up a
+/// render loop.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// #
+/// # use blinkt::{Blinkt, FrameClock};
+/// use blinkt::effect::Morse;
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::new()?;
+/// let mut clock = FrameClock::new();
+/// let mut morse = Morse::new("SOS", 12.0, (255, 0, 0));
+///
+/// loop {
+///     clock.tick();
+///
+///     blinkt.render(&mut morse, clock.elapsed());
+///     blinkt.show()?;
+/// }
+/// # }
+/// ```
+///
+/// [`Blinkt::play_morse()`]: ../struct.Blinkt.html#method.play_morse
+#[derive(Debug, Clone, PartialEq)]
+pub struct Morse {
+    units: Vec<bool>,
+    wpm: f32,
+    color: (u8, u8, u8),
+    brightness: f32,
+    repeat: bool,
+}
+
+impl Morse {
+    /// Constructs a new `Morse` effect for `text`, at the specified number of
+    /// words per minute.
+    ///
+    /// `color` contains the red, green and blue values, specified as 8-bit values
+    /// between `0` (0%) and `255` (100%). Unsupported characters are skipped.
+    /// Repeat is enabled by default.
+    pub fn new(text: &str, wpm: f32, color: (u8, u8, u8)) -> Self {
+        Self {
+            units: morse::encode(text),
+            wpm: wpm.clamp(1.0, 60.0),
+            color,
+            brightness: 1.0,
+            repeat: true,
+        }
+    }
+
+    /// Replaces the text.
+    pub fn set_text(&mut self, text: &str) {
+        self.units = morse::encode(text);
+    }
+
+    /// Returns `true` if repeat is enabled.
+    pub fn is_repeat(&self) -> bool {
+        self.repeat
+    }
+
+    /// When enabled, the message starts over once it's complete.
+    pub fn set_repeat(&mut self, repeat: bool) {
+        self.repeat = repeat;
+    }
+
+    /// Returns the duration of a single unit.
+    pub fn unit(&self) -> Duration {
+        Duration::from_secs_f32(1.2 / self.wpm)
+    }
+
+    /// Returns the duration of the message, excluding the gap before it's
+    /// repeated.
+    pub fn duration(&self) -> Duration {
+        self.unit() * self.units.len() as u32
+    }
+
+    /// Returns `true` if the message is complete at the specified elapsed time.
+    /// A repeating message is never complete.
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        !self.repeat && elapsed >= self.duration()
+    }
+
+    // Returns whether the signal is on at the specified elapsed time.
+    fn is_on(&self, elapsed: Duration) -> bool {
+        let unit = (elapsed.as_secs_f64() / self.unit().as_secs_f64()) as usize;
+        let unit = if self.repeat {
+            unit % (self.units.len() + 7)
+        } else {
+            unit
+        };
+
+        self.units.get(unit).copied().unwrap_or(false)
+    }
+}
+
+impl Effect for Morse {
+    fn name(&self) -> &str {
+        "morse"
+    }
+
+    fn render(&mut self, elapsed: Duration, pixels: &mut [Pixel]) {
+        let (red, green, blue) = self.color;
+        let on = self.is_on(elapsed);
+
+        for pixel in pixels {
+            if on {
+                pixel.set_rgbb(red, green, blue, self.brightness);
+            } else {
+                pixel.clear();
+            }
+        }
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let (red, green, blue) = self.color;
+
+        vec![
+            Parameter::color("color", red, green, blue),
+            Parameter::float("wpm", self.wpm, 1.0, 60.0),
+            Parameter::float("brightness", self.brightness, 0.0, 1.0),
+            Parameter::bool("repeat", self.repeat),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: Value) -> bool {
+        match (name, value) {
+            ("color", Value::Color(red, green, blue)) => self.color = (red, green, blue),
+            ("wpm", Value::Float(wpm)) => self.wpm = wpm.clamp(1.0, 60.0),
+            ("brightness", Value::Float(brightness)) => {
+                self.brightness = brightness.clamp(0.0, 1.0)
+            }
+            ("repeat", Value::Bool(repeat)) => self.repeat = repeat,
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+impl Blinkt {
+    /// Renders `effect` to the local buffer at the specified elapsed time.
+    pub fn render(&mut self, effect: &mut dyn Effect, elapsed: Duration) {
+        effect.render(elapsed, &mut self.pixels);
+        self.notify_change();
+    }
+
+    /// Blinks all pixels in Morse code, and blocks until the message is complete.
+    ///
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    /// The timing is based on the specified number of words per minute. See
+    /// [`Morse`] for details. The local buffer is restored and sent once the
+    /// message is complete.
+    ///
+    /// [`Morse`]: effect/struct.Morse.html
+    pub fn play_morse(&mut self, text: &str, wpm: f32, red: u8, green: u8, blue: u8) -> Result<()> {
+        let mut morse = Morse::new(text, wpm, (red, green, blue));
+        morse.set_repeat(false);
+
+        let pixels = self.pixels.clone();
+        let unit = morse.unit();
+        let mut start = 0;
+
+        // Only send a frame when the signal changes, rendered halfway through
+        // the first unit to avoid rounding errors.
+        while let Some(&on) = morse.units.get(start) {
+            let run = morse.units[start..]
+                .iter()
+                .take_while(|unit| **unit == on)
+                .count();
+
+            self.render(&mut morse, unit * start as u32 + unit / 2);
+            self.show()?;
+
+            thread::sleep(unit * run as u32);
+            start += run;
+        }
+
+        self.pixels = pixels;
+        self.notify_change();
+        self.show()
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::{end_frame_len, ColorOrder, Correction, Pixel};
+
+/// Encodes pixels into the data sent to APA102 or SK9822 LED strips.
+///
+/// `FrameEncoder` performs the same encoding as `Blinkt::show()`, but returns
+/// the encoded frame instead of sending it. This can be used to send frames
+/// through a custom transport, or to combine the pixels of several logical
+/// strips into a single transfer on daisy-chained hardware. A frame consists
+/// of a start frame, 4 bytes for every pixel, and an end frame.
+///
+/// ```rust
+/// use blinkt::{FrameEncoder, Pixel};
+///
+/// let mut encoder = FrameEncoder::new();
+/// let frame = encoder.encode(&[Pixel::default(); 8]);
+///
+/// assert_eq!(frame.len(), 4 + 8 * 4 + 5);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FrameEncoder {
+    correction: Correction,
+    color_order: ColorOrder,
+    end_frame_len: Option<usize>,
+    buffer: Vec<u8>,
+}
+
+impl FrameEncoder {
+    /// Constructs a new `FrameEncoder` that doesn't apply any color correction.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the color correction applied to all pixels.
+    pub fn correction(&self) -> &Correction {
+        &self.correction
+    }
+
+    /// Sets the color correction applied to all pixels.
+    ///
+    /// By default, all values are left unchanged.
+    pub fn set_correction(&mut self, correction: Correction) {
+        self.correction = correction;
+    }
+
+    /// Returns the order in which the red, green and blue values are encoded.
+    pub fn color_order(&self) -> ColorOrder {
+        self.color_order
+    }
+
+    /// Sets the order in which the red, green and blue values are encoded.
+    ///
+    /// By default, this is set to `ColorOrder::Bgr`.
+    pub fn set_color_order(&mut self, color_order: ColorOrder) {
+        self.color_order = color_order;
+    }
+
+    /// Returns the overridden length in bytes of the end frame.
+    pub fn end_frame_len(&self) -> Option<usize> {
+        self.end_frame_len
+    }
+
+    /// Overrides the length in bytes of the end frame. Set to `None` to calculate
+    /// the length based on the number of pixels, the same way as
+    /// [`Blinkt::end_frame_len()`].
+    ///
+    /// By default, this is set to `None`.
+    ///
+    /// [`Blinkt::end_frame_len()`]: struct.Blinkt.html#method.end_frame_len
+    pub fn set_end_frame_len(&mut self, end_frame_len: Option<usize>) {
+        self.end_frame_len = end_frame_len;
+    }
+
+    /// Encodes `pixels` into a complete frame.
+    ///
+    /// The returned slice is valid until the next call to `encode()`, which
+    /// reuses the same buffer.
+    pub fn encode(&mut self, pixels: &[Pixel]) -> &[u8] {
+        let end_frame_len = self
+            .end_frame_len
+            .unwrap_or_else(|| end_frame_len(pixels.len()));
+
+        self.buffer.clear();
+        self.buffer.reserve(4 + pixels.len() * 4 + end_frame_len);
+
+        // Start frame (32*0).
+        self.buffer.extend_from_slice(&[0u8; 4]);
+
+        // LED frames (3*1, 5*brightness, 8*blue, 8*green, 8*red).
+        for pixel in pixels {
+            self.buffer
+                .extend_from_slice(&self.correction.apply(pixel).bytes_ordered(self.color_order));
+        }
+
+        // End frame (8*0 for every 16 pixels, 32*0 SK9822 reset frame).
+        self.buffer.resize(self.buffer.len() + end_frame_len, 0);
+
+        &self.buffer
+    }
+}
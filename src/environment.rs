@@ -0,0 +1,293 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::ffi::CString;
+use std::fs;
+use std::path::Path;
+
+use crate::Blinkt;
+
+const PATH_GROUPS: &str = "/etc/group";
+const PATH_GPIOMEM: &str = "/dev/gpiomem";
+const PATH_SPIDEV: &str = "/dev/spidev0.0";
+const PATHS_CONFIG: [&str; 2] = ["/boot/firmware/config.txt", "/boot/config.txt"];
+const PATHS_UDEV_RULES: [&str; 3] = [
+    "/etc/udev/rules.d",
+    "/lib/udev/rules.d",
+    "/usr/lib/udev/rules.d",
+];
+
+/// The result of a single check performed by [`Blinkt::check_environment()`].
+///
+/// [`Blinkt::check_environment()`]: struct.Blinkt.html#method.check_environment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvironmentCheck {
+    name: String,
+    passed: bool,
+    details: String,
+    remedy: Option<String>,
+}
+
+impl EnvironmentCheck {
+    fn pass(name: &str, details: String) -> Self {
+        Self {
+            name: name.to_owned(),
+            passed: true,
+            details,
+            remedy: None,
+        }
+    }
+
+    fn fail(name: &str, details: String, remedy: String) -> Self {
+        Self {
+            name: name.to_owned(),
+            passed: false,
+            details,
+            remedy: Some(remedy),
+        }
+    }
+
+    /// Returns the name of the check.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `true` if the check passed.
+    pub fn passed(&self) -> bool {
+        self.passed
+    }
+
+    /// Returns a description of what was found.
+    pub fn details(&self) -> &str {
+        &self.details
+    }
+
+    /// Returns the steps needed to fix the problem, or `None` if the check
+    /// passed.
+    pub fn remedy(&self) -> Option<&str> {
+        self.remedy.as_deref()
+    }
+}
+
+impl Blinkt {
+    /// Checks the system for common configuration problems that prevent access to
+    /// the GPIO and SPI peripherals, without accessing any hardware.
+    ///
+    /// `check_environment()` verifies whether the current user is a member of the
+    /// `gpio` and `spi` groups, whether the GPIO and SPI device nodes exist and
+    /// are accessible, whether SPI is enabled in the firmware configuration, and
+    /// whether udev rules are installed to set the device node permissions. Every
+    /// failed check includes the steps needed to fix the problem.
+    ///
+    /// ```rust,no_run
+    /// # use blinkt::Blinkt;
+    /// #
+    /// for check in Blinkt::check_environment() {
+    ///     println!("[{}] {}: {}", if check.passed() { "ok" } else { "!!" }, check.name(), check.details());
+    ///
+    ///     if let Some(remedy) = check.remedy() {
+    ///         println!("     {}", remedy);
+    ///     }
+    /// }
+    /// ```
+    pub fn check_environment() -> Vec<EnvironmentCheck> {
+        vec![
+            check_group("gpio"),
+            check_group("spi"),
+            check_device(PATH_GPIOMEM, "gpio"),
+            check_device(PATH_SPIDEV, "spi"),
+            check_spi_overlay(),
+            check_udev_rules(),
+        ]
+    }
+}
+
+fn is_root() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+// Returns the group ID for `name`, based on the group database.
+fn group_id(name: &str) -> Option<u32> {
+    fs::read_to_string(PATH_GROUPS)
+        .ok()?
+        .lines()
+        .find_map(|line| {
+            let mut fields = line.split(':');
+            if fields.next()? != name {
+                return None;
+            }
+
+            fields.nth(1)?.parse().ok()
+        })
+}
+
+// Returns the group IDs of the current process.
+fn process_groups() -> Vec<u32> {
+    let len = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+    let mut groups = vec![0; len.max(0) as usize];
+
+    let len = unsafe { libc::getgroups(len, groups.as_mut_ptr()) };
+    groups.truncate(len.max(0) as usize);
+    groups.push(unsafe { libc::getegid() });
+
+    groups
+}
+
+fn check_group(name: &str) -> EnvironmentCheck {
+    let check = format!("{} group", name);
+
+    let gid = match group_id(name) {
+        Some(gid) => gid,
+        None => {
+            return EnvironmentCheck::fail(
+                &check,
+                format!("The {} group doesn't exist", name),
+                format!(
+                    "Create the group with `sudo groupadd {}`, and install a udev rule that assigns the device nodes to it",
+                    name
+                ),
+            )
+        }
+    };
+
+    if is_root() {
+        EnvironmentCheck::pass(&check, "Running as root".to_owned())
+    } else if process_groups().contains(&gid) {
+        EnvironmentCheck::pass(
+            &check,
+            format!("The current user is a member of the {} group", name),
+        )
+    } else {
+        EnvironmentCheck::fail(
+            &check,
+            format!("The current user isn't a member of the {} group", name),
+            format!(
+                "Run `sudo usermod -a -G {} $USER`, then log out and back in for the change to take effect",
+                name
+            ),
+        )
+    }
+}
+
+fn check_device(path: &str, group: &str) -> EnvironmentCheck {
+    let check = format!("{} access", path);
+
+    if !Path::new(path).exists() {
+        let remedy = if group == "spi" {
+            "Enable SPI with `sudo raspi-config nonint do_spi 0`, or add `dtparam=spi=on` to config.txt, and reboot".to_owned()
+        } else {
+            "Make sure this is a Raspberry Pi running an up-to-date version of Raspberry Pi OS"
+                .to_owned()
+        };
+
+        return EnvironmentCheck::fail(&check, format!("{} doesn't exist", path), remedy);
+    }
+
+    let accessible = CString::new(path)
+        .map(|path| unsafe { libc::access(path.as_ptr(), libc::R_OK | libc::W_OK) == 0 })
+        .unwrap_or(false);
+
+    if accessible {
+        EnvironmentCheck::pass(&check, format!("{} is readable and writable", path))
+    } else {
+        EnvironmentCheck::fail(
+            &check,
+            format!("{} isn't readable and writable by the current user", path),
+            format!(
+                "Join the {} group, or run `sudo chgrp {} {} && sudo chmod 660 {}`, and install a udev rule to make the change permanent",
+                group, group, path, path
+            ),
+        )
+    }
+}
+
+fn check_spi_overlay() -> EnvironmentCheck {
+    let check = "SPI overlay";
+
+    let (path, config) = match PATHS_CONFIG
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok().map(|config| (*path, config)))
+    {
+        Some(config) => config,
+        None => {
+            return EnvironmentCheck::fail(
+                check,
+                "config.txt wasn't found".to_owned(),
+                "Make sure the boot partition is mounted at /boot/firmware or /boot".to_owned(),
+            )
+        }
+    };
+
+    let enabled = config.lines().any(|line| {
+        let line: String = line
+            .split('#')
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+
+        line == "dtparam=spi=on" || line.starts_with("dtoverlay=spi0")
+    });
+
+    if enabled {
+        EnvironmentCheck::pass(check, format!("SPI is enabled in {}", path))
+    } else {
+        EnvironmentCheck::fail(
+            check,
+            format!("SPI isn't enabled in {}", path),
+            format!(
+                "Run `sudo raspi-config nonint do_spi 0`, or add `dtparam=spi=on` to {}, and reboot",
+                path
+            ),
+        )
+    }
+}
+
+fn check_udev_rules() -> EnvironmentCheck {
+    let check = "udev rules";
+
+    let found = |pattern: &str| {
+        PATHS_UDEV_RULES
+            .iter()
+            .filter_map(|path| fs::read_dir(path).ok())
+            .flat_map(|entries| entries.flatten())
+            .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+            .any(|rules| rules.contains(pattern))
+    };
+
+    let missing: Vec<&str> = ["gpiomem", "spidev"]
+        .iter()
+        .copied()
+        .filter(|pattern| !found(pattern))
+        .collect();
+
+    if missing.is_empty() {
+        EnvironmentCheck::pass(
+            check,
+            "udev rules for gpiomem and spidev are installed".to_owned(),
+        )
+    } else {
+        EnvironmentCheck::fail(
+            check,
+            format!("No udev rules found for {}", missing.join(" and ")),
+            "Add `SUBSYSTEM==\"bcm2835-gpiomem\", GROUP=\"gpio\", MODE=\"0660\"` and `SUBSYSTEM==\"spidev\", GROUP=\"spi\", MODE=\"0660\"` to /etc/udev/rules.d/99-blinkt.rules, and reboot".to_owned(),
+        )
+    }
+}
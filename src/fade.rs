@@ -0,0 +1,93 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Fades that make use of the full resolution of the pixels.
+//!
+//! Each pixel combines a 5-bit brightness value with 8-bit red, green and blue
+//! values. Dimming a color by only lowering its red, green and blue values
+//! quickly runs out of distinct steps, resulting in visible banding at low
+//! intensities. The functions in this module pick the lowest possible
+//! brightness for each step, and compensate with the color values, which
+//! maximizes the number of distinct output levels during dark fades.
+//!
+//! Planned fades assume the default gamma value of `1.0`. With gamma correction
+//! enabled, the color values are corrected, but the brightness values aren't.
+//!
+//! ```rust
+//! use blinkt::fade;
+//!
+//! // Fade from full intensity to off in 100 steps.
+//! let steps = fade::plan(255, 64, 0, 1.0, 0.0, 100);
+//!
+//! assert_eq!(steps.len(), 100);
+//! assert_eq!(steps[99].rgb(), (0, 0, 0));
+//! ```
+
+use crate::Pixel;
+
+/// Returns a `Pixel` that displays the specified color at the requested
+/// intensity.
+///
+/// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+/// `intensity` is specified as a floating point value between `0.0` (0%) and `1.0` (100%),
+/// relative to the color at full brightness.
+pub fn level(red: u8, green: u8, blue: u8, intensity: f32) -> Pixel {
+    let mut pixel = Pixel::default();
+    let target = 31.0 * intensity.clamp(0.0, 1.0);
+
+    // The lowest brightness that's still able to reach the target intensity
+    // leaves the most room for the color values.
+    let brightness = target.ceil() as u8;
+    if brightness == 0 {
+        pixel.set_brightness_bits(0);
+        pixel.clear();
+
+        return pixel;
+    }
+
+    let scale = target / f32::from(brightness);
+    pixel.set_brightness_bits(brightness);
+    pixel.set_rgb(
+        (f32::from(red) * scale).round() as u8,
+        (f32::from(green) * scale).round() as u8,
+        (f32::from(blue) * scale).round() as u8,
+    );
+
+    pixel
+}
+
+/// Returns the `Pixel` values for each step of a fade between two intensities.
+///
+/// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+/// `from` and `to` are specified as floating point values between `0.0` (0%) and `1.0` (100%),
+/// relative to the color at full brightness. The first step displays `from`,
+/// and the last step displays `to`.
+pub fn plan(red: u8, green: u8, blue: u8, from: f32, to: f32, steps: usize) -> Vec<Pixel> {
+    match steps {
+        0 => Vec::new(),
+        1 => vec![level(red, green, blue, to)],
+        _ => (0..steps)
+            .map(|step| {
+                let t = step as f32 / (steps - 1) as f32;
+                level(red, green, blue, from + (to - from) * t)
+            })
+            .collect(),
+    }
+}
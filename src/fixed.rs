@@ -0,0 +1,290 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::slice;
+
+use crate::{end_frame_len, BlinktGpio, BlinktSpi, Pixel, Result, SerialOutput, CLK, DAT};
+
+// Zeroes sent as the end frame, in chunks to avoid allocating a buffer
+const END_FRAME_CHUNK: [u8; 32] = [0u8; 32];
+
+/// Interface for boards or LED strips with a fixed number of pixels.
+///
+/// `BlinktFixed` stores its `N` pixels in an array rather than on the heap.
+/// The output is allocated once when a `BlinktFixed` is constructed, after
+/// which frames are sent without any allocations. It's a lightweight
+/// alternative to [`Blinkt`] for a known number of pixels, without any of the
+/// color corrections, effects or other features that are applied by
+/// `Blinkt::show()`.
+///
+/// `BlinktFixed` only drives APA102 pixels, so there are no setters for a
+/// white channel, and 16-bit values are reduced to their 8 most significant
+/// bits.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// #
+/// # use blinkt::BlinktFixed;
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt: BlinktFixed<8> = BlinktFixed::new()?;
+///
+/// blinkt.set_all_pixels(0, 0, 255);
+/// blinkt.show()?;
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`Blinkt`]: struct.Blinkt.html
+pub struct BlinktFixed<const N: usize> {
+    serial_output: Box<dyn SerialOutput + Send>,
+    pixels: [Pixel; N],
+    clear_on_drop: bool,
+}
+
+impl<const N: usize> BlinktFixed<N> {
+    /// Constructs a new `BlinktFixed` using bitbanging mode, with the data pin
+    /// set to GPIO 23 (physical pin 16) and the clock pin to GPIO 24 (physical
+    /// pin 18).
+    pub fn new() -> Result<Self> {
+        Self::with_settings(DAT, CLK)
+    }
+
+    /// Constructs a new `BlinktFixed` using bitbanging mode, with custom settings
+    /// for the data pin and clock pin. Pins should be specified by their BCM GPIO
+    /// pin numbers.
+    pub fn with_settings(pin_data: u8, pin_clock: u8) -> Result<Self> {
+        Ok(Self::with_output(Box::new(BlinktGpio::with_settings(
+            pin_data, pin_clock,
+        )?)))
+    }
+
+    /// Constructs a new `BlinktFixed` using hardware SPI.
+    ///
+    /// This sets the data pin to GPIO 10 (physical pin 19) and the clock pin
+    /// to GPIO 11 (physical pin 23).
+    pub fn with_spi(spi: BlinktSpi) -> Self {
+        Self::with_output(Box::new(spi))
+    }
+
+    fn with_output(serial_output: Box<dyn SerialOutput + Send>) -> Self {
+        Self {
+            serial_output,
+            pixels: [Pixel::default(); N],
+            clear_on_drop: true,
+        }
+    }
+
+    /// Returns a slice containing all `Pixel`s stored in `BlinktFixed`.
+    pub fn as_slice(&self) -> &[Pixel] {
+        &self.pixels
+    }
+
+    /// Returns a mutable slice containing all `Pixel`s stored in `BlinktFixed`.
+    ///
+    /// This allows slice methods such as `rotate_left()` or `reverse()` to be
+    /// used directly on the local buffer.
+    pub fn as_mut_slice(&mut self) -> &mut [Pixel] {
+        &mut self.pixels
+    }
+
+    /// Returns a mutable iterator over all `Pixel`s stored in `BlinktFixed`.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, Pixel> {
+        self.pixels.iter_mut()
+    }
+
+    /// Sets the red, green and blue values for a single pixel in the local
+    /// buffer.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    pub fn set_pixel(&mut self, pixel: usize, red: u8, green: u8, blue: u8) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_rgb(red, green, blue);
+        }
+    }
+
+    /// Sets the red, green, blue and brightness values for a single pixel in
+    /// the local buffer.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    /// `brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%), and is converted to a 5-bit value.
+    pub fn set_pixel_rgbb(&mut self, pixel: usize, red: u8, green: u8, blue: u8, brightness: f32) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_rgbb(red, green, blue, brightness);
+        }
+    }
+
+    /// Sets the 16-bit red, green and blue values for a single pixel in the
+    /// local buffer.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `red`, `green` and `blue` are specified as 16-bit values between `0` (0%) and `65535` (100%).
+    /// Only the 8 most significant bits are sent to the pixels.
+    pub fn set_pixel_u16(&mut self, pixel: usize, red: u16, green: u16, blue: u16) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_rgb16(red, green, blue);
+        }
+    }
+
+    /// Sets the red, green and blue values for a single pixel in the local
+    /// buffer, based on hue, saturation and value (HSV).
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `hue` is specified in degrees, and wraps around at `360.0`. `saturation` and
+    /// `value` are specified as floating point values between `0.0` (0%) and `1.0` (100%).
+    pub fn set_pixel_hsv(&mut self, pixel: usize, hue: f32, saturation: f32, value: f32) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_hsv(hue, saturation, value);
+        }
+    }
+
+    /// Sets the brightness value for a single pixel in the local buffer.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%), and is converted to a 5-bit value.
+    pub fn set_pixel_brightness(&mut self, pixel: usize, brightness: f32) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_brightness(brightness);
+        }
+    }
+
+    /// Sets the red, green and blue values for all pixels in the local buffer.
+    ///
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    pub fn set_all_pixels(&mut self, red: u8, green: u8, blue: u8) {
+        for pixel in &mut self.pixels {
+            pixel.set_rgb(red, green, blue);
+        }
+    }
+
+    /// Sets the red, green, blue and brightness values for all pixels in the
+    /// local buffer.
+    ///
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    /// `brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%), and is converted to a 5-bit value.
+    pub fn set_all_pixels_rgbb(&mut self, red: u8, green: u8, blue: u8, brightness: f32) {
+        for pixel in &mut self.pixels {
+            pixel.set_rgbb(red, green, blue, brightness);
+        }
+    }
+
+    /// Sets the 16-bit red, green and blue values for all pixels in the local
+    /// buffer.
+    ///
+    /// `red`, `green` and `blue` are specified as 16-bit values between `0` (0%) and `65535` (100%).
+    /// Only the 8 most significant bits are sent to the pixels.
+    pub fn set_all_pixels_u16(&mut self, red: u16, green: u16, blue: u16) {
+        for pixel in &mut self.pixels {
+            pixel.set_rgb16(red, green, blue);
+        }
+    }
+
+    /// Sets the red, green and blue values for all pixels in the local buffer,
+    /// based on hue, saturation and value (HSV).
+    ///
+    /// `hue` is specified in degrees, and wraps around at `360.0`. `saturation` and
+    /// `value` are specified as floating point values between `0.0` (0%) and `1.0` (100%).
+    pub fn set_all_pixels_hsv(&mut self, hue: f32, saturation: f32, value: f32) {
+        for pixel in &mut self.pixels {
+            pixel.set_hsv(hue, saturation, value);
+        }
+    }
+
+    /// Sets the brightness value for all pixels.
+    ///
+    /// `brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%), and is converted to a 5-bit value.
+    pub fn set_all_pixels_brightness(&mut self, brightness: f32) {
+        for pixel in &mut self.pixels {
+            pixel.set_brightness(brightness);
+        }
+    }
+
+    /// Sets the red, green and blue values for all pixels to `0`.
+    pub fn clear(&mut self) {
+        self.set_all_pixels(0, 0, 0);
+    }
+
+    /// Sends the contents of the local buffer to the pixels.
+    pub fn show(&mut self) -> Result<()> {
+        // The output is released even if the frame couldn't be sent.
+        self.serial_output.acquire()?;
+        let result = self.write_frame();
+        let released = self.serial_output.release();
+
+        result.and(released)
+    }
+
+    fn write_frame(&mut self) -> Result<()> {
+        // Start frame (32*0).
+        self.serial_output.write(&[0u8; 4])?;
+
+        // LED frames (3*1, 5*brightness, 8*blue, 8*green, 8*red).
+        for pixel in &self.pixels {
+            let (red, green, blue) = pixel.rgb();
+            self.serial_output
+                .write(&[0b1110_0000 | pixel.brightness_bits(), blue, green, red])?;
+        }
+
+        // End frame (8*0 for every 16 pixels, 32*0 SK9822 reset frame).
+        let mut remaining = end_frame_len(N);
+        while remaining > 0 {
+            let len = remaining.min(END_FRAME_CHUNK.len());
+            self.serial_output.write(&END_FRAME_CHUNK[..len])?;
+            remaining -= len;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the value of `clear_on_drop`.
+    pub fn clear_on_drop(&self) -> bool {
+        self.clear_on_drop
+    }
+
+    /// When enabled, clears all pixels when `BlinktFixed` goes out of scope.
+    ///
+    /// By default, this is set to `true`.
+    pub fn set_clear_on_drop(&mut self, clear_on_drop: bool) {
+        self.clear_on_drop = clear_on_drop;
+    }
+}
+
+impl<const N: usize> Drop for BlinktFixed<N> {
+    /// Clears all pixels if [`clear_on_drop`] is set to `true` (default).
+    ///
+    /// [`clear_on_drop`]: #method.clear_on_drop
+    fn drop(&mut self) {
+        if self.clear_on_drop {
+            self.clear();
+            let _ = self.show();
+        }
+    }
+}
+
+impl<'a, const N: usize> IntoIterator for &'a mut BlinktFixed<N> {
+    type Item = &'a mut Pixel;
+    type IntoIter = slice::IterMut<'a, Pixel>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
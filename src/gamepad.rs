@@ -0,0 +1,185 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Gamepad and joystick input, to control effects for interactive demos and
+//! simple games.
+//!
+//! [`Gamepad`] reads events from a Linux joystick device, such as
+//! `/dev/input/js0`, which is available for most USB and Bluetooth gamepads.
+//! Axes and buttons can be mapped to named parameters, which can be used to
+//! control any effect.
+//!
+//! This module is only available when the `gamepad` feature is enabled.
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! #
+//! # use blinkt::Blinkt;
+//! use blinkt::gamepad::Gamepad;
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let mut blinkt = Blinkt::new()?;
+//! let mut gamepad = Gamepad::open("/dev/input/js0")?;
+//!
+//! gamepad.map_axis(0, "position");
+//! gamepad.map_button(0, "flash");
+//!
+//! loop {
+//!     gamepad.poll()?;
+//!
+//!     let position = (gamepad.value("position").unwrap_or(0.0) + 1.0) * 4.0;
+//!     let red = if gamepad.value("flash") == Some(1.0) { 255 } else { 0 };
+//!
+//!     blinkt.clear();
+//!     blinkt.draw_point_f(position, red, 0, 255, 1.0);
+//!     blinkt.show()?;
+//! }
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+use crate::Result;
+
+// Size of struct js_event (u32 time, i16 value, u8 type, u8 number)
+const JS_EVENT_SIZE: usize = 8;
+const JS_EVENT_BUTTON: u8 = 0x01;
+const JS_EVENT_AXIS: u8 = 0x02;
+const JS_EVENT_INIT: u8 = 0x80;
+
+/// An input event.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Event {
+    /// A button was pressed (`true`) or released (`false`).
+    Button(u8, bool),
+    /// An axis moved. The position is specified as a floating point value
+    /// between `-1.0` and `1.0`.
+    Axis(u8, f32),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum Input {
+    Button(u8),
+    Axis(u8),
+}
+
+/// A gamepad or joystick.
+#[derive(Debug)]
+pub struct Gamepad {
+    device: File,
+    buttons: HashMap<u8, bool>,
+    axes: HashMap<u8, f32>,
+    mapping: HashMap<Input, String>,
+}
+
+impl Gamepad {
+    /// Opens the joystick device at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let device = OpenOptions::new()
+            .read(true)
+            // Don't block when no events are available.
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)?;
+
+        Ok(Self {
+            device,
+            buttons: HashMap::new(),
+            axes: HashMap::new(),
+            mapping: HashMap::new(),
+        })
+    }
+
+    /// Reads all pending events without blocking, and returns them.
+    ///
+    /// The initial state of all buttons and axes is included in the events
+    /// returned by the first call to `poll()`.
+    pub fn poll(&mut self) -> Result<Vec<Event>> {
+        let mut events = Vec::new();
+        let mut buffer = [0u8; JS_EVENT_SIZE];
+
+        loop {
+            match self.device.read_exact(&mut buffer) {
+                Ok(()) => {}
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err.into()),
+            }
+
+            let value = i16::from_ne_bytes([buffer[4], buffer[5]]);
+            let number = buffer[7];
+
+            let event = match buffer[6] & !JS_EVENT_INIT {
+                JS_EVENT_BUTTON => {
+                    self.buttons.insert(number, value != 0);
+                    Event::Button(number, value != 0)
+                }
+                JS_EVENT_AXIS => {
+                    let position = (f32::from(value) / 32767.0).max(-1.0);
+                    self.axes.insert(number, position);
+                    Event::Axis(number, position)
+                }
+                _ => continue,
+            };
+
+            events.push(event);
+        }
+
+        Ok(events)
+    }
+
+    /// Returns `true` if `button` is currently pressed.
+    pub fn button(&self, button: u8) -> bool {
+        self.buttons.get(&button).copied().unwrap_or(false)
+    }
+
+    /// Returns the current position of `axis`, specified as a floating point
+    /// value between `-1.0` and `1.0`.
+    pub fn axis(&self, axis: u8) -> f32 {
+        self.axes.get(&axis).copied().unwrap_or(0.0)
+    }
+
+    /// Maps `axis` to the parameter named `name`.
+    pub fn map_axis(&mut self, axis: u8, name: &str) {
+        self.mapping.insert(Input::Axis(axis), name.to_owned());
+    }
+
+    /// Maps `button` to the parameter named `name`.
+    pub fn map_button(&mut self, button: u8, name: &str) {
+        self.mapping.insert(Input::Button(button), name.to_owned());
+    }
+
+    /// Returns the value of the parameter named `name`, or `None` if it isn't
+    /// mapped.
+    ///
+    /// Axes return their position between `-1.0` and `1.0`. Buttons return `1.0`
+    /// while they're pressed, and `0.0` otherwise.
+    pub fn value(&self, name: &str) -> Option<f32> {
+        let (input, _) = self.mapping.iter().find(|(_, mapped)| *mapped == name)?;
+
+        Some(match *input {
+            Input::Axis(axis) => self.axis(axis),
+            Input::Button(button) if self.button(button) => 1.0,
+            Input::Button(_) => 0.0,
+        })
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::driver::DriverOutput;
+use crate::{end_frame_len, Backend, Blinkt, BlinktSpi, Driver, Protocol, Result, SerialOutput};
+
+// Start frame (128*0).
+const HD108_START_FRAME_LEN: usize = 16;
+
+// Sends HD108 data through hardware SPI.
+struct BlinktHd108 {
+    spi: BlinktSpi,
+}
+
+impl SerialOutput for BlinktHd108 {
+    fn acquire(&mut self) -> Result<()> {
+        self.spi.acquire()
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.spi.write(data)
+    }
+
+    fn release(&mut self) -> Result<()> {
+        self.spi.release()
+    }
+
+    fn clock_speed(&self) -> u32 {
+        self.spi.clock_speed()
+    }
+
+    fn set_clock_speed(&mut self, clock_speed_hz: u32) -> Result<()> {
+        self.spi.set_clock_speed(clock_speed_hz)
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Spi
+    }
+
+    fn protocol(&self) -> Protocol {
+        Protocol::Hd108
+    }
+}
+
+impl Blinkt {
+    /// Constructs a new `Blinkt` for HD108 LED strips, using hardware SPI with
+    /// the specified number of pixels.
+    ///
+    /// The HD108 uses a data and clock line like the APA102, but supports 16-bit
+    /// red, green and blue values, which can be set through `set_pixel_u16()`
+    /// and `set_all_pixels_u16()`. This avoids visible steps at the low end of
+    /// slow fades. Pixels set through the 8-bit methods are scaled to the full
+    /// 16-bit range.
+    ///
+    /// Gamma correction and calibration are applied at 16-bit precision. The
+    /// brightness of each pixel is sent as the brightness of all three LEDs. The
+    /// color order is set to `ColorOrder::Rgb`.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::{Blinkt, BlinktSpi};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mut blinkt = Blinkt::with_hd108_spi(BlinktSpi::default(), 60);
+    ///
+    /// // A very dim red, which would be rounded to 0 with 8-bit values.
+    /// blinkt.set_all_pixels_u16(100, 0, 0);
+    /// blinkt.show()?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn with_hd108_spi(spi: BlinktSpi, num_pixels: usize) -> Self {
+        Self::with_output(Box::new(BlinktHd108 { spi }), num_pixels)
+    }
+
+    /// Constructs a new `Blinkt` for HD108 LED strips, which sends the encoded
+    /// frames through a custom [`Driver`], with the specified number of pixels.
+    ///
+    /// See [`with_hd108_spi()`] for details.
+    ///
+    /// ```rust
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::{Blinkt, MockOutput};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mock = MockOutput::new();
+    /// let mut blinkt = Blinkt::with_hd108_driver(mock.clone(), 1);
+    ///
+    /// blinkt.set_pixel_rgbb(0, 255, 0, 0, 1.0);
+    /// blinkt.show()?;
+    ///
+    /// let frame = mock.last_frame().unwrap();
+    /// // Start frame (128*0).
+    /// assert_eq!(frame[..16], [0; 16]);
+    /// // Start bit and full brightness for all three LEDs, followed by 16-bit
+    /// // red, green and blue values.
+    /// assert_eq!(frame[16..24], [0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0]);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Driver`]: trait.Driver.html
+    /// [`with_hd108_spi()`]: #method.with_hd108_spi
+    pub fn with_hd108_driver<D: Driver + Send + 'static>(driver: D, num_pixels: usize) -> Self {
+        Self::with_output(
+            Box::new(DriverOutput::new(driver, Protocol::Hd108)),
+            num_pixels,
+        )
+    }
+
+    /// Adds HD108 pixels to the end of the local buffer, which are sent through
+    /// hardware SPI.
+    ///
+    /// See [`with_hd108_spi()`] for details on the HD108 output, and
+    /// `chain_gpio()` for details on chaining. The color order of the chained
+    /// output is set to `ColorOrder::Rgb`, without affecting any other outputs.
+    ///
+    /// [`with_hd108_spi()`]: #method.with_hd108_spi
+    pub fn chain_hd108_spi(&mut self, spi: BlinktSpi, num_pixels: usize) {
+        self.chain_output(Box::new(BlinktHd108 { spi }), num_pixels);
+    }
+
+    /// Adds HD108 pixels to the end of the local buffer, which are sent through
+    /// a custom [`Driver`].
+    ///
+    /// See [`with_hd108_driver()`] for details on the encoded frames, and
+    /// `chain_gpio()` for details on chaining.
+    ///
+    /// [`Driver`]: trait.Driver.html
+    /// [`with_hd108_driver()`]: #method.with_hd108_driver
+    pub fn chain_hd108_driver<D: Driver + Send + 'static>(&mut self, driver: D, num_pixels: usize) {
+        self.chain_output(
+            Box::new(DriverOutput::new(driver, Protocol::Hd108)),
+            num_pixels,
+        );
+    }
+
+    // Sends the frame buffer to an HD108 output.
+    pub(crate) fn write_hd108(&mut self, output: usize) -> Result<()> {
+        let pixels = self.outputs[output].pixels.clone();
+        let color_order = self.outputs[output].color_order;
+        let end_frame_len = self
+            .end_frame_len
+            .unwrap_or_else(|| end_frame_len(pixels.len()));
+
+        let mut data = vec![0u8; HD108_START_FRAME_LEN];
+        data.reserve(pixels.len() * 8 + end_frame_len);
+
+        // LED frames (1*1, 5*red brightness, 5*green brightness, 5*blue brightness,
+        // 16*red, 16*green, 16*blue).
+        for index in pixels {
+            let pixel = self.frame[index];
+            let wire_pixel = self.preview_at(index, &pixel);
+            let (red, green, blue) = self.correction_at(index).apply_rgb16(&pixel);
+
+            let brightness = u16::from(wire_pixel.brightness());
+            let header = 0x8000 | (brightness << 10) | (brightness << 5) | brightness;
+
+            data.extend_from_slice(&header.to_be_bytes());
+            for value in &color_order.arrange(red, green, blue) {
+                data.extend_from_slice(&value.to_be_bytes());
+            }
+
+            self.sent_frame.push(wire_pixel);
+        }
+
+        // End frame, which provides the additional clock cycles needed to pass
+        // on the data to the last pixels.
+        data.resize(data.len() + end_frame_len, 0);
+
+        let serial_output = &mut self.outputs[output].serial_output;
+        serial_output.acquire()?;
+        let result = serial_output.write(&data);
+        let released = serial_output.release();
+
+        result.and(released)
+    }
+}
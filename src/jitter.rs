@@ -0,0 +1,186 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::{Blinkt, Pixel};
+
+// Maximum number of frames kept in the buffer
+const MAX_FRAMES: usize = 64;
+
+#[derive(Debug, Clone)]
+struct Entry {
+    due: Instant,
+    frame: Vec<Pixel>,
+}
+
+/// Smooths out irregular arrival of frames received over a network.
+///
+/// Every frame is pushed with the timestamp assigned by the sender, and played
+/// back after a fixed delay, relative to the time the sender sent it, rather
+/// than the time it arrived. Frames that arrive late because of network jitter
+/// are still shown at the right moment, as long as the delay is large enough
+/// to cover the jitter. A larger delay hides more jitter, at the cost of
+/// additional latency.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// # use std::time::Duration;
+/// #
+/// # use blinkt::{Blinkt, JitterBuffer, Pixel};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #     let mut blinkt = Blinkt::new()?;
+/// let mut jitter_buffer = JitterBuffer::new(Duration::from_millis(50));
+///
+/// loop {
+///     // Push any frames received from the network, along with their timestamp.
+/// #   let (timestamp, frame) = (Duration::default(), [Pixel::default(); 8]);
+///     jitter_buffer.push(timestamp, &frame);
+///
+///     if jitter_buffer.apply(&mut blinkt) {
+///         blinkt.show()?;
+///     }
+/// #   break;
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct JitterBuffer {
+    delay: Duration,
+    // Local time and sender timestamp of the fastest frame received so far
+    base: Option<(Instant, Duration)>,
+    entries: VecDeque<Entry>,
+}
+
+impl JitterBuffer {
+    /// Constructs a new, empty `JitterBuffer` with the specified playback delay.
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            base: None,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Returns the playback delay.
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Sets the playback delay.
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.delay = delay;
+    }
+
+    /// Returns the number of frames waiting to be played back.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no frames are waiting to be played back.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds a frame to the buffer.
+    ///
+    /// `timestamp` is the time the sender sent the frame, relative to any fixed
+    /// point in time chosen by the sender. Frames should be pushed in the order
+    /// they were sent. When the buffer is full, the oldest frame is dropped.
+    pub fn push(&mut self, timestamp: Duration, frame: &[Pixel]) {
+        let now = Instant::now();
+
+        // The fastest frame received so far determines the offset between the
+        // clocks of the sender and the receiver, so later frames are only ever
+        // delayed by the jitter. Frames that arrive earlier than expected, or
+        // can't be expressed relative to the current base, become the new base.
+        let expected = self.base.and_then(|(base, base_timestamp)| {
+            match timestamp.checked_sub(base_timestamp) {
+                Some(offset) => base.checked_add(offset),
+                None => base.checked_sub(base_timestamp - timestamp),
+            }
+        });
+
+        let sent = match expected {
+            Some(expected) if expected <= now => expected,
+            _ => {
+                self.base = Some((now, timestamp));
+                now
+            }
+        };
+
+        // Frames are never played back before any frame that was pushed earlier.
+        let due = sent.checked_add(self.delay).unwrap_or(sent);
+        let due = self.entries.back().map_or(due, |entry| entry.due.max(due));
+
+        if self.entries.len() == MAX_FRAMES {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(Entry {
+            due,
+            frame: frame.to_vec(),
+        });
+    }
+
+    /// Removes and returns the most recent frame that's due for playback.
+    ///
+    /// Any older frames that are due are skipped. Returns `None` if no frame is
+    /// due yet.
+    pub fn pop(&mut self) -> Option<Vec<Pixel>> {
+        let now = Instant::now();
+        let mut frame = None;
+
+        while self.entries.front().map_or(false, |entry| entry.due <= now) {
+            frame = self.entries.pop_front().map(|entry| entry.frame);
+        }
+
+        frame
+    }
+
+    /// Copies the most recent frame that's due for playback to the local buffer
+    /// of `blinkt`.
+    ///
+    /// If the frame contains fewer pixels than `blinkt`, the remaining pixels are
+    /// left unchanged. Returns `true` if a frame was copied.
+    pub fn apply(&mut self, blinkt: &mut Blinkt) -> bool {
+        match self.pop() {
+            Some(frame) => {
+                for (pixel, value) in blinkt.pixels.iter_mut().zip(&frame) {
+                    *pixel = *value;
+                }
+
+                blinkt.notify_change();
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes all frames, and resets the synchronization with the sender.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.base = None;
+    }
+}
@@ -51,7 +51,7 @@
 //!
 //! ```rust,no_run
 //! use std::error::Error;
-//! use std::time::Duration;
+//! use std::time::{Duration, Instant};
 //! use std::{thread, mem};
 //!
 //! use blinkt::Blinkt;
@@ -146,27 +146,140 @@
 #![doc(html_root_url = "https://docs.rs/blinkt/0.7.1")]
 #![allow(clippy::trivially_copy_pass_by_ref)]
 
+use std::collections::HashMap;
 use std::error;
 use std::fmt;
 use std::io;
+use std::ops::Range;
 use std::result;
 use std::slice;
-use std::time::Duration;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use rppal::gpio::{Gpio, OutputPin};
 
+use lock::DeviceLock;
+
 pub use rppal::gpio::Error as GpioError;
+pub use rppal::i2c::Error as I2cError;
 pub use rppal::spi::Error as SpiError;
 
+pub mod affinity;
+mod arbiter;
+pub mod artnet;
+mod builder;
+mod capture;
+mod checksum;
+mod clock;
+pub mod color;
+mod correction;
+mod diagnostics;
+mod discovery;
+mod dmx;
+mod driver;
+pub mod effect;
+mod encoder;
+mod environment;
+pub mod fade;
+mod fixed;
+#[cfg(feature = "gamepad")]
+pub mod gamepad;
+mod hd108;
+mod jitter;
+mod lock;
+pub mod midi;
+mod morse;
+mod net;
+pub mod opc;
+pub mod osc;
+mod p9813;
+mod palette;
+mod pins;
 mod pixel;
+mod power;
+mod preset;
+mod profile;
+mod recording;
+#[cfg(feature = "sacn")]
+pub mod sacn;
+mod scale;
+mod schedule;
+mod smoothing;
+mod solar;
+mod tcp;
+#[cfg(feature = "terminal")]
+mod terminal;
+mod timeline;
+mod tone_map;
+mod transaction;
+#[cfg(feature = "ups")]
+pub mod ups;
+mod watchdog;
+pub mod wave;
+#[cfg(feature = "wled")]
+pub mod wled;
+mod ws2812;
 
-pub use pixel::Pixel;
+pub use arbiter::Arbiter;
+pub use builder::BlinktBuilder;
+pub use capture::CaptureOutput;
+pub use clock::{BeatClock, FrameClock};
+pub use correction::Correction;
+pub use diagnostics::{LengthEstimate, TestPattern};
+pub use discovery::{DiscoveredReceiver, DiscoveryResponder};
+pub use driver::{Driver, MockFault, MockOutput};
+pub use encoder::FrameEncoder;
+pub use environment::EnvironmentCheck;
+pub use fixed::BlinktFixed;
+pub use jitter::JitterBuffer;
+pub use palette::{Palette, Status};
+pub use pixel::{ColorOrder, Pixel, WirePixel};
+pub use power::{PowerModel, SoftStart};
+pub use preset::PresetStore;
+pub use profile::Profile;
+pub use recording::{RecordedFrame, RecordingMismatch, RecordingOutput, RecordingPlayer};
+pub use scale::ScaleMode;
+pub use schedule::DateSchedule;
+pub use smoothing::Smoothing;
+pub use solar::SolarSchedule;
+pub use tcp::{TcpOutput, TcpReceiver};
+#[cfg(feature = "terminal")]
+pub use terminal::TerminalOutput;
+pub use timeline::{Easing, Playback, Timeline};
+pub use tone_map::ToneMap;
+pub use transaction::Transaction;
+pub use watchdog::{Watchdog, WatchdogAction};
 
 // Default values for the Pimoroni Blinkt! board using BCM GPIO pin numbers
 const DAT: u8 = 23;
 const CLK: u8 = 24;
 const NUM_PIXELS: usize = 8;
 
+// Hardware SPI pins using BCM GPIO pin numbers
+const SPI_DAT: u8 = 10;
+const SPI_CLK: u8 = 11;
+
+// Time between the intermediate frames sent during a soft start
+const SOFT_START_INTERVAL: Duration = Duration::from_millis(10);
+// Number of bisection steps used to find the scale of an intermediate frame
+const SOFT_START_ITERATIONS: u32 = 12;
+
+// Paranoid mode lowers the clock speed after a number of consecutive failed
+// frames, or frames that take much longer than average to send, and raises it
+// again after a number of consecutive frames without problems
+const PARANOID_MAX_FAILURES: u32 = 3;
+const PARANOID_TIMEOUT_FACTOR: u32 = 4;
+// Frames that take less time than this are never considered slow, so the
+// scheduling jitter of very short frames isn't mistaken for a problem
+const PARANOID_MIN_TIMEOUT: Duration = Duration::from_millis(10);
+const PARANOID_MIN_CLOCK_SPEED: u32 = 100_000;
+const PARANOID_RECOVERY_FRAMES: u32 = 1000;
+
+// An error handler can retry a frame a limited number of times, with an
+// increasing delay between attempts, before the error is returned anyway
+const MAX_RETRIES: u32 = 5;
+const RETRY_DELAY: Duration = Duration::from_millis(1);
+
 #[derive(Debug)]
 /// Errors that can occur while using Blinkt.
 pub enum Error {
@@ -177,8 +290,32 @@ pub enum Error {
     Gpio(GpioError),
     /// Accessing the SPI peripheral returned an error.
     Spi(SpiError),
+    /// Accessing the I2C peripheral returned an error.
+    I2c(I2cError),
     /// An I/O operation returned an error.
     Io(io::Error),
+    /// A GPIO pin is already in use by another process or one of the peripherals.
+    ///
+    /// Includes the BCM GPIO pin number, and a description of the function the
+    /// pin is currently used for.
+    PinConflict(u8, String),
+    /// A device is already in use by another process using this crate.
+    ///
+    /// Includes the name of the device, and the PID of the process that created
+    /// its lock file, or `0` if it's unknown. That's usually the process that
+    /// uses the device, unless the lock file was left behind by a process that
+    /// exited. See [`Blinkt::force_unlock()`] to take over the device.
+    ///
+    /// [`Blinkt::force_unlock()`]: struct.Blinkt.html#method.force_unlock
+    DeviceInUse(String, u32),
+    /// The settings of a [`BlinktBuilder`] are invalid.
+    ///
+    /// Includes a description of every problem that was found. See
+    /// [`BlinktBuilder::validate()`] for details.
+    ///
+    /// [`BlinktBuilder`]: struct.BlinktBuilder.html
+    /// [`BlinktBuilder::validate()`]: struct.BlinktBuilder.html#method.validate
+    InvalidConfig(String),
 }
 
 impl fmt::Display for Error {
@@ -186,7 +323,15 @@ impl fmt::Display for Error {
         match *self {
             Error::Gpio(ref err) => write!(f, "GPIO error: {}", err),
             Error::Spi(ref err) => write!(f, "SPI error: {}", err),
+            Error::I2c(ref err) => write!(f, "I2C error: {}", err),
             Error::Io(ref err) => write!(f, "I/O error: {}", err),
+            Error::PinConflict(pin, ref function) => {
+                write!(f, "GPIO {} is already in use ({})", pin, function)
+            }
+            Error::DeviceInUse(ref device, pid) => {
+                write!(f, "Device {} is in use by PID {}", device, pid)
+            }
+            Error::InvalidConfig(ref problems) => write!(f, "Invalid configuration: {}", problems),
         }
     }
 }
@@ -205,6 +350,12 @@ impl From<GpioError> for Error {
     }
 }
 
+impl From<I2cError> for Error {
+    fn from(err: I2cError) -> Self {
+        Self::I2c(err)
+    }
+}
+
 impl From<SpiError> for Error {
     fn from(err: SpiError) -> Self {
         Self::Spi(err)
@@ -215,57 +366,348 @@ impl From<SpiError> for Error {
 pub type Result<T> = result::Result<T, Error>;
 
 trait SerialOutput {
+    // Called before a frame is sent.
+    fn acquire(&mut self) -> Result<()> {
+        Ok(())
+    }
+
     fn write(&mut self, data: &[u8]) -> Result<()>;
+
+    // Called after a frame is sent.
+    fn release(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn clock_speed(&self) -> u32;
+
+    fn set_clock_speed(&mut self, clock_speed_hz: u32) -> Result<()>;
+
+    fn backend(&self) -> Backend;
+
+    // The number of data lines driven in parallel. Outputs with multiple lanes
+    // receive a single stream with the bytes for every lane interleaved.
+    fn lanes(&self) -> usize {
+        1
+    }
+
+    fn set_timing_strategy(&mut self, _timing_strategy: TimingStrategy) {}
+
+    fn set_color_order(&mut self, _color_order: ColorOrder) {}
+
+    fn protocol(&self) -> Protocol {
+        Protocol::Apa102
+    }
+}
+
+// The protocol used to encode the frame buffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Protocol {
+    // APA102 and SK9822, with a start frame, LED frames and an end frame.
+    Apa102,
+    // WS2812 single-wire timing, encoded as SPI data.
+    Ws2812,
+    // WS2812 timing with an additional white channel, used by the SK6812 RGBW.
+    Sk6812Rgbw,
+    // HD108, with 16-bit color values and a 5-bit brightness for each channel.
+    Hd108,
+    // P9813, with a checksum in the flag byte of every pixel.
+    P9813,
 }
 
+impl Protocol {
+    // Returns the order in which the pixels expect the red, green and blue
+    // values, unless the order is changed with set_color_order().
+    fn color_order(self) -> ColorOrder {
+        match self {
+            Protocol::Apa102 | Protocol::P9813 => ColorOrder::Bgr,
+            Protocol::Ws2812 | Protocol::Sk6812Rgbw => ColorOrder::Grb,
+            Protocol::Hd108 => ColorOrder::Rgb,
+        }
+    }
+}
+
+/// The method used to wait for the duration of each clock pulse in bitbanging
+/// mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TimingStrategy {
+    /// Puts the thread to sleep. This keeps the CPU usage low, but the actual
+    /// duration of each pulse depends on the scheduler, and is usually
+    /// considerably longer than requested.
+    Sleep,
+    /// Continuously checks the time until the pulse ends. This results in the
+    /// most accurate timing and the highest frame rate, but keeps a CPU core
+    /// fully occupied while a frame is sent.
+    BusyWait,
+    /// Checks the time until the pulse ends, and yields to other threads in
+    /// between. This is more accurate than `Sleep`, while other threads
+    /// can still run when the system is under load.
+    SpinYield,
+}
+
+impl TimingStrategy {
+    fn wait(self, duration: Duration) {
+        match self {
+            TimingStrategy::Sleep => thread::sleep(duration),
+            TimingStrategy::BusyWait => {
+                let started = Instant::now();
+                while started.elapsed() < duration {
+                    std::hint::spin_loop();
+                }
+            }
+            TimingStrategy::SpinYield => {
+                let started = Instant::now();
+                while started.elapsed() < duration {
+                    thread::yield_now();
+                }
+            }
+        }
+    }
+}
+
+impl Default for TimingStrategy {
+    fn default() -> Self {
+        TimingStrategy::Sleep
+    }
+}
+
+/// The interface used to send data to the pixels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Backend {
+    /// Bitbanging mode on GPIO pins.
+    Gpio,
+    /// Hardware SPI.
+    Spi,
+    /// A custom [`Driver`].
+    ///
+    /// [`Driver`]: trait.Driver.html
+    Custom,
+}
+
+// Default time the clock pin is held high in bitbanging mode
+const GPIO_CLOCK_PULSE: Duration = Duration::from_nanos(10000);
+
 struct BlinktGpio {
     pin_data: OutputPin,
     pin_clock: OutputPin,
+    clock_pulse: Duration,
+    timing_strategy: TimingStrategy,
+    data_level: bool,
+    bits: Vec<bool>,
+    _locks: [DeviceLock; 2],
 }
 
 impl BlinktGpio {
     pub fn with_settings(pin_data: u8, pin_clock: u8) -> Result<Self> {
-        let gpio = Gpio::new()?;
-
-        let mut pin_data = gpio.get(pin_data)?.into_output();
-        let mut pin_clock = gpio.get(pin_clock)?.into_output();
+        let locks = [
+            DeviceLock::acquire(&lock::gpio_device(pin_data))?,
+            DeviceLock::acquire(&lock::gpio_device(pin_clock))?,
+        ];
 
-        pin_data.set_low();
-        pin_clock.set_low();
+        let gpio = Gpio::new()?;
 
         Ok(Self {
-            pin_data,
-            pin_clock,
+            pin_data: output_pin(&gpio, pin_data)?,
+            pin_clock: output_pin(&gpio, pin_clock)?,
+            clock_pulse: GPIO_CLOCK_PULSE,
+            timing_strategy: TimingStrategy::default(),
+            data_level: false,
+            bits: Vec::new(),
+            _locks: locks,
         })
     }
 }
 
+// Appends the bits of `data` to `bits`, most significant bit first.
+fn extend_bits(bits: &mut Vec<bool>, data: &[u8]) {
+    bits.extend(
+        data.iter()
+            .flat_map(|byte| (0..8).rev().map(move |n| (byte & (1 << n)) > 0)),
+    );
+}
+
 impl SerialOutput for BlinktGpio {
     fn write(&mut self, data: &[u8]) -> Result<()> {
-        for byte in data {
-            for n in 0..8 {
-                if (byte & (1 << (7 - n))) > 0 {
+        // Convert the data to bits up front, so the loop below only has to
+        // toggle the pins. The data pin is only written when its level changes.
+        self.bits.clear();
+        extend_bits(&mut self.bits, data);
+
+        for &bit in &self.bits {
+            if bit != self.data_level {
+                if bit {
                     self.pin_data.set_high();
                 } else {
                     self.pin_data.set_low();
                 }
 
-                self.pin_clock.set_high();
-                std::thread::sleep(Duration::from_nanos(10000));
-                self.pin_clock.set_low();
+                self.data_level = bit;
+            }
+
+            self.pin_clock.set_high();
+            self.timing_strategy.wait(self.clock_pulse);
+            self.pin_clock.set_low();
+        }
+
+        Ok(())
+    }
+
+    // With TimingStrategy::Sleep, the actual clock speed is usually considerably
+    // lower, because the duration of each clock pulse depends on the accuracy of
+    // thread::sleep().
+    fn clock_speed(&self) -> u32 {
+        (1_000_000_000 / (2 * self.clock_pulse.as_nanos().max(1))) as u32
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Gpio
+    }
+
+    fn set_timing_strategy(&mut self, timing_strategy: TimingStrategy) {
+        self.timing_strategy = timing_strategy;
+    }
+
+    fn set_clock_speed(&mut self, clock_speed_hz: u32) -> Result<()> {
+        self.clock_pulse =
+            Duration::from_nanos(1_000_000_000 / (2 * u64::from(clock_speed_hz.max(1))));
+
+        Ok(())
+    }
+}
+
+// Returns the pin configured as an output, after making sure it isn't in use
+// by another process or one of the peripherals.
+fn output_pin(gpio: &Gpio, pin: u8) -> Result<OutputPin> {
+    let pin = gpio.get(pin)?;
+    pins::check_available(&pin)?;
+
+    let mut pin = pin.into_output();
+    pin.set_low();
+
+    Ok(pin)
+}
+
+// Bitbanging mode with several data pins sharing a single clock pin, which
+// sends the data for all data pins at the same time.
+struct BlinktParallelGpio {
+    pins_data: Vec<OutputPin>,
+    pin_clock: OutputPin,
+    clock_pulse: Duration,
+    timing_strategy: TimingStrategy,
+    data_levels: Vec<bool>,
+    bits: Vec<bool>,
+    _locks: Vec<DeviceLock>,
+}
+
+impl BlinktParallelGpio {
+    fn with_settings(pins_data: &[u8], pin_clock: u8) -> Result<Self> {
+        if pins_data.is_empty() {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no data pins specified",
+            )));
+        }
+
+        let mut locks = Vec::with_capacity(pins_data.len() + 1);
+        for pin in pins_data.iter().chain(&[pin_clock]) {
+            locks.push(DeviceLock::acquire(&lock::gpio_device(*pin))?);
+        }
+
+        let gpio = Gpio::new()?;
+
+        Ok(Self {
+            pins_data: pins_data
+                .iter()
+                .map(|pin| output_pin(&gpio, *pin))
+                .collect::<Result<_>>()?,
+            pin_clock: output_pin(&gpio, pin_clock)?,
+            clock_pulse: GPIO_CLOCK_PULSE,
+            timing_strategy: TimingStrategy::default(),
+            data_levels: vec![false; pins_data.len()],
+            bits: Vec::new(),
+            _locks: locks,
+        })
+    }
+}
+
+impl SerialOutput for BlinktParallelGpio {
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        let lanes = self.pins_data.len();
+
+        // Convert the interleaved data to one bit per lane for every clock cycle.
+        self.bits.clear();
+        for bytes in data.chunks(lanes) {
+            for n in (0..8).rev() {
+                self.bits
+                    .extend(bytes.iter().map(|byte| (byte & (1 << n)) > 0));
+            }
+        }
+
+        for bits in self.bits.chunks(lanes) {
+            for ((pin, level), &bit) in self
+                .pins_data
+                .iter_mut()
+                .zip(&mut self.data_levels)
+                .zip(bits)
+            {
+                if bit != *level {
+                    if bit {
+                        pin.set_high();
+                    } else {
+                        pin.set_low();
+                    }
+
+                    *level = bit;
+                }
             }
+
+            self.pin_clock.set_high();
+            self.timing_strategy.wait(self.clock_pulse);
+            self.pin_clock.set_low();
         }
 
         Ok(())
     }
+
+    fn clock_speed(&self) -> u32 {
+        (1_000_000_000 / (2 * self.clock_pulse.as_nanos().max(1))) as u32
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Gpio
+    }
+
+    fn set_timing_strategy(&mut self, timing_strategy: TimingStrategy) {
+        self.timing_strategy = timing_strategy;
+    }
+
+    fn lanes(&self) -> usize {
+        self.pins_data.len()
+    }
+
+    fn set_clock_speed(&mut self, clock_speed_hz: u32) -> Result<()> {
+        self.clock_pulse =
+            Duration::from_nanos(1_000_000_000 / (2 * u64::from(clock_speed_hz.max(1))));
+
+        Ok(())
+    }
 }
 
 pub mod spi {
     pub(crate) use rppal::spi::Spi;
-    pub use rppal::spi::{Bus, Mode, SlaveSelect};
+    pub use rppal::spi::{Bus, Mode, Polarity, SlaveSelect};
 }
 
-pub struct BlinktSpi(spi::Spi);
+pub struct BlinktSpi {
+    spi: Option<spi::Spi>,
+    bus: spi::Bus,
+    slave: spi::SlaveSelect,
+    clock_speed_hz: u32,
+    mode: spi::Mode,
+    cooperative: bool,
+    ss_polarity: spi::Polarity,
+    release_delay: Duration,
+    _lock: DeviceLock,
+}
 
 impl BlinktSpi {
     pub fn with_settings(
@@ -274,32 +716,267 @@ impl BlinktSpi {
         clock_speed_hz: u32,
         mode: spi::Mode,
     ) -> Result<Self> {
-        Ok(Self(spi::Spi::new(bus, slave, clock_speed_hz, mode)?))
+        let lock = DeviceLock::acquire(&lock::spi_device(bus as u8, slave as u8))?;
+
+        Ok(Self {
+            spi: Some(spi::Spi::new(bus, slave, clock_speed_hz, mode)?),
+            bus,
+            slave,
+            clock_speed_hz,
+            mode,
+            cooperative: false,
+            ss_polarity: spi::Polarity::ActiveLow,
+            release_delay: Duration::default(),
+            _lock: lock,
+        })
+    }
+
+    /// Constructs a new `BlinktSpi` that shares the SPI bus with other devices.
+    ///
+    /// Instead of keeping the SPI device open, the bus is acquired at the start
+    /// of every frame, and released once the frame is sent. Use
+    /// `set_ss_polarity()` and `set_release_delay()` to configure slave select
+    /// handling and the time between frames and transfers to other devices.
+    /// The SPI device stays locked against other processes using this crate for
+    /// as long as the `BlinktSpi` exists.
+    ///
+    /// APA102 and SK9822 pixels don't have a slave select input, and respond to
+    /// any data sent on the bus. Sharing the bus requires additional hardware,
+    /// for instance a buffer IC that only passes through the data and clock
+    /// signals while the slave select line is active.
+    pub fn cooperative(
+        bus: spi::Bus,
+        slave: spi::SlaveSelect,
+        clock_speed_hz: u32,
+        mode: spi::Mode,
+    ) -> Result<Self> {
+        // Verify the settings, and release the bus right away.
+        let mut spi = Self::with_settings(bus, slave, clock_speed_hz, mode)?;
+        spi.spi = None;
+        spi.cooperative = true;
+
+        Ok(spi)
+    }
+
+    /// Removes the lock that marks the SPI device as in use by another process.
+    ///
+    /// See [`Blinkt::force_unlock()`] for details.
+    ///
+    /// [`Blinkt::force_unlock()`]: struct.Blinkt.html#method.force_unlock
+    pub fn force_unlock(bus: spi::Bus, slave: spi::SlaveSelect) -> Result<()> {
+        DeviceLock::force_release(&lock::spi_device(bus as u8, slave as u8))
+    }
+
+    /// Returns `true` if the SPI bus is shared with other devices.
+    pub fn is_cooperative(&self) -> bool {
+        self.cooperative
+    }
+
+    /// Returns the slave select polarity.
+    pub fn ss_polarity(&self) -> spi::Polarity {
+        self.ss_polarity
+    }
+
+    /// Sets the slave select polarity.
+    ///
+    /// By default, this is set to `Polarity::ActiveLow`.
+    pub fn set_ss_polarity(&mut self, polarity: spi::Polarity) -> Result<()> {
+        if let Some(spi) = &self.spi {
+            spi.set_ss_polarity(polarity)?;
+        }
+
+        self.ss_polarity = polarity;
+
+        Ok(())
+    }
+
+    /// Returns the delay after every frame before the SPI bus is released.
+    pub fn release_delay(&self) -> Duration {
+        self.release_delay
+    }
+
+    /// Sets the delay after every frame before the SPI bus is released to other
+    /// devices.
+    ///
+    /// By default, this is set to `0`.
+    pub fn set_release_delay(&mut self, delay: Duration) {
+        self.release_delay = delay;
+    }
+
+    fn open(&self) -> Result<spi::Spi> {
+        let spi = spi::Spi::new(self.bus, self.slave, self.clock_speed_hz, self.mode)?;
+        spi.set_ss_polarity(self.ss_polarity)?;
+
+        Ok(spi)
     }
 }
 
 impl Default for BlinktSpi {
     fn default() -> Self {
-        Self(
-            spi::Spi::new(
-                spi::Bus::Spi0,
-                spi::SlaveSelect::Ss0,
-                1_000_000,
-                spi::Mode::Mode0,
-            )
-            .expect("Can't create spi bus"),
+        Self::with_settings(
+            spi::Bus::Spi0,
+            spi::SlaveSelect::Ss0,
+            1_000_000,
+            spi::Mode::Mode0,
         )
+        .expect("Can't create spi bus")
     }
 }
 
 impl SerialOutput for BlinktSpi {
+    fn acquire(&mut self) -> Result<()> {
+        if self.spi.is_none() {
+            self.spi = Some(self.open()?);
+        }
+
+        Ok(())
+    }
+
     fn write(&mut self, data: &[u8]) -> Result<()> {
-        self.0.write(data)?;
+        if let Some(spi) = &mut self.spi {
+            spi.write(data)?;
+        }
+
+        Ok(())
+    }
+
+    fn clock_speed(&self) -> u32 {
+        self.clock_speed_hz
+    }
+
+    fn set_clock_speed(&mut self, clock_speed_hz: u32) -> Result<()> {
+        if let Some(spi) = &self.spi {
+            spi.set_clock_speed(clock_speed_hz)?;
+        }
+
+        self.clock_speed_hz = clock_speed_hz;
+
+        Ok(())
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Spi
+    }
+
+    fn release(&mut self) -> Result<()> {
+        if self.release_delay > Duration::default() {
+            thread::sleep(self.release_delay);
+        }
+
+        if self.cooperative {
+            self.spi = None;
+        }
 
         Ok(())
     }
 }
 
+type ChangeObserver = Box<dyn FnMut(&[Pixel]) + Send>;
+type ShowObserver = Box<dyn FnMut(&ShowInfo) + Send>;
+type PostProcess = Box<dyn FnMut(&mut [Pixel]) + Send>;
+type ErrorHandler = Box<dyn FnMut(&Error) -> ErrorAction + Send>;
+
+/// Actions an error handler can choose from when `show()` fails to send a frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErrorAction {
+    /// Tries to send the frame again, after a short delay.
+    ///
+    /// A frame is retried at most 5 times, after which `show()` returns the
+    /// error.
+    Retry,
+    /// Drops the frame, and returns `Ok(())` from `show()`.
+    Skip,
+    /// Returns the error from `show()`.
+    Abort,
+}
+
+/// Timing information for a frame sent by [`Blinkt::show()`].
+///
+/// [`Blinkt::show()`]: struct.Blinkt.html#method.show
+#[derive(Debug, Copy, Clone)]
+pub struct ShowInfo {
+    changed: Option<Instant>,
+    started: Instant,
+    duration: Duration,
+    num_pixels: usize,
+}
+
+impl ShowInfo {
+    /// Returns the time at which the local buffer was last changed before the
+    /// frame was sent, or `None` if it hasn't been changed since `Blinkt` was
+    /// constructed.
+    ///
+    /// Changes are tracked for the same methods that are reported to
+    /// [`Blinkt::on_change()`].
+    ///
+    /// [`Blinkt::on_change()`]: struct.Blinkt.html#method.on_change
+    pub fn changed(&self) -> Option<Instant> {
+        self.changed
+    }
+
+    /// Returns the time at which `show()` started sending the frame.
+    pub fn started(&self) -> Instant {
+        self.started
+    }
+
+    /// Returns the time at which the frame was completely sent to the pixels.
+    pub fn transmitted(&self) -> Instant {
+        self.started + self.duration
+    }
+
+    /// Returns the time it took to send the frame.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the time between the most recent change to the local buffer and
+    /// the moment the frame was completely sent to the pixels.
+    ///
+    /// If the local buffer hasn't been changed, this is the same as `duration()`.
+    pub fn latency(&self) -> Duration {
+        self.transmitted()
+            .duration_since(self.changed.unwrap_or(self.started).min(self.started))
+    }
+
+    /// Returns the number of pixels included in the frame.
+    pub fn num_pixels(&self) -> usize {
+        self.num_pixels
+    }
+}
+
+// A serial output, the range of pixels in the local buffer it's responsible for,
+// and the color order of its pixels.
+struct Output {
+    serial_output: Box<dyn SerialOutput + Send>,
+    pixels: Range<usize>,
+    end_frame: Vec<u8>,
+    color_order: ColorOrder,
+}
+
+impl Output {
+    fn new(mut serial_output: Box<dyn SerialOutput + Send>, pixels: Range<usize>) -> Self {
+        let end_frame = vec![0u8; end_frame_len(pixels.len())];
+        let color_order = serial_output.protocol().color_order();
+        serial_output.set_color_order(color_order);
+
+        Self {
+            serial_output,
+            pixels,
+            end_frame,
+            color_order,
+        }
+    }
+}
+
+// Returns the length in bytes of the end frame for the specified number of
+// pixels. Every pixel passes on the data with a delay of half a clock cycle,
+// so the end frame needs at least one additional clock cycle for every two
+// pixels, rounded up to whole bytes (8*0 for every 16 pixels). The SK9822
+// also requires a 32-bit reset frame (32*0).
+pub(crate) fn end_frame_len(num_pixels: usize) -> usize {
+    4 + (num_pixels + 15) / 16
+}
+
 /// Interface for the Pimoroni Blinkt!, and any similar APA102 or SK9822 LED
 /// strips or boards.
 ///
@@ -307,10 +984,39 @@ impl SerialOutput for BlinktSpi {
 /// data pin GPIO 23 (physical pin 16) and clock pin GPIO 24 (physical pin 18).
 /// These settings can be changed to support alternate configurations.
 pub struct Blinkt {
-    serial_output: Box<dyn SerialOutput + Send>,
+    outputs: Vec<Output>,
     pixels: Vec<Pixel>,
     clear_on_drop: bool,
-    end_frame: Vec<u8>,
+    enabled: bool,
+    tags: HashMap<String, Vec<usize>>,
+    change_observers: Vec<ChangeObserver>,
+    show_observers: Vec<ShowObserver>,
+    correction: Correction,
+    global_brightness: f32,
+    segment_corrections: Vec<(Range<usize>, Correction)>,
+    smoothing: Option<Smoothing>,
+    persistence: f32,
+    post_process: Option<PostProcess>,
+    power_model: PowerModel,
+    soft_start: Option<SoftStart>,
+    shown_current_ma: f32,
+    max_frame_rate: Option<u32>,
+    timing_strategy: TimingStrategy,
+    end_frame_len: Option<usize>,
+    last_sent: Option<Instant>,
+    pending_frame: Option<bool>,
+    last_changed: Option<Instant>,
+    last_show_info: Option<ShowInfo>,
+    paranoid: bool,
+    frame_failures: u32,
+    clean_frames: u32,
+    // Clock speed before paranoid mode first lowered it.
+    paranoid_clock_speed: Option<u32>,
+    average_frame_duration: Option<Duration>,
+    error_handler: Option<ErrorHandler>,
+    frame: Vec<Pixel>,
+    previous_frame: Vec<Pixel>,
+    sent_frame: Vec<WirePixel>,
 }
 
 impl Blinkt {
@@ -327,12 +1033,29 @@ impl Blinkt {
     /// the data pin, clock pin, and number of pixels. Pins should be specified
     /// by their BCM GPIO pin numbers.
     pub fn with_settings(pin_data: u8, pin_clock: u8, num_pixels: usize) -> Result<Self> {
-        Ok(Self {
-            serial_output: Box::new(BlinktGpio::with_settings(pin_data, pin_clock)?),
-            pixels: vec![Pixel::default(); num_pixels],
-            clear_on_drop: true,
-            end_frame: vec![0u8; 4 + (((num_pixels as f32 / 16.0f32) + 0.94f32) as usize)],
-        })
+        Ok(Self::with_output(
+            Box::new(BlinktGpio::with_settings(pin_data, pin_clock)?),
+            num_pixels,
+        ))
+    }
+
+    /// Constructs a new `Blinkt` using bitbanging mode, with the pins and number
+    /// of pixels specified by `profile`.
+    pub fn with_profile(profile: &Profile) -> Result<Self> {
+        Self::with_settings(
+            profile.pin_data(),
+            profile.pin_clock(),
+            profile.num_pixels(),
+        )
+    }
+
+    /// Constructs a new `Blinkt` using the settings for the attached board.
+    ///
+    /// Boards with an ID EEPROM are identified through `Profile::detect()`. If no
+    /// supported board is detected, the default settings for a Pimoroni Blinkt!
+    /// board are used, which doesn't have an ID EEPROM.
+    pub fn autodetect() -> Result<Self> {
+        Self::with_profile(&Profile::detect().unwrap_or_default())
     }
 
     /// Constructs a new `Blinkt` using hardware SPI, with custom settings for the
@@ -348,39 +1071,358 @@ impl Blinkt {
     /// short LED strip. Visit the [Raspberry Pi SPI Documentation](https://www.raspberrypi.org/documentation/hardware/raspberrypi/spi/)
     /// page for a complete list of supported clock speeds.
     pub fn with_spi(spi: BlinktSpi, num_pixels: usize) -> Self {
-        Self {
-            serial_output: Box::new(spi),
-            pixels: vec![Pixel::default(); num_pixels],
-            clear_on_drop: true,
-            end_frame: vec![0u8; 4 + (((num_pixels as f32 / 16.0f32) + 0.94f32) as usize)],
-        }
+        Self::with_output(Box::new(spi), num_pixels)
     }
 
-    /// Returns a mutable iterator over all `Pixel`s stored in `Blinkt`.
-    pub fn iter_mut(&mut self) -> slice::IterMut<'_, Pixel> {
-        self.pixels.iter_mut()
+    /// Constructs a new `Blinkt` using bitbanging mode, with several LED strips
+    /// connected to separate data pins that share a single clock pin. Pins should
+    /// be specified by their BCM GPIO pin numbers.
+    ///
+    /// The data for all strips is sent at the same time, which multiplies the
+    /// frame rate by the number of strips compared to a single long strip.
+    /// `num_pixels` sets the number of pixels on each strip. The local buffer
+    /// contains the pixels of all strips, in the order of `pins_data`.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::Blinkt;
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// // Three strips of 60 pixels on GPIO 17, 22 and 23, with the clock on GPIO 24.
+    /// let mut blinkt = Blinkt::with_parallel_gpio(&[17, 22, 23], 24, 60)?;
+    ///
+    /// // Pixel 60 is the first pixel on the second strip.
+    /// blinkt.set_pixel(60, 255, 0, 0);
+    /// blinkt.show()?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn with_parallel_gpio(pins_data: &[u8], pin_clock: u8, num_pixels: usize) -> Result<Self> {
+        Ok(Self::with_output(
+            Box::new(BlinktParallelGpio::with_settings(pins_data, pin_clock)?),
+            pins_data.len() * num_pixels,
+        ))
     }
 
-    /// Sets the red, green and blue values for a single pixel in the local
-    /// buffer.
+    /// Constructs a new `Blinkt` using hardware SPI if possible, or bitbanging mode
+    /// otherwise, with custom settings for the data pin, clock pin, and number of
+    /// pixels. Pins should be specified by their BCM GPIO pin numbers.
     ///
-    /// Pixels are numbered starting at `0`.
-    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
-    pub fn set_pixel(&mut self, pixel: usize, red: u8, green: u8, blue: u8) {
-        if let Some(pixel) = self.pixels.get_mut(pixel) {
-            pixel.set_rgb(red, green, blue);
+    /// Hardware SPI is only available when the data pin is set to GPIO 10
+    /// (physical pin 19) and the clock pin to GPIO 11 (physical pin 23), and SPI
+    /// is enabled on the Raspberry Pi. In all other cases, this falls back to
+    /// bitbanging mode, which means the same application works on a Raspberry Pi
+    /// with or without SPI enabled. Use `backend()` to check which backend is
+    /// used, for instance to log a warning.
+    ///
+    /// Only a missing SPI device, or missing permissions to access it, cause a
+    /// fallback to bitbanging mode. Any other errors are returned, including
+    /// `Error::DeviceInUse` when another process uses the SPI device.
+    pub fn auto_backend(pin_data: u8, pin_clock: u8, num_pixels: usize) -> Result<Self> {
+        if (pin_data, pin_clock) == (SPI_DAT, SPI_CLK) {
+            let spi = BlinktSpi::with_settings(
+                spi::Bus::Spi0,
+                spi::SlaveSelect::Ss0,
+                1_000_000,
+                spi::Mode::Mode0,
+            );
+
+            match spi {
+                Ok(spi) => return Ok(Self::with_spi(spi, num_pixels)),
+                Err(Error::Spi(SpiError::Io(ref err)))
+                    if err.kind() == io::ErrorKind::NotFound
+                        || err.kind() == io::ErrorKind::PermissionDenied => {}
+                Err(err) => return Err(err),
+            }
         }
+
+        Self::with_settings(pin_data, pin_clock, num_pixels)
     }
 
-    /// Sets the red, green, blue and brightness values for a single pixel in
-    /// the local buffer.
+    /// Removes the locks that mark the data pin and clock pin as in use by
+    /// another process.
     ///
-    /// Pixels are numbered starting at `0`.
-    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    /// Pins and SPI devices are locked by the process that uses them, so a second
+    /// process fails with `Error::DeviceInUse` rather than fighting over the same
+    /// pixels. Locks are released automatically when the process that holds
+    /// them exits, even if it crashes. Call `force_unlock()` before constructing
+    /// a new `Blinkt` to take over the pins from a process that's still running.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::Blinkt;
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let blinkt = match Blinkt::new() {
+    ///     Err(blinkt::Error::DeviceInUse(device, pid)) => {
+    ///         println!("Taking over {} from PID {}", device, pid);
+    ///
+    ///         Blinkt::force_unlock(23, 24)?;
+    ///         Blinkt::new()?
+    ///     }
+    ///     blinkt => blinkt?,
+    /// };
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn force_unlock(pin_data: u8, pin_clock: u8) -> Result<()> {
+        DeviceLock::force_release(&lock::gpio_device(pin_data))?;
+        DeviceLock::force_release(&lock::gpio_device(pin_clock))
+    }
+
+    fn with_output(serial_output: Box<dyn SerialOutput + Send>, num_pixels: usize) -> Self {
+        Self {
+            outputs: vec![Output::new(serial_output, 0..num_pixels)],
+            pixels: vec![Pixel::default(); num_pixels],
+            clear_on_drop: true,
+            enabled: true,
+            tags: HashMap::new(),
+            change_observers: Vec::new(),
+            show_observers: Vec::new(),
+            correction: Correction::new(),
+            global_brightness: 1.0,
+            segment_corrections: Vec::new(),
+            smoothing: None,
+            persistence: 0.0,
+            post_process: None,
+            power_model: PowerModel::default(),
+            soft_start: None,
+            shown_current_ma: 0.0,
+            max_frame_rate: None,
+            timing_strategy: TimingStrategy::default(),
+            end_frame_len: None,
+            last_sent: None,
+            pending_frame: None,
+            last_changed: None,
+            last_show_info: None,
+            paranoid: false,
+            frame_failures: 0,
+            clean_frames: 0,
+            paranoid_clock_speed: None,
+            average_frame_duration: None,
+            error_handler: None,
+            frame: Vec::new(),
+            previous_frame: Vec::new(),
+            sent_frame: Vec::new(),
+        }
+    }
+
+    /// Adds pixels to the end of the local buffer, which are sent through
+    /// bitbanging mode, with custom settings for the data pin, clock pin, and
+    /// number of pixels. Pins should be specified by their BCM GPIO pin numbers.
+    ///
+    /// Chaining makes it possible to treat several LED strips or boards connected
+    /// to different outputs as a single strip. `show()` sends each range of pixels
+    /// to its own output.
+    pub fn chain_gpio(&mut self, pin_data: u8, pin_clock: u8, num_pixels: usize) -> Result<()> {
+        self.chain_output(
+            Box::new(BlinktGpio::with_settings(pin_data, pin_clock)?),
+            num_pixels,
+        );
+
+        Ok(())
+    }
+
+    /// Adds pixels to the end of the local buffer, which are sent through hardware
+    /// SPI.
+    ///
+    /// Chaining makes it possible to treat several LED strips or boards connected
+    /// to different outputs as a single strip. `show()` sends each range of pixels
+    /// to its own output.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::{Blinkt, BlinktSpi};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// // Pixels 0-7 are located on a Blinkt! board, and pixels 8-151 on an
+    /// // LED strip connected to the hardware SPI pins.
+    /// let mut blinkt = Blinkt::new()?;
+    /// blinkt.chain_spi(BlinktSpi::default(), 144);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn chain_spi(&mut self, spi: BlinktSpi, num_pixels: usize) {
+        self.chain_output(Box::new(spi), num_pixels);
+    }
+
+    fn chain_output(&mut self, mut serial_output: Box<dyn SerialOutput + Send>, num_pixels: usize) {
+        let start = self.pixels.len();
+        serial_output.set_timing_strategy(self.timing_strategy);
+
+        self.outputs
+            .push(Output::new(serial_output, start..start + num_pixels));
+        self.pixels.resize(start + num_pixels, Pixel::default());
+    }
+
+    /// Returns the backend used by the first output.
+    pub fn backend(&self) -> Backend {
+        self.outputs
+            .first()
+            .map_or(Backend::Gpio, |output| output.serial_output.backend())
+    }
+
+    /// Returns the clock speed in Hz of the first output.
+    ///
+    /// In bitbanging mode with `TimingStrategy::Sleep`, the actual clock speed is
+    /// usually considerably lower, because the timing depends on the accuracy of
+    /// `thread::sleep()`.
+    pub fn clock_speed(&self) -> u32 {
+        self.outputs
+            .first()
+            .map_or(0, |output| output.serial_output.clock_speed())
+    }
+
+    /// Sets the clock speed in Hz for all outputs.
+    ///
+    /// In bitbanging mode, this changes the time the clock pin is held high
+    /// for every bit. In hardware SPI mode, this changes the SPI clock speed.
+    pub fn set_clock_speed(&mut self, clock_speed_hz: u32) -> Result<()> {
+        // Paranoid mode doesn't raise the clock speed above the new setting.
+        self.paranoid_clock_speed = None;
+
+        self.set_output_clock_speed(clock_speed_hz)
+    }
+
+    fn set_output_clock_speed(&mut self, clock_speed_hz: u32) -> Result<()> {
+        for output in &mut self.outputs {
+            output.serial_output.set_clock_speed(clock_speed_hz)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the timing strategy used in bitbanging mode.
+    pub fn timing_strategy(&self) -> TimingStrategy {
+        self.timing_strategy
+    }
+
+    /// Sets the timing strategy used in bitbanging mode for all outputs.
+    ///
+    /// `TimingStrategy::BusyWait` and `TimingStrategy::SpinYield` result in more
+    /// accurate clock pulses and higher frame rates, at the cost of a higher CPU
+    /// usage. This has no effect on hardware SPI.
+    ///
+    /// By default, this is set to `TimingStrategy::Sleep`.
+    pub fn set_timing_strategy(&mut self, timing_strategy: TimingStrategy) {
+        self.timing_strategy = timing_strategy;
+
+        for output in &mut self.outputs {
+            output.serial_output.set_timing_strategy(timing_strategy);
+        }
+    }
+
+    /// Returns the length in bytes of the end frame sent by the first output.
+    ///
+    /// Unless overridden with `set_end_frame_len()`, the end frame contains 8
+    /// zero bits for every 16 pixels, rounded up, followed by a 32-bit reset frame
+    /// required by the SK9822, which is ignored by the APA102. The length is
+    /// calculated separately for every output, based on its number of pixels.
+    pub fn end_frame_len(&self) -> usize {
+        self.end_frame_len.unwrap_or_else(|| {
+            self.outputs.first().map_or(end_frame_len(0), |output| {
+                end_frame_len(output.pixels.len())
+            })
+        })
+    }
+
+    /// Overrides the length in bytes of the end frame sent by all outputs.
+    ///
+    /// Some LED strips, or long and unusual chains of pixels, require a longer
+    /// end frame for the last pixels to update reliably. Set to `None` to
+    /// calculate the length based on the number of pixels.
+    ///
+    /// By default, this is set to `None`.
+    pub fn set_end_frame_len(&mut self, end_frame_len: Option<usize>) {
+        self.end_frame_len = end_frame_len;
+    }
+
+    /// Returns a slice containing all `Pixel`s stored in `Blinkt`.
+    pub fn as_slice(&self) -> &[Pixel] {
+        &self.pixels
+    }
+
+    /// Returns a mutable slice containing all `Pixel`s stored in `Blinkt`.
+    ///
+    /// This allows slice methods such as `rotate_left()` or `reverse()` to be
+    /// used directly on the local buffer. Changes made through the slice aren't
+    /// reported to callbacks registered with [`on_change()`].
+    ///
+    /// [`on_change()`]: #method.on_change
+    pub fn as_mut_slice(&mut self) -> &mut [Pixel] {
+        &mut self.pixels
+    }
+
+    /// Returns a mutable iterator over all `Pixel`s stored in `Blinkt`.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, Pixel> {
+        self.pixels.iter_mut()
+    }
+
+    /// Sets the red, green and blue values for a single pixel in the local
+    /// buffer.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    pub fn set_pixel(&mut self, pixel: usize, red: u8, green: u8, blue: u8) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_rgb(red, green, blue);
+            self.notify_change();
+        }
+    }
+
+    /// Sets the red, green, blue and brightness values for a single pixel in
+    /// the local buffer.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
     /// `brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%), and is converted to a 5-bit value.
     pub fn set_pixel_rgbb(&mut self, pixel: usize, red: u8, green: u8, blue: u8, brightness: f32) {
         if let Some(pixel) = self.pixels.get_mut(pixel) {
             pixel.set_rgbb(red, green, blue, brightness);
+            self.notify_change();
+        }
+    }
+
+    /// Sets the red, green, blue and white values for a single pixel in the
+    /// local buffer.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `red`, `green`, `blue` and `white` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    /// The white value is only sent to RGBW pixels, such as the SK6812 RGBW.
+    pub fn set_pixel_rgbw(&mut self, pixel: usize, red: u8, green: u8, blue: u8, white: u8) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_rgbw(red, green, blue, white);
+            self.notify_change();
+        }
+    }
+
+    /// Sets the 16-bit red, green and blue values for a single pixel in the
+    /// local buffer.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `red`, `green` and `blue` are specified as 16-bit values between `0` (0%) and `65535` (100%).
+    /// The full 16-bit values are only sent to pixels that support them, such as the
+    /// HD108. Other pixels receive the 8 most significant bits.
+    pub fn set_pixel_u16(&mut self, pixel: usize, red: u16, green: u16, blue: u16) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_rgb16(red, green, blue);
+            self.notify_change();
+        }
+    }
+
+    /// Sets the red, green and blue values for a single pixel in the local
+    /// buffer, based on hue, saturation and value (HSV).
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `hue` is specified in degrees, and wraps around at `360.0`. `saturation` and
+    /// `value` are specified as floating point values between `0.0` (0%) and `1.0` (100%).
+    pub fn set_pixel_hsv(&mut self, pixel: usize, hue: f32, saturation: f32, value: f32) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_hsv(hue, saturation, value);
+            self.notify_change();
         }
     }
 
@@ -391,6 +1433,7 @@ impl Blinkt {
     pub fn set_pixel_brightness(&mut self, pixel: usize, brightness: f32) {
         if let Some(pixel) = self.pixels.get_mut(pixel) {
             pixel.set_brightness(brightness);
+            self.notify_change();
         }
     }
 
@@ -401,6 +1444,21 @@ impl Blinkt {
         for pixel in &mut self.pixels {
             pixel.set_rgb(red, green, blue);
         }
+
+        self.notify_change();
+    }
+
+    /// Sets the red, green and blue values for all pixels in the local buffer,
+    /// based on hue, saturation and value (HSV).
+    ///
+    /// `hue` is specified in degrees, and wraps around at `360.0`. `saturation` and
+    /// `value` are specified as floating point values between `0.0` (0%) and `1.0` (100%).
+    pub fn set_all_pixels_hsv(&mut self, hue: f32, saturation: f32, value: f32) {
+        for pixel in &mut self.pixels {
+            pixel.set_hsv(hue, saturation, value);
+        }
+
+        self.notify_change();
     }
 
     /// Sets the red, green, blue and brightness values for all pixels in the
@@ -412,6 +1470,35 @@ impl Blinkt {
         for pixel in &mut self.pixels {
             pixel.set_rgbb(red, green, blue, brightness);
         }
+
+        self.notify_change();
+    }
+
+    /// Sets the 16-bit red, green and blue values for all pixels in the local
+    /// buffer.
+    ///
+    /// `red`, `green` and `blue` are specified as 16-bit values between `0` (0%) and `65535` (100%).
+    /// The full 16-bit values are only sent to pixels that support them, such as the
+    /// HD108. Other pixels receive the 8 most significant bits.
+    pub fn set_all_pixels_u16(&mut self, red: u16, green: u16, blue: u16) {
+        for pixel in &mut self.pixels {
+            pixel.set_rgb16(red, green, blue);
+        }
+
+        self.notify_change();
+    }
+
+    /// Sets the red, green, blue and white values for all pixels in the local
+    /// buffer.
+    ///
+    /// `red`, `green`, `blue` and `white` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    /// The white value is only sent to RGBW pixels, such as the SK6812 RGBW.
+    pub fn set_all_pixels_rgbw(&mut self, red: u8, green: u8, blue: u8, white: u8) {
+        for pixel in &mut self.pixels {
+            pixel.set_rgbw(red, green, blue, white);
+        }
+
+        self.notify_change();
     }
 
     /// Sets the brightness value for all pixels.
@@ -421,6 +1508,72 @@ impl Blinkt {
         for pixel in &mut self.pixels {
             pixel.set_brightness(brightness);
         }
+
+        self.notify_change();
+    }
+
+    /// Assigns a tag to one or more pixels, so they can be addressed by name
+    /// instead of by their index.
+    ///
+    /// Any pixels previously assigned to `tag` are replaced. Pixels can be
+    /// assigned multiple tags, and tags can cover any combination of pixels,
+    /// for instance `0..4` or `[1, 3, 5]`.
+    pub fn tag_pixels<I>(&mut self, tag: &str, pixels: I)
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        self.tags
+            .insert(tag.to_owned(), pixels.into_iter().collect());
+    }
+
+    /// Removes a tag, and returns the pixels it was assigned to.
+    pub fn untag(&mut self, tag: &str) -> Option<Vec<usize>> {
+        self.tags.remove(tag)
+    }
+
+    /// Returns the pixels assigned to a tag.
+    pub fn tagged_pixels(&self, tag: &str) -> Option<&[usize]> {
+        self.tags.get(tag).map(Vec::as_slice)
+    }
+
+    /// Sets the red, green and blue values for all pixels assigned to a tag in the
+    /// local buffer.
+    ///
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    pub fn set_tagged(&mut self, tag: &str, red: u8, green: u8, blue: u8) {
+        self.for_each_tagged(tag, |pixel| pixel.set_rgb(red, green, blue));
+    }
+
+    /// Sets the red, green, blue and brightness values for all pixels assigned to
+    /// a tag in the local buffer.
+    ///
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    /// `brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%), and is converted to a 5-bit value.
+    pub fn set_tagged_rgbb(&mut self, tag: &str, red: u8, green: u8, blue: u8, brightness: f32) {
+        self.for_each_tagged(tag, |pixel| pixel.set_rgbb(red, green, blue, brightness));
+    }
+
+    /// Sets the brightness value for all pixels assigned to a tag in the local
+    /// buffer.
+    ///
+    /// `brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%), and is converted to a 5-bit value.
+    pub fn set_tagged_brightness(&mut self, tag: &str, brightness: f32) {
+        self.for_each_tagged(tag, |pixel| pixel.set_brightness(brightness));
+    }
+
+    fn for_each_tagged<F>(&mut self, tag: &str, mut f: F)
+    where
+        F: FnMut(&mut Pixel),
+    {
+        if let Some(indices) = self.tags.get(tag) {
+            for &index in indices {
+                if let Some(pixel) = self.pixels.get_mut(index) {
+                    f(pixel);
+                }
+            }
+
+            self.notify_change();
+        }
     }
 
     /// Sets the red, green and blue values for all pixels to `0`.
@@ -428,65 +1581,1340 @@ impl Blinkt {
         self.set_all_pixels(0, 0, 0);
     }
 
+    /// Draws an anti-aliased point of light with sub-pixel precision.
+    ///
+    /// `position` and `width` are measured in pixels, where pixel `n` covers the
+    /// range from `n` to `n + 1`, so a `position` of `0.5` is centered on the first
+    /// pixel. Every pixel covered by the point has the color added to its current
+    /// value, scaled by the fraction of the pixel that's covered.
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    pub fn draw_point_f(&mut self, position: f32, red: u8, green: u8, blue: u8, width: f32) {
+        let half_width = width.max(0.0) / 2.0;
+
+        self.draw_span_f(
+            position - half_width,
+            position + half_width,
+            red,
+            green,
+            blue,
+        );
+    }
+
+    /// Draws an anti-aliased span of light with sub-pixel precision.
+    ///
+    /// `start` and `end` are measured in pixels, where pixel `n` covers the range
+    /// from `n` to `n + 1`. Every pixel covered by the span has the color added to
+    /// its current value, scaled by the fraction of the pixel that's covered.
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    pub fn draw_span_f(&mut self, start: f32, end: f32, red: u8, green: u8, blue: u8) {
+        let (start, end) = if start <= end {
+            (start, end)
+        } else {
+            (end, start)
+        };
+
+        for (index, pixel) in self.pixels.iter_mut().enumerate() {
+            let coverage = (end.min(index as f32 + 1.0) - start.max(index as f32)).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                let (r, g, b) = pixel.rgb();
+                pixel.set_rgb(
+                    r.saturating_add((f32::from(red) * coverage).round() as u8),
+                    g.saturating_add((f32::from(green) * coverage).round() as u8),
+                    b.saturating_add((f32::from(blue) * coverage).round() as u8),
+                );
+            }
+        }
+
+        self.notify_change();
+    }
+
+    /// Applies a group of changes to the local buffer all at once.
+    ///
+    /// The closure receives a [`Transaction`] that stages all changes on a copy
+    /// of the local buffer. Once the closure returns, the changes are committed
+    /// in a single step, so nothing ever observes a partially applied update.
+    /// If [`Transaction::show()`] was called, the local buffer is sent to the
+    /// pixels after the changes are committed.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::Blinkt;
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #     let mut blinkt = Blinkt::new()?;
+    /// blinkt.transaction(|tx| {
+    ///     tx.fill(0, 0, 255);
+    ///     tx.set_pixel(0, 255, 0, 0);
+    ///     tx.show();
+    /// })?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Transaction`]: struct.Transaction.html
+    /// [`Transaction::show()`]: struct.Transaction.html#method.show
+    pub fn transaction<F, T>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Transaction) -> T,
+    {
+        let mut transaction = Transaction::new(&self.pixels);
+        let result = f(&mut transaction);
+
+        let (pixels, show) = transaction.into_parts();
+        if let Some(pixels) = pixels {
+            self.pixels = pixels;
+            self.notify_change();
+        }
+
+        if show {
+            self.show()?;
+        }
+
+        Ok(result)
+    }
+
     /// Sends the contents of the local buffer to the pixels, updating their
     /// LED colors and brightness.
     pub fn show(&mut self) -> Result<()> {
-        // Start frame (32*0).
-        self.serial_output.write(&[0u8; 4])?;
+        self.show_frame(false).map(|_| ())
+    }
 
-        // LED frames (3*1, 5*brightness, 8*blue, 8*green, 8*red).
-        for pixel in &self.pixels {
-            self.serial_output.write(pixel.bytes())?;
+    /// Sends the contents of the local buffer to the pixels, unless the pixels
+    /// already show the exact same frame.
+    ///
+    /// `show_diff()` keeps a copy of the last frame that was sent successfully,
+    /// and compares it to the new frame after all processing and corrections
+    /// are applied. This avoids unnecessary transfers for applications that call
+    /// `show()` on a fixed interval, regardless of any changes. Returns `true`
+    /// if the frame was sent.
+    pub fn show_diff(&mut self) -> Result<bool> {
+        self.show_frame(true)
+    }
+
+    fn show_frame(&mut self, diff: bool) -> Result<bool> {
+        let started = Instant::now();
+
+        // Skip frames that exceed the maximum frame rate. The local buffer is
+        // sent by the first call to show() or flush() after the minimum
+        // interval passed.
+        if self.frame_interval_remaining(started).is_some() {
+            self.pending_frame = Some(diff && self.pending_frame != Some(false));
+            return Ok(false);
         }
 
-        // End frame (8*0 for every 16 pixels, 32*0 SK9822 reset frame).
-        // The SK9822 won't update any pixels until it receives the next
-        // start frame (32*0). The APA102 doesn't care if we send zeroes
-        // instead of ones as the end frame. This workaround is
-        // compatible with both the APA102 and SK9822.
-        self.serial_output.write(&self.end_frame)?;
+        self.pending_frame = None;
+        let last_sent = self.last_sent.replace(started);
+        if !self.write_frame(diff)? {
+            self.last_sent = last_sent;
+            return Ok(false);
+        }
 
-        Ok(())
+        let info = ShowInfo {
+            changed: self.last_changed,
+            started,
+            duration: started.elapsed(),
+            num_pixels: self.pixels.len(),
+        };
+
+        for observer in &mut self.show_observers {
+            observer(&info);
+        }
+
+        self.last_show_info = Some(info);
+
+        Ok(true)
     }
 
-    /// Returns the value of `clear_on_drop`.
-    pub fn clear_on_drop(&self) -> bool {
-        self.clear_on_drop
+    /// Sends the most recent frame skipped by `show()` or `show_diff()` because
+    /// it exceeded the maximum frame rate, waiting until enough time has passed
+    /// since the previous frame.
+    ///
+    /// Call `flush()` after a burst of changes to make sure the last frame of
+    /// the burst is shown. Returns `true` if a frame was sent.
+    pub fn flush(&mut self) -> Result<bool> {
+        let diff = match self.pending_frame {
+            Some(diff) => diff,
+            None => return Ok(false),
+        };
+
+        if let Some(remaining) = self.frame_interval_remaining(Instant::now()) {
+            thread::sleep(remaining);
+        }
+
+        self.show_frame(diff)
     }
 
-    /// When enabled, clears all pixels when `Blinkt` goes out of scope.
+    // Returns the time left until the maximum frame rate allows another frame
+    // to be sent, or `None` if a frame can be sent right away.
+    fn frame_interval_remaining(&self, now: Instant) -> Option<Duration> {
+        let interval = Duration::from_secs(1) / self.max_frame_rate?;
+        let elapsed = now.saturating_duration_since(self.last_sent?);
+
+        interval
+            .checked_sub(elapsed)
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Returns the timing information for the most recent frame sent by `show()`,
+    /// or `None` if no frames were sent yet.
     ///
-    /// By default, this is set to `true`.
+    /// This can be used to measure the latency between changes to the local
+    /// buffer and the moment they're visible, for instance for displays that
+    /// react to audio or games.
+    pub fn last_show_info(&self) -> Option<ShowInfo> {
+        self.last_show_info
+    }
+
+    /// Registers a callback that's called whenever the local buffer is changed.
     ///
-    /// ## Note
+    /// The callback receives the contents of the local buffer after the change.
+    /// Changes are reported by all `set_` methods, `clear()`, the `draw_` methods,
+    /// and once for every committed [`transaction()`]. Changes made to pixels
+    /// directly through `iter_mut()` or `as_mut_slice()` aren't reported.
     ///
-    /// Drop methods aren't called when a process is abnormally terminated, for
-    /// instance when a user presses <kbd>Ctrl</kbd> + <kbd>C</kbd>, and the `SIGINT` signal
-    /// isn't caught. You can catch those using crates such as [`simple_signal`].
+    /// To receive changes on a different thread, send them through a channel
+    /// from within the callback.
     ///
-    /// [`simple_signal`]: https://crates.io/crates/simple-signal
-    pub fn set_clear_on_drop(&mut self, clear_on_drop: bool) {
-        self.clear_on_drop = clear_on_drop;
+    /// [`transaction()`]: #method.transaction
+    pub fn on_change<F>(&mut self, observer: F)
+    where
+        F: FnMut(&[Pixel]) + Send + 'static,
+    {
+        self.change_observers.push(Box::new(observer));
     }
-}
 
-impl Drop for Blinkt {
-    /// Clears all pixels if [`clear_on_drop`] is set to `true` (default).
+    /// Registers a callback that's called whenever `show()` successfully sent the
+    /// contents of the local buffer to the pixels.
     ///
-    /// [`clear_on_drop`]: #method.clear_on_drop
-    fn drop(&mut self) {
-        if self.clear_on_drop {
-            self.clear();
-            let _ = self.show();
+    /// The callback receives a [`ShowInfo`] containing timing information.
+    ///
+    /// [`ShowInfo`]: struct.ShowInfo.html
+    pub fn on_show<F>(&mut self, observer: F)
+    where
+        F: FnMut(&ShowInfo) + Send + 'static,
+    {
+        self.show_observers.push(Box::new(observer));
+    }
+
+    pub(crate) fn notify_change(&mut self) {
+        self.last_changed = Some(Instant::now());
+
+        for observer in &mut self.change_observers {
+            observer(&self.pixels);
         }
     }
-}
 
-impl<'a> IntoIterator for &'a mut Blinkt {
-    type Item = &'a mut Pixel;
-    type IntoIter = slice::IterMut<'a, Pixel>;
+    // Copies the local buffer to the frame buffer, and applies all processing.
+    fn process_frame(&mut self) {
+        // Any processing is applied to a copy of the local buffer, so the stored
+        // pixel values remain unchanged.
+        self.frame.clear();
+        self.frame.extend_from_slice(&self.pixels);
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter_mut()
+        if let Some(smoothing) = &mut self.smoothing {
+            smoothing.apply(&mut self.frame);
+        }
+
+        if self.persistence > 0.0 {
+            if self.previous_frame.len() == self.frame.len() {
+                let fade = |value: u8| (f32::from(value) * self.persistence) as u8;
+
+                for (pixel, previous) in self.frame.iter_mut().zip(&self.previous_frame) {
+                    let (red, green, blue) = pixel.rgb();
+                    let (prev_red, prev_green, prev_blue) = previous.rgb();
+
+                    pixel.set_rgb(
+                        red.max(fade(prev_red)),
+                        green.max(fade(prev_green)),
+                        blue.max(fade(prev_blue)),
+                    );
+                }
+            }
+
+            self.previous_frame.clone_from(&self.frame);
+        }
+
+        if let Some(post_process) = &mut self.post_process {
+            post_process(&mut self.frame);
+        }
+
+        if !self.enabled {
+            self.frame.iter_mut().for_each(Pixel::clear);
+        }
+    }
+
+    // Processes and sends the local buffer. If `diff` is set, frames that are
+    // identical to the last frame that was sent successfully are skipped.
+    fn write_frame(&mut self, diff: bool) -> Result<bool> {
+        self.process_frame();
+
+        if diff && self.frame_matches_sent() {
+            return Ok(false);
+        }
+
+        let current_ma = self.frame_current_ma();
+        if let Some(soft_start) = self.soft_start {
+            if current_ma - self.shown_current_ma > soft_start.threshold_ma() {
+                self.ramp_frame(soft_start, current_ma)?;
+            }
+        }
+
+        self.send_frame()?;
+        self.shown_current_ma = current_ma;
+
+        Ok(true)
+    }
+
+    fn frame_matches_sent(&self) -> bool {
+        self.frame.len() == self.sent_frame.len()
+            && self
+                .frame
+                .iter()
+                .enumerate()
+                .all(|(index, pixel)| self.preview_at(index, pixel) == self.sent_frame[index])
+    }
+
+    fn frame_current_ma(&self) -> f32 {
+        self.frame
+            .iter()
+            .enumerate()
+            .map(|(index, pixel)| self.power_model.current_ma(&self.preview_at(index, pixel)))
+            .sum()
+    }
+
+    // Sends intermediate frames with scaled down color values, so the current
+    // gradually increases to the current of the frame buffer.
+    fn ramp_frame(&mut self, soft_start: SoftStart, current_ma: f32) -> Result<()> {
+        let idle_ma = self.power_model.idle_ma() * self.frame.len() as f32;
+        let start_ma = self.shown_current_ma.max(idle_ma);
+        if current_ma <= idle_ma {
+            return Ok(());
+        }
+
+        let target = self.frame.clone();
+        let steps = (soft_start.duration().as_millis() / SOFT_START_INTERVAL.as_millis()) as u32;
+
+        for step in 1..steps {
+            let step_ma = start_ma + (current_ma - start_ma) * (step as f32 / steps as f32);
+
+            // The color correction isn't linear, so the scale is found by
+            // bisection on the estimated current of the corrected frame.
+            let (mut low, mut high) = (0.0, 1.0);
+            for _ in 0..SOFT_START_ITERATIONS {
+                let scale = (low + high) / 2.0;
+                self.scale_frame(&target, scale);
+
+                if self.frame_current_ma() <= step_ma {
+                    low = scale;
+                } else {
+                    high = scale;
+                }
+            }
+
+            self.scale_frame(&target, low);
+            self.send_frame()?;
+            self.shown_current_ma = self.frame_current_ma();
+
+            thread::sleep(SOFT_START_INTERVAL);
+        }
+
+        self.frame = target;
+
+        Ok(())
+    }
+
+    // Sets the frame buffer to `target`, with its color values scaled by `scale`.
+    fn scale_frame(&mut self, target: &[Pixel], scale: f32) {
+        for (pixel, target) in self.frame.iter_mut().zip(target) {
+            let (red, green, blue) = target.rgb();
+            pixel.set_rgb(
+                (f32::from(red) * scale) as u8,
+                (f32::from(green) * scale) as u8,
+                (f32::from(blue) * scale) as u8,
+            );
+        }
+    }
+
+    // Sends the frame buffer to the pixels. Any errors are passed on to the
+    // error handler to decide how to proceed.
+    fn send_frame(&mut self) -> Result<()> {
+        let mut retries = 0;
+
+        loop {
+            let started = Instant::now();
+            let result = self.write_outputs();
+
+            if self.paranoid {
+                self.monitor_frame(result.is_ok(), started.elapsed())?;
+            }
+
+            let err = match result {
+                Ok(()) => return Ok(()),
+                Err(err) => err,
+            };
+
+            let action = match &mut self.error_handler {
+                Some(error_handler) => error_handler(&err),
+                None => ErrorAction::Abort,
+            };
+
+            match action {
+                ErrorAction::Retry if retries < MAX_RETRIES => {
+                    retries += 1;
+                    thread::sleep(RETRY_DELAY * retries);
+                }
+                ErrorAction::Retry | ErrorAction::Abort => return Err(err),
+                ErrorAction::Skip => return Ok(()),
+            }
+        }
+    }
+
+    // Keeps track of failed and unusually slow frames, and lowers the clock
+    // speed when too many of them occur in a row. The clock speed is raised
+    // again, up to its original setting, once enough frames in a row were sent
+    // without problems.
+    fn monitor_frame(&mut self, success: bool, duration: Duration) -> Result<()> {
+        // With Sleep, the duration of a bitbanged frame depends on the scheduler
+        // rather than the connection, so slow frames are expected.
+        let sleeps = self.timing_strategy == TimingStrategy::Sleep
+            && self
+                .outputs
+                .iter()
+                .any(|output| output.serial_output.backend() == Backend::Gpio);
+        let timeout = !sleeps
+            && self.average_frame_duration.map_or(false, |average| {
+                duration > (average * PARANOID_TIMEOUT_FACTOR).max(PARANOID_MIN_TIMEOUT)
+            });
+
+        if success && !timeout {
+            self.frame_failures = 0;
+            self.average_frame_duration = Some(
+                self.average_frame_duration
+                    .map_or(duration, |average| (average * 7 + duration) / 8),
+            );
+
+            self.clean_frames += 1;
+            if self.clean_frames >= PARANOID_RECOVERY_FRAMES {
+                self.clean_frames = 0;
+
+                if let Some(original) = self.paranoid_clock_speed {
+                    let clock_speed = self.clock_speed().saturating_mul(2).min(original);
+                    if clock_speed == original {
+                        self.paranoid_clock_speed = None;
+                    }
+
+                    self.average_frame_duration = None;
+                    self.set_output_clock_speed(clock_speed)?;
+                }
+            }
+
+            return Ok(());
+        }
+
+        self.clean_frames = 0;
+        self.frame_failures += 1;
+        if self.frame_failures >= PARANOID_MAX_FAILURES {
+            let original = self.clock_speed();
+            let clock_speed = (original / 2).max(PARANOID_MIN_CLOCK_SPEED);
+
+            self.paranoid_clock_speed.get_or_insert(original);
+            self.frame_failures = 0;
+            self.average_frame_duration = None;
+            self.set_output_clock_speed(clock_speed)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_outputs(&mut self) -> Result<()> {
+        // Frames that fail halfway through leave a partial copy behind, which
+        // never matches the next frame.
+        self.sent_frame.clear();
+
+        for output in 0..self.outputs.len() {
+            match self.outputs[output].serial_output.protocol() {
+                Protocol::Apa102 if self.outputs[output].serial_output.lanes() > 1 => {
+                    self.write_lanes(output)?
+                }
+                Protocol::Apa102 => {
+                    // The output is released even if the frame couldn't be sent.
+                    self.outputs[output].serial_output.acquire()?;
+                    let result = self.write_apa102(output);
+                    let released = self.outputs[output].serial_output.release();
+
+                    result.and(released)?
+                }
+                Protocol::Ws2812 | Protocol::Sk6812Rgbw => self.write_ws2812(output)?,
+                Protocol::Hd108 => self.write_hd108(output)?,
+                Protocol::P9813 => self.write_p9813(output)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    // Sends the frame buffer to an acquired APA102 or SK9822 output.
+    fn write_apa102(&mut self, output: usize) -> Result<()> {
+        // Start frame (32*0).
+        self.outputs[output].serial_output.write(&[0u8; 4])?;
+
+        // LED frames (3*1, 5*brightness, 8*blue, 8*green, 8*red).
+        let color_order = self.outputs[output].color_order;
+        for index in self.outputs[output].pixels.clone() {
+            let wire_pixel = self.preview_at(index, &self.frame[index]);
+            self.outputs[output]
+                .serial_output
+                .write(&wire_pixel.bytes_ordered(color_order))?;
+            self.sent_frame.push(wire_pixel);
+        }
+
+        // End frame (8*0 for every 16 pixels, 32*0 SK9822 reset frame).
+        // The SK9822 won't update any pixels until it receives the next
+        // start frame (32*0). The APA102 doesn't care if we send zeroes
+        // instead of ones as the end frame. This workaround is
+        // compatible with both the APA102 and SK9822.
+        let output = &mut self.outputs[output];
+        let len = self
+            .end_frame_len
+            .unwrap_or_else(|| end_frame_len(output.pixels.len()));
+        output.end_frame.resize(len, 0);
+        output.serial_output.write(&output.end_frame)
+    }
+
+    // Sends the frame buffer to an output with multiple lanes, with the pixels
+    // of the output evenly divided between the lanes.
+    fn write_lanes(&mut self, output: usize) -> Result<()> {
+        let lanes = self.outputs[output].serial_output.lanes();
+        let pixels = self.outputs[output].pixels.clone();
+        let color_order = self.outputs[output].color_order;
+        let lane_pixels = pixels.len() / lanes;
+        let end_frame_len = self
+            .end_frame_len
+            .unwrap_or_else(|| end_frame_len(lane_pixels));
+        let lane_len = 4 + lane_pixels * 4 + end_frame_len;
+
+        let mut data = vec![0u8; lane_len * lanes];
+        for (offset, index) in pixels.enumerate() {
+            let wire_pixel = self.preview_at(index, &self.frame[index]);
+            let (lane, pixel) = (offset / lane_pixels, offset % lane_pixels);
+
+            // Interleave the bytes of all lanes, after the start frame (32*0).
+            for (byte, value) in wire_pixel.bytes_ordered(color_order).iter().enumerate() {
+                data[(4 + pixel * 4 + byte) * lanes + lane] = *value;
+            }
+
+            self.sent_frame.push(wire_pixel);
+        }
+
+        let serial_output = &mut self.outputs[output].serial_output;
+        serial_output.acquire()?;
+        let result = serial_output.write(&data);
+        let released = serial_output.release();
+
+        result.and(released)
+    }
+
+    /// Returns the color correction applied to all pixels that aren't part of a
+    /// segment with its own correction.
+    pub fn correction(&self) -> &Correction {
+        &self.correction
+    }
+
+    /// Returns the gamma value of the color correction applied to all pixels
+    /// that aren't part of a segment with its own correction.
+    pub fn gamma(&self) -> f32 {
+        self.correction.gamma()
+    }
+
+    /// Sets the gamma value of the color correction applied to all pixels that
+    /// aren't part of a segment with its own correction.
+    ///
+    /// This is a shortcut for `Correction::set_gamma()`. A `gamma` of `1.0`
+    /// leaves the red, green and blue values unchanged. Values around `2.2`
+    /// result in smoother fades and less washed out colors. Like any correction,
+    /// gamma correction is applied by `show()`, and doesn't change the stored
+    /// pixel values.
+    ///
+    /// By default, this is set to `1.0`.
+    pub fn set_gamma(&mut self, gamma: f32) {
+        self.correction.set_gamma(gamma);
+    }
+
+    /// Sets the color correction applied to all pixels that aren't part of a
+    /// segment with its own correction.
+    ///
+    /// The correction is applied when the local buffer is sent to the pixels
+    /// by `show()`, and doesn't change the stored pixel values. By default,
+    /// all values are left unchanged.
+    pub fn set_correction(&mut self, correction: Correction) {
+        self.correction = correction;
+    }
+
+    /// Sets the color correction applied to a segment of pixels, replacing the
+    /// default correction set with `set_correction()` for those pixels.
+    ///
+    /// Pixels are numbered starting at `0`. When segments overlap, the most
+    /// recently added correction takes precedence.
+    pub fn set_segment_correction(&mut self, pixels: Range<usize>, correction: Correction) {
+        self.segment_corrections.push((pixels, correction));
+    }
+
+    /// Returns the values that would be sent for `pixel` with the current
+    /// correction and global brightness settings.
+    ///
+    /// This shows how a color is quantized before it's sent to the pixels, which
+    /// explains why two slightly different dark colors can look identical.
+    /// Segment corrections aren't taken into account. Use `preview_at()` to
+    /// preview a color at a specific position.
+    pub fn preview(&self, pixel: &Pixel) -> WirePixel {
+        self.scale_brightness(self.correction.apply(pixel))
+    }
+
+    /// Returns the values that would be sent for `pixel` at position `index`
+    /// with the current correction and global brightness settings, including
+    /// any segment corrections.
+    ///
+    /// Pixels are numbered starting at `0`.
+    pub fn preview_at(&self, index: usize, pixel: &Pixel) -> WirePixel {
+        let wire_pixel = self.correction_at(index).apply(pixel);
+
+        self.scale_brightness(wire_pixel)
+    }
+
+    // Returns the correction for the pixel at the specified index, taking segment
+    // corrections into account.
+    pub(crate) fn correction_at(&self, index: usize) -> &Correction {
+        self.segment_corrections
+            .iter()
+            .rev()
+            .find(|(range, _)| range.contains(&index))
+            .map_or(&self.correction, |(_, correction)| correction)
+    }
+
+    fn scale_brightness(&self, wire_pixel: WirePixel) -> WirePixel {
+        if self.global_brightness >= 1.0 {
+            return wire_pixel;
+        }
+
+        let brightness = (f32::from(wire_pixel.brightness()) * self.global_brightness).round();
+
+        wire_pixel.with_brightness(brightness as u8)
+    }
+
+    /// Returns the order in which the red, green and blue values are sent to
+    /// the first output.
+    pub fn color_order(&self) -> ColorOrder {
+        self.outputs
+            .first()
+            .map_or_else(ColorOrder::default, |output| output.color_order)
+    }
+
+    /// Sets the order in which the red, green and blue values are sent by
+    /// `show()` for all outputs, for LED strips that don't use the standard
+    /// order of their protocol.
+    ///
+    /// By default, this is set to `ColorOrder::Bgr` for APA102, SK9822 and
+    /// P9813 outputs, `ColorOrder::Grb` for WS2812 and SK6812 RGBW outputs, and
+    /// `ColorOrder::Rgb` for HD108 outputs. Use `set_output_color_order()` to
+    /// change the color order of a single output.
+    pub fn set_color_order(&mut self, color_order: ColorOrder) {
+        for output in 0..self.outputs.len() {
+            self.set_output_color_order(output, color_order);
+        }
+    }
+
+    /// Returns the order in which the red, green and blue values are sent to
+    /// `output`, or `None` if the output doesn't exist.
+    ///
+    /// Outputs are numbered in the order they were added, with `0` for the
+    /// output the `Blinkt` was constructed with, followed by any chained
+    /// outputs.
+    pub fn output_color_order(&self, output: usize) -> Option<ColorOrder> {
+        self.outputs.get(output).map(|output| output.color_order)
+    }
+
+    /// Sets the order in which the red, green and blue values are sent to
+    /// `output`. Outputs that don't exist are ignored.
+    ///
+    /// See `output_color_order()` for the numbering of the outputs.
+    ///
+    /// ```rust
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::{Blinkt, ColorOrder, MockOutput};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mut blinkt = Blinkt::with_driver(MockOutput::new(), 8);
+    /// blinkt.chain_ws2812_driver(MockOutput::new(), 60);
+    ///
+    /// // Only the chained strip uses a different order.
+    /// blinkt.set_output_color_order(1, ColorOrder::Rgb);
+    ///
+    /// assert_eq!(blinkt.output_color_order(0), Some(ColorOrder::Bgr));
+    /// assert_eq!(blinkt.output_color_order(1), Some(ColorOrder::Rgb));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn set_output_color_order(&mut self, output: usize, color_order: ColorOrder) {
+        if let Some(output) = self.outputs.get_mut(output) {
+            output.color_order = color_order;
+            output.serial_output.set_color_order(color_order);
+        }
+    }
+
+    /// Returns the global brightness.
+    pub fn global_brightness(&self) -> f32 {
+        self.global_brightness
+    }
+
+    /// Sets the global brightness, which scales the brightness of all pixels
+    /// when they're sent by `show()`.
+    ///
+    /// This dims everything that's shown, without changing the stored pixel
+    /// values. The scaled brightness is rounded to the nearest 5-bit value, so
+    /// dim pixels may turn off completely at a low global brightness.
+    /// `global_brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%).
+    ///
+    /// By default, this is set to `1.0`.
+    pub fn set_global_brightness(&mut self, global_brightness: f32) {
+        self.global_brightness = global_brightness.clamp(0.0, 1.0);
+    }
+
+    /// Removes all segment corrections set with `set_segment_correction()`.
+    pub fn clear_segment_corrections(&mut self) {
+        self.segment_corrections.clear();
+    }
+
+    /// Returns the temporal smoothing filter applied by `show()`.
+    pub fn smoothing(&self) -> Option<&Smoothing> {
+        self.smoothing.as_ref()
+    }
+
+    /// Sets a temporal smoothing filter, which `show()` applies to every frame
+    /// before it's sent to the pixels. The stored pixel values aren't changed.
+    ///
+    /// By default, no smoothing is applied.
+    pub fn set_smoothing(&mut self, smoothing: Option<Smoothing>) {
+        self.smoothing = smoothing;
+    }
+
+    /// Returns the persistence value.
+    pub fn persistence(&self) -> f32 {
+        self.persistence
+    }
+
+    /// Sets the persistence value, which leaves a fading trail behind moving
+    /// lights, similar to the afterglow of a phosphor screen.
+    ///
+    /// Before each frame is sent by `show()`, the previously sent frame is faded
+    /// by the persistence value, and every color value of the new frame that's
+    /// darker than its faded counterpart is replaced. This is applied regardless
+    /// of how the frame was created, and doesn't change the stored pixel values.
+    ///
+    /// `persistence` is specified as a floating point value between `0.0` (0%) and `1.0` (100%).
+    /// By default, this is set to `0.0`, which disables the effect.
+    pub fn set_persistence(&mut self, persistence: f32) {
+        self.persistence = persistence.clamp(0.0, 1.0);
+        self.previous_frame.clear();
+    }
+
+    /// Sets a post-processing function, which `show()` calls for every frame
+    /// after all other processing is applied, and before the frame is sent to
+    /// the pixels.
+    ///
+    /// The function receives a copy of the local buffer, so any changes only
+    /// affect the frame that's being sent. This makes it possible to install
+    /// cross-cutting adjustments like a global tint or a vignette in one place.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::Blinkt;
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #     let mut blinkt = Blinkt::new()?;
+    /// // Remove all blue light.
+    /// blinkt.set_post_process(|pixels| {
+    ///     for pixel in pixels {
+    ///         pixel.set_blue(0);
+    ///     }
+    /// });
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn set_post_process<F>(&mut self, post_process: F)
+    where
+        F: FnMut(&mut [Pixel]) + Send + 'static,
+    {
+        self.post_process = Some(Box::new(post_process));
+    }
+
+    /// Removes the post-processing function set with `set_post_process()`.
+    pub fn clear_post_process(&mut self) {
+        self.post_process = None;
+    }
+
+    /// Returns `true` if output is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables output.
+    ///
+    /// While output is disabled, `show()` turns off all pixels, but the local
+    /// buffer is left unchanged. Once output is enabled again, the next call to
+    /// `show()` restores the pixels to their stored values. This is useful to
+    /// temporarily turn off all pixels, for instance during quiet hours, without
+    /// losing the current state.
+    ///
+    /// By default, this is set to `true`.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns the power model used by `estimated_current_ma()`.
+    pub fn power_model(&self) -> PowerModel {
+        self.power_model
+    }
+
+    /// Sets the power model used by `estimated_current_ma()`.
+    ///
+    /// By default, this is set to a model based on a typical APA102 pixel.
+    pub fn set_power_model(&mut self, power_model: PowerModel) {
+        self.power_model = power_model;
+    }
+
+    /// Returns the estimated current in mA drawn by all pixels when the local
+    /// buffer is sent.
+    ///
+    /// The estimate is calculated with the current power model, and takes any
+    /// corrections and the output state into account. Effects that depend on
+    /// previous frames, like smoothing and persistence, as well as any
+    /// post-processing, aren't included.
+    pub fn estimated_current_ma(&self) -> f32 {
+        self.pixels
+            .iter()
+            .enumerate()
+            .map(|(index, pixel)| {
+                if self.enabled {
+                    self.power_model.current_ma(&self.preview_at(index, pixel))
+                } else {
+                    self.power_model.idle_ma()
+                }
+            })
+            .sum()
+    }
+
+    /// Returns the soft start settings.
+    pub fn soft_start(&self) -> Option<SoftStart> {
+        self.soft_start
+    }
+
+    /// Sets the soft start settings, which make `show()` gradually ramp up to
+    /// frames that draw considerably more current than the previous frame.
+    ///
+    /// While ramping up, `show()` blocks until the new frame is reached.
+    /// By default, soft start is disabled.
+    pub fn set_soft_start(&mut self, soft_start: Option<SoftStart>) {
+        self.soft_start = soft_start;
+    }
+
+    /// Returns the maximum frame rate.
+    pub fn max_frame_rate(&self) -> Option<u32> {
+        self.max_frame_rate
+    }
+
+    /// Sets the maximum number of frames per second sent by `show()`.
+    ///
+    /// Calls to `show()` that would exceed the maximum frame rate return
+    /// immediately without sending anything. Any changes to the local buffer
+    /// are sent by the first call to `show()` once enough time has passed, or by
+    /// `flush()`, which waits for the interval to pass. This
+    /// protects against runaway loops saturating the SPI bus, which could starve
+    /// other devices sharing the same bus.
+    ///
+    /// By default, the frame rate isn't limited.
+    pub fn set_max_frame_rate(&mut self, max_frame_rate: Option<u32>) {
+        self.max_frame_rate = max_frame_rate.filter(|&max_frame_rate| max_frame_rate > 0);
+    }
+
+    /// Returns `true` if paranoid mode is enabled.
+    pub fn is_paranoid(&self) -> bool {
+        self.paranoid
+    }
+
+    /// Enables or disables paranoid mode.
+    ///
+    /// In paranoid mode, `show()` keeps track of frames that the output fails
+    /// to send, and frames that take more than four times as long to send as
+    /// the recent average, and over 10 ms. After three of these problems in a
+    /// row, the clock speed of all outputs is halved, down to a minimum of
+    /// 100 kHz. Once 1000 frames in a row are sent without problems, the clock
+    /// speed is doubled again, up to the clock speed that was set before
+    /// paranoid mode first lowered it. This helps unattended installations
+    /// recover from signal integrity problems. Any errors are still returned by
+    /// `show()`.
+    ///
+    /// The pixels can't send any data back, so frames that are corrupted on the
+    /// way to the pixels aren't detected. In bitbanging mode with
+    /// `TimingStrategy::Sleep`, the time it takes to send a frame depends on the
+    /// scheduler, so only failed frames are counted.
+    ///
+    /// By default, this is set to `false`.
+    pub fn set_paranoid(&mut self, paranoid: bool) {
+        self.paranoid = paranoid;
+        self.frame_failures = 0;
+        self.clean_frames = 0;
+        self.average_frame_duration = None;
+    }
+
+    /// Sets an error handler, which is called whenever `show()` fails to send a
+    /// frame, and decides whether to retry, skip the frame or return the error.
+    ///
+    /// Without an error handler, `show()` returns the error. An error handler lets
+    /// long-running applications recover from occasional write errors.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::{Blinkt, ErrorAction};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #     let mut blinkt = Blinkt::new()?;
+    /// let mut retries = 0;
+    ///
+    /// blinkt.set_error_handler(move |err| {
+    ///     eprintln!("Failed to send frame: {}", err);
+    ///
+    ///     retries += 1;
+    ///     if retries % 3 == 0 {
+    ///         ErrorAction::Skip
+    ///     } else {
+    ///         ErrorAction::Retry
+    ///     }
+    /// });
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn set_error_handler<F>(&mut self, error_handler: F)
+    where
+        F: FnMut(&Error) -> ErrorAction + Send + 'static,
+    {
+        self.error_handler = Some(Box::new(error_handler));
+    }
+
+    /// Removes the error handler set with `set_error_handler()`.
+    pub fn clear_error_handler(&mut self) {
+        self.error_handler = None;
+    }
+
+    /// Returns the value of `clear_on_drop`.
+    pub fn clear_on_drop(&self) -> bool {
+        self.clear_on_drop
+    }
+
+    /// When enabled, clears all pixels when `Blinkt` goes out of scope.
+    ///
+    /// By default, this is set to `true`.
+    ///
+    /// ## Note
+    ///
+    /// Drop methods aren't called when a process is abnormally terminated, for
+    /// instance when a user presses <kbd>Ctrl</kbd> + <kbd>C</kbd>, and the `SIGINT` signal
+    /// isn't caught. You can catch those using crates such as [`simple_signal`].
+    ///
+    /// [`simple_signal`]: https://crates.io/crates/simple-signal
+    pub fn set_clear_on_drop(&mut self, clear_on_drop: bool) {
+        self.clear_on_drop = clear_on_drop;
+    }
+}
+
+impl Drop for Blinkt {
+    /// Clears all pixels if [`clear_on_drop`] is set to `true` (default).
+    ///
+    /// [`clear_on_drop`]: #method.clear_on_drop
+    fn drop(&mut self) {
+        if self.clear_on_drop {
+            // Make sure the pixels are turned off immediately, rather than
+            // fading out.
+            self.smoothing = None;
+            self.persistence = 0.0;
+            self.post_process = None;
+            self.max_frame_rate = None;
+            self.error_handler = None;
+
+            self.clear();
+            let _ = self.show();
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Blinkt {
+    type Item = &'a mut Pixel;
+    type IntoIter = slice::IterMut<'a, Pixel>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    // A driver that fails to send frames on request, and keeps track of its
+    // clock speed.
+    #[derive(Clone, Default)]
+    struct FlakyOutput {
+        fail: Arc<AtomicBool>,
+        clock_speed: Arc<AtomicU32>,
+    }
+
+    impl Driver for FlakyOutput {
+        fn write(&mut self, _data: &[u8]) -> Result<()> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(io::Error::new(io::ErrorKind::Other, "flaky").into());
+            }
+
+            Ok(())
+        }
+
+        fn clock_speed(&self) -> u32 {
+            self.clock_speed.load(Ordering::SeqCst)
+        }
+
+        fn set_clock_speed(&mut self, clock_speed_hz: u32) -> Result<()> {
+            self.clock_speed.store(clock_speed_hz, Ordering::SeqCst);
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn paranoid_lowers_and_restores_clock_speed() {
+        let output = FlakyOutput::default();
+        let mut blinkt = Blinkt::with_driver(output.clone(), 8);
+        blinkt.set_clock_speed(8_000_000).unwrap();
+        blinkt.set_paranoid(true);
+
+        output.fail.store(true, Ordering::SeqCst);
+        for _ in 0..PARANOID_MAX_FAILURES * 2 {
+            assert!(blinkt.show().is_err());
+        }
+        assert_eq!(blinkt.clock_speed(), 2_000_000);
+
+        output.fail.store(false, Ordering::SeqCst);
+        for _ in 0..PARANOID_RECOVERY_FRAMES {
+            blinkt.show().unwrap();
+        }
+        assert_eq!(blinkt.clock_speed(), 4_000_000);
+
+        // The clock speed isn't raised above its original setting.
+        for _ in 0..PARANOID_RECOVERY_FRAMES * 2 {
+            blinkt.show().unwrap();
+        }
+        assert_eq!(blinkt.clock_speed(), 8_000_000);
+    }
+
+    // An output with two lanes, which records all data it receives.
+    struct LanesOutput {
+        data: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl SerialOutput for LanesOutput {
+        fn write(&mut self, data: &[u8]) -> Result<()> {
+            self.data.lock().unwrap().extend_from_slice(data);
+
+            Ok(())
+        }
+
+        fn clock_speed(&self) -> u32 {
+            0
+        }
+
+        fn set_clock_speed(&mut self, _clock_speed_hz: u32) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend(&self) -> Backend {
+            Backend::Custom
+        }
+
+        fn lanes(&self) -> usize {
+            2
+        }
+    }
+
+    // Returns the red, green and blue values of every LED frame of an APA102
+    // frame.
+    fn leds(frame: &[u8], num_pixels: usize) -> Vec<[u8; 3]> {
+        frame[4..4 + num_pixels * 4]
+            .chunks_exact(4)
+            .map(|led| [led[3], led[2], led[1]])
+            .collect()
+    }
+
+    #[test]
+    fn transaction_setters_match_blinkt() {
+        let mut blinkt = Blinkt::with_driver(MockOutput::new(), 4);
+        let mut expected = Blinkt::with_driver(MockOutput::new(), 4);
+
+        blinkt
+            .transaction(|transaction| {
+                transaction.fill_hsv(120.0, 1.0, 0.5);
+                transaction.set_pixel_rgbw(0, 1, 2, 3, 4);
+                transaction.set_pixel_u16(1, 0x1234, 0x5678, 0x9abc);
+                transaction.set_pixel_hsv(2, 240.0, 0.5, 1.0);
+            })
+            .unwrap();
+
+        expected.set_all_pixels_hsv(120.0, 1.0, 0.5);
+        expected.set_pixel_rgbw(0, 1, 2, 3, 4);
+        expected.set_pixel_u16(1, 0x1234, 0x5678, 0x9abc);
+        expected.set_pixel_hsv(2, 240.0, 0.5, 1.0);
+
+        for (pixel, expected) in blinkt.iter_mut().zip(expected.iter_mut()) {
+            assert_eq!(pixel.rgbw(), expected.rgbw());
+            assert_eq!(pixel.rgb16(), expected.rgb16());
+        }
+
+        blinkt
+            .transaction(|transaction| {
+                transaction.fill_rgbw(5, 6, 7, 8);
+                transaction.fill_u16(0xffff, 0, 0x8000);
+            })
+            .unwrap();
+
+        let pixel = blinkt.iter_mut().next().unwrap();
+        assert_eq!((pixel.rgb16(), pixel.white()), ((0xffff, 0, 0x8000), 8));
+    }
+
+    #[test]
+    fn max_frame_rate_skips_and_flushes() {
+        let mock = MockOutput::new();
+        let mut blinkt = Blinkt::with_driver(mock.clone(), 1);
+        blinkt.set_max_frame_rate(Some(20));
+
+        let started = Instant::now();
+        blinkt.set_pixel(0, 1, 0, 0);
+        blinkt.show().unwrap();
+        blinkt.set_pixel(0, 2, 0, 0);
+        blinkt.show().unwrap();
+        assert_eq!(mock.len(), 1);
+
+        // The skipped frame is sent once the 50 ms interval has passed.
+        assert!(blinkt.flush().unwrap());
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert_eq!(mock.len(), 2);
+        assert_eq!(leds(&mock.last_frame().unwrap(), 1), [[2, 0, 0]]);
+
+        assert!(!blinkt.flush().unwrap());
+        assert_eq!(mock.len(), 2);
+    }
+
+    #[test]
+    fn soft_start_ramps_up() {
+        let mock = MockOutput::new();
+        let mut blinkt = Blinkt::with_driver(mock.clone(), 8);
+        blinkt.set_soft_start(Some(SoftStart::new(10.0, Duration::from_millis(50))));
+
+        blinkt.set_all_pixels_rgbb(255, 255, 255, 1.0);
+        blinkt.show().unwrap();
+
+        // Four intermediate frames at 10 ms intervals, followed by the frame.
+        let frames = mock.frames();
+        assert_eq!(frames.len(), 5);
+
+        let reds: Vec<u8> = frames.iter().map(|frame| leds(frame, 8)[0][0]).collect();
+        assert!(reds.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(reds[4], 255);
+
+        // Frames below the threshold are sent right away.
+        blinkt.set_all_pixels_rgbb(250, 250, 250, 1.0);
+        blinkt.show().unwrap();
+        assert_eq!(mock.len(), 6);
+    }
+
+    #[test]
+    fn smoothing_and_persistence_leave_buffer_unchanged() {
+        let mock = MockOutput::new();
+        let mut blinkt = Blinkt::with_driver(mock.clone(), 1);
+
+        // Rising values take an hour, falling values are immediate.
+        blinkt.set_smoothing(Some(Smoothing::new(
+            Duration::from_secs(3600),
+            Duration::default(),
+        )));
+        blinkt.show().unwrap();
+        blinkt.set_pixel(0, 255, 0, 0);
+        blinkt.show().unwrap();
+        assert_eq!(leds(&mock.last_frame().unwrap(), 1), [[0, 0, 0]]);
+
+        blinkt.set_smoothing(None);
+        blinkt.set_persistence(0.5);
+        blinkt.show().unwrap();
+        blinkt.set_pixel(0, 0, 200, 0);
+        blinkt.show().unwrap();
+        assert_eq!(leds(&mock.last_frame().unwrap(), 1), [[127, 200, 0]]);
+
+        assert_eq!(blinkt.iter_mut().next().unwrap().rgb(), (0, 200, 0));
+    }
+
+    #[test]
+    fn show_diff_skips_identical_frames() {
+        let mock = MockOutput::new();
+        let mut blinkt = Blinkt::with_driver(mock.clone(), 2);
+
+        assert!(blinkt.show_diff().unwrap());
+        assert!(!blinkt.show_diff().unwrap());
+        assert_eq!(mock.len(), 1);
+
+        blinkt.set_pixel(1, 0, 0, 255);
+        assert!(blinkt.show_diff().unwrap());
+        assert_eq!(mock.len(), 2);
+
+        // show() always sends the frame.
+        blinkt.show().unwrap();
+        assert_eq!(mock.len(), 3);
+
+        // A failed frame is never considered identical to the next frame.
+        blinkt.set_pixel(0, 255, 0, 0);
+        mock.inject_fault(3, MockFault::Error);
+        assert!(blinkt.show_diff().is_err());
+        assert!(blinkt.show_diff().unwrap());
+        assert_eq!(mock.len(), 5);
+    }
+
+    #[test]
+    fn error_handler_actions() {
+        let attempts = |action: ErrorAction, failures: usize| {
+            let mock = MockOutput::new();
+            let mut blinkt = Blinkt::with_driver(mock.clone(), 1);
+            blinkt.set_error_handler(move |_| action);
+            for frame in 0..failures {
+                mock.inject_fault(frame, MockFault::Error);
+            }
+
+            let result = blinkt.show();
+            blinkt.set_clear_on_drop(false);
+
+            (result.is_ok(), mock.len())
+        };
+
+        assert_eq!(attempts(ErrorAction::Abort, 1), (false, 1));
+        assert_eq!(attempts(ErrorAction::Skip, 1), (true, 1));
+        assert_eq!(attempts(ErrorAction::Retry, 2), (true, 3));
+
+        // Retries are limited.
+        let failures = MAX_RETRIES as usize + 1;
+        assert_eq!(attempts(ErrorAction::Retry, failures), (false, failures));
+
+        // Without an error handler, the error is returned.
+        let mock = MockOutput::new();
+        let mut blinkt = Blinkt::with_driver(mock.clone(), 1);
+        mock.inject_fault(0, MockFault::Error);
+        assert!(blinkt.show().is_err());
+        assert_eq!(mock.len(), 1);
+    }
+
+    #[test]
+    fn color_order() {
+        let mock = MockOutput::new();
+        let mut blinkt = Blinkt::with_driver(mock.clone(), 1);
+        blinkt.set_pixel_rgbb(0, 1, 2, 3, 1.0);
+
+        blinkt.show().unwrap();
+        assert_eq!(mock.last_frame().unwrap()[4..8], [0xff, 3, 2, 1]);
+
+        blinkt.set_color_order(ColorOrder::Rgb);
+        blinkt.show().unwrap();
+        assert_eq!(mock.last_frame().unwrap()[4..8], [0xff, 1, 2, 3]);
+        assert_eq!(blinkt.color_order(), ColorOrder::Rgb);
+
+        // Outputs that don't exist are ignored.
+        blinkt.set_output_color_order(1, ColorOrder::Grb);
+        assert_eq!(blinkt.output_color_order(1), None);
+    }
+
+    #[test]
+    fn chain_outputs() {
+        let first = MockOutput::new();
+        let second = MockOutput::new();
+        let mut blinkt = Blinkt::with_driver(first.clone(), 2);
+        blinkt.chain_driver(second.clone(), 20);
+
+        blinkt.set_pixel(1, 1, 0, 0);
+        blinkt.set_pixel(2, 2, 0, 0);
+        blinkt.set_pixel(21, 3, 0, 0);
+        blinkt.show().unwrap();
+
+        // Every output has its own start frame, LED frames and end frame.
+        let frame = first.last_frame().unwrap();
+        assert_eq!(frame.len(), 4 + 2 * 4 + end_frame_len(2));
+        assert_eq!(leds(&frame, 2), [[0, 0, 0], [1, 0, 0]]);
+
+        let frame = second.last_frame().unwrap();
+        assert_eq!(frame.len(), 4 + 20 * 4 + end_frame_len(20));
+        let leds = leds(&frame, 20);
+        assert_eq!((leds[0], leds[19]), ([2, 0, 0], [3, 0, 0]));
+    }
+
+    #[test]
+    fn write_lanes_interleaves_pixels() {
+        let data = Arc::new(Mutex::new(Vec::new()));
+        let output = LanesOutput { data: data.clone() };
+        let mut blinkt = Blinkt::with_output(Box::new(output), 4);
+
+        // The first half of the pixels is sent through the first lane.
+        blinkt.set_pixel_rgbb(0, 1, 0, 0, 1.0);
+        blinkt.set_pixel_rgbb(3, 0, 0, 2, 1.0);
+        blinkt.show().unwrap();
+
+        let data = data.lock().unwrap().clone();
+        let lane =
+            |lane: usize| -> Vec<u8> { data.iter().skip(lane).step_by(2).copied().collect() };
+
+        let lane_len = 4 + 2 * 4 + end_frame_len(2);
+        assert_eq!(data.len(), lane_len * 2);
+        assert_eq!(lane(0)[..12], [0, 0, 0, 0, 0xff, 0, 0, 1, 0xe7, 0, 0, 0]);
+        assert_eq!(lane(1)[..12], [0, 0, 0, 0, 0xe7, 0, 0, 0, 0xff, 2, 0, 0]);
+        assert!(lane(0)[12..]
+            .iter()
+            .chain(&lane(1)[12..])
+            .all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn chain_mixed_protocols() {
+        let apa102 = MockOutput::new();
+        let ws2812 = MockOutput::new();
+        let mut blinkt = Blinkt::with_driver(apa102.clone(), 2);
+        blinkt.chain_ws2812_driver(ws2812.clone(), 1);
+
+        assert_eq!(blinkt.pixels.len(), 3);
+        assert_eq!(blinkt.output_color_order(0), Some(ColorOrder::Bgr));
+        assert_eq!(blinkt.output_color_order(1), Some(ColorOrder::Grb));
+
+        blinkt.set_pixel_rgbb(0, 255, 0, 0, 1.0);
+        blinkt.set_pixel_rgbb(2, 255, 0, 0, 1.0);
+        blinkt.show().unwrap();
+
+        // Start frame, pixel 0 in BGR order, pixel 1, and the end frame.
+        let frame = apa102.last_frame().unwrap();
+        assert_eq!(frame.len(), 4 + 2 * 4 + end_frame_len(2));
+        assert_eq!(frame[..4], [0; 4]);
+        assert_eq!(frame[4..8], [0xff, 0, 0, 255]);
+
+        // Pixel 2 in GRB order, followed by the reset.
+        let frame = ws2812.last_frame().unwrap();
+        assert_eq!(
+            frame[..9],
+            [0x92, 0x49, 0x24, 0xdb, 0x6d, 0xb6, 0x92, 0x49, 0x24]
+        );
+        assert!(frame[9..].iter().all(|&byte| byte == 0));
     }
 }
@@ -0,0 +1,126 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::{Error, Result};
+
+const PATH_RUN_LOCK: &str = "/run/lock";
+
+// An advisory flock(2) lock on a lock file, so other processes using this crate
+// can't unknowingly use the same pins or SPI device. The kernel releases the
+// lock when the file is closed, which includes the process exiting or crashing,
+// so a lock can't be left behind. The lock file contains the PID of the process
+// that created it, for error reporting only.
+//
+// Lock files may be created in a world-writable directory, so symbolic links are
+// never followed, and an existing lock file is only opened for reading, and
+// never modified. flock(2) doesn't require write access.
+//
+// If the lock file can't be opened or locked for any other reason than another
+// process holding the lock, the device is used without a lock.
+#[derive(Debug)]
+pub(crate) struct DeviceLock {
+    _file: Option<File>,
+}
+
+impl DeviceLock {
+    pub(crate) fn acquire(device: &str) -> Result<Self> {
+        let path = lock_path(device);
+        let (mut file, created) = match OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .mode(0o644)
+            .custom_flags(libc::O_NOFOLLOW)
+            .open(&path)
+        {
+            Ok(file) => (file, true),
+            Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => match OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_NOFOLLOW)
+                .open(&path)
+            {
+                Ok(file) => (file, false),
+                Err(_) => return Ok(Self { _file: None }),
+            },
+            Err(_) => return Ok(Self { _file: None }),
+        };
+
+        // Safety: the file descriptor stays valid for as long as `file` is alive.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == -1 {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() == Some(libc::EWOULDBLOCK) {
+                return Err(Error::DeviceInUse(
+                    device.to_owned(),
+                    read_pid(&mut file).unwrap_or(0),
+                ));
+            }
+
+            return Ok(Self { _file: None });
+        }
+
+        // The PID is informational, so failing to write it doesn't matter.
+        if created {
+            let _ = write!(file, "{}", process::id());
+        }
+
+        Ok(Self { _file: Some(file) })
+    }
+
+    // Removes the lock file for the device, regardless of which process holds
+    // the lock. The next process to acquire the lock creates a new lock file.
+    pub(crate) fn force_release(device: &str) -> Result<()> {
+        match fs::remove_file(lock_path(device)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub(crate) fn gpio_device(pin: u8) -> String {
+    format!("gpio{}", pin)
+}
+
+pub(crate) fn spi_device(bus: u8, slave: u8) -> String {
+    format!("spidev{}.{}", bus, slave)
+}
+
+fn lock_path(device: &str) -> PathBuf {
+    let dir = if Path::new(PATH_RUN_LOCK).is_dir() {
+        PathBuf::from(PATH_RUN_LOCK)
+    } else {
+        std::env::temp_dir()
+    };
+
+    dir.join(format!("blinkt-{}.lock", device))
+}
+
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut pid = String::new();
+    file.read_to_string(&mut pid).ok()?;
+
+    pid.trim().parse().ok()
+}
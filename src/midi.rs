@@ -0,0 +1,349 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! MIDI input, to play the pixels from a controller or DAW.
+//!
+//! [`Parser`] turns a stream of raw MIDI bytes into [`Message`]s, and
+//! [`Mapping`] applies those messages to the pixels. MIDI devices on the
+//! Raspberry Pi are available as ALSA raw MIDI devices, such as
+//! `/dev/snd/midiC1D0`, which can be read like any other file. Timing clock
+//! messages can be passed on to a [`BeatClock`].
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! # use std::fs::File;
+//! # use std::io::Read;
+//! #
+//! # use blinkt::Blinkt;
+//! use blinkt::midi::{Mapping, Parser};
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let mut blinkt = Blinkt::new()?;
+//! let mut device = File::open("/dev/snd/midiC1D0")?;
+//! let mut parser = Parser::new();
+//! let mut mapping = Mapping::new();
+//!
+//! // Notes C4 to G4 light up the corresponding pixels.
+//! mapping.map_notes(60, 0..8, 255, 0, 0);
+//! mapping.map_control(1, "speed");
+//!
+//! let mut buffer = [0u8; 64];
+//! loop {
+//!     let len = device.read(&mut buffer)?;
+//!
+//!     for message in buffer[..len].iter().filter_map(|byte| parser.parse(*byte)) {
+//!         mapping.apply(&message, &mut blinkt);
+//!     }
+//!
+//!     blinkt.show()?;
+//! }
+//! # }
+//! ```
+//!
+//! [`BeatClock`]: ../struct.BeatClock.html
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::Blinkt;
+
+/// A MIDI message supported by [`Parser`].
+///
+/// Channels are numbered starting at `0`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// A key was pressed. Note on messages with a velocity of `0` are reported
+    /// as `NoteOff`.
+    NoteOn { channel: u8, note: u8, velocity: u8 },
+    /// A key was released.
+    NoteOff { channel: u8, note: u8, velocity: u8 },
+    /// A controller, such as a knob or slider, changed its value.
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    /// A different program (patch) was selected.
+    ProgramChange { channel: u8, program: u8 },
+    /// A timing clock pulse, sent 24 times per beat.
+    Clock,
+    /// Playback started from the beginning.
+    Start,
+    /// Playback continued from the current position.
+    Continue,
+    /// Playback stopped.
+    Stop,
+}
+
+/// A parser for a stream of raw MIDI bytes.
+///
+/// Running status is supported. System exclusive messages and any other
+/// unsupported messages are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct Parser {
+    status: Option<u8>,
+    data: Vec<u8>,
+}
+
+impl Parser {
+    /// Constructs a new `Parser`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a single byte, and returns a `Message` once a complete message
+    /// is received.
+    pub fn parse(&mut self, byte: u8) -> Option<Message> {
+        match byte {
+            // System real-time messages can occur anywhere, without affecting
+            // the running status.
+            0xf8 => Some(Message::Clock),
+            0xfa => Some(Message::Start),
+            0xfb => Some(Message::Continue),
+            0xfc => Some(Message::Stop),
+            0xf9 | 0xfd..=0xff => None,
+            // System common and system exclusive messages cancel the running
+            // status.
+            0xf0..=0xf7 => {
+                self.status = None;
+                self.data.clear();
+                None
+            }
+            0x80..=0xef => {
+                self.status = Some(byte);
+                self.data.clear();
+                None
+            }
+            _ => {
+                let status = self.status?;
+                self.data.push(byte);
+
+                let len = match status & 0xf0 {
+                    0xc0 | 0xd0 => 1,
+                    _ => 2,
+                };
+
+                if self.data.len() < len {
+                    return None;
+                }
+
+                let message = message(status, &self.data);
+                self.data.clear();
+
+                message
+            }
+        }
+    }
+}
+
+fn message(status: u8, data: &[u8]) -> Option<Message> {
+    let channel = status & 0x0f;
+
+    match status & 0xf0 {
+        0x80 => Some(Message::NoteOff {
+            channel,
+            note: data[0],
+            velocity: data[1],
+        }),
+        0x90 if data[1] == 0 => Some(Message::NoteOff {
+            channel,
+            note: data[0],
+            velocity: 0,
+        }),
+        0x90 => Some(Message::NoteOn {
+            channel,
+            note: data[0],
+            velocity: data[1],
+        }),
+        0xb0 => Some(Message::ControlChange {
+            channel,
+            controller: data[0],
+            value: data[1],
+        }),
+        0xc0 => Some(Message::ProgramChange {
+            channel,
+            program: data[0],
+        }),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Target {
+    Pixel(usize),
+    Tag(String),
+}
+
+#[derive(Debug, Clone)]
+struct NoteMapping {
+    target: Target,
+    color: (u8, u8, u8),
+}
+
+/// Maps MIDI notes to pixels or tagged segments, and controllers to named
+/// parameters.
+///
+/// Notes light up their pixels with the mapped color while they're held, with
+/// the brightness based on the velocity, and clear the pixels once they're
+/// released. Controller values are stored as parameters between `0.0` and
+/// `1.0`, and can be used to control any effect.
+#[derive(Debug, Clone, Default)]
+pub struct Mapping {
+    channel: Option<u8>,
+    notes: HashMap<u8, NoteMapping>,
+    controls: HashMap<u8, String>,
+    values: HashMap<String, f32>,
+    program: Option<u8>,
+}
+
+impl Mapping {
+    /// Constructs a new, empty `Mapping` that accepts messages on all channels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the channel messages are accepted on, or `None` for all channels.
+    pub fn channel(&self) -> Option<u8> {
+        self.channel
+    }
+
+    /// Sets the channel messages are accepted on, or `None` for all channels.
+    ///
+    /// By default, this is set to `None`.
+    pub fn set_channel(&mut self, channel: Option<u8>) {
+        self.channel = channel;
+    }
+
+    /// Maps consecutive notes starting at `first_note` to `pixels`.
+    ///
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    pub fn map_notes(
+        &mut self,
+        first_note: u8,
+        pixels: Range<usize>,
+        red: u8,
+        green: u8,
+        blue: u8,
+    ) {
+        for (note, pixel) in (first_note..=127).zip(pixels) {
+            self.notes.insert(
+                note,
+                NoteMapping {
+                    target: Target::Pixel(pixel),
+                    color: (red, green, blue),
+                },
+            );
+        }
+    }
+
+    /// Maps `note` to all pixels tagged with `tag`.
+    ///
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    pub fn map_note_to_tag(&mut self, note: u8, tag: &str, red: u8, green: u8, blue: u8) {
+        self.notes.insert(
+            note,
+            NoteMapping {
+                target: Target::Tag(tag.to_owned()),
+                color: (red, green, blue),
+            },
+        );
+    }
+
+    /// Maps `controller` to the parameter named `name`.
+    pub fn map_control(&mut self, controller: u8, name: &str) {
+        self.controls.insert(controller, name.to_owned());
+    }
+
+    /// Removes all mapped notes and controllers.
+    pub fn clear(&mut self) {
+        self.notes.clear();
+        self.controls.clear();
+    }
+
+    /// Returns the value of the parameter named `name`, or `None` if its
+    /// controller hasn't sent a value yet.
+    ///
+    /// The value is specified as a floating point value between `0.0` (0%) and `1.0` (100%).
+    pub fn value(&self, name: &str) -> Option<f32> {
+        self.values.get(name).copied()
+    }
+
+    /// Returns the most recently selected program, which can be used to switch
+    /// between effects.
+    pub fn program(&self) -> Option<u8> {
+        self.program
+    }
+
+    /// Applies `message` to the local buffer of `blinkt`, or to the stored
+    /// parameters. Returns `true` if the message was mapped.
+    pub fn apply(&mut self, message: &Message, blinkt: &mut Blinkt) -> bool {
+        let channel = match *message {
+            Message::NoteOn { channel, .. }
+            | Message::NoteOff { channel, .. }
+            | Message::ControlChange { channel, .. }
+            | Message::ProgramChange { channel, .. } => channel,
+            _ => return false,
+        };
+
+        if self.channel.map_or(false, |accepted| accepted != channel) {
+            return false;
+        }
+
+        match *message {
+            Message::NoteOn { note, velocity, .. } => self.apply_note(note, velocity, blinkt),
+            Message::NoteOff { note, .. } => self.apply_note(note, 0, blinkt),
+            Message::ControlChange {
+                controller, value, ..
+            } => match self.controls.get(&controller) {
+                Some(name) => {
+                    self.values.insert(name.clone(), f32::from(value) / 127.0);
+                    true
+                }
+                None => false,
+            },
+            Message::ProgramChange { program, .. } => {
+                self.program = Some(program);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn apply_note(&self, note: u8, velocity: u8, blinkt: &mut Blinkt) -> bool {
+        let mapping = match self.notes.get(&note) {
+            Some(mapping) => mapping,
+            None => return false,
+        };
+
+        let (red, green, blue) = if velocity > 0 {
+            mapping.color
+        } else {
+            (0, 0, 0)
+        };
+        let brightness = f32::from(velocity.max(1)) / 127.0;
+
+        match &mapping.target {
+            Target::Pixel(pixel) => blinkt.set_pixel_rgbb(*pixel, red, green, blue, brightness),
+            Target::Tag(tag) => blinkt.set_tagged_rgbb(tag, red, green, blue, brightness),
+        }
+
+        true
+    }
+}
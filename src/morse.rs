@@ -0,0 +1,111 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+// International Morse code for letters, digits and common punctuation.
+const CODES: [(char, &str); 46] = [
+    ('A', ".-"),
+    ('B', "-..."),
+    ('C', "-.-."),
+    ('D', "-.."),
+    ('E', "."),
+    ('F', "..-."),
+    ('G', "--."),
+    ('H', "...."),
+    ('I', ".."),
+    ('J', ".---"),
+    ('K', "-.-"),
+    ('L', ".-.."),
+    ('M', "--"),
+    ('N', "-."),
+    ('O', "---"),
+    ('P', ".--."),
+    ('Q', "--.-"),
+    ('R', ".-."),
+    ('S', "..."),
+    ('T', "-"),
+    ('U', "..-"),
+    ('V', "...-"),
+    ('W', ".--"),
+    ('X', "-..-"),
+    ('Y', "-.--"),
+    ('Z', "--.."),
+    ('0', "-----"),
+    ('1', ".----"),
+    ('2', "..---"),
+    ('3', "...--"),
+    ('4', "....-"),
+    ('5', "....."),
+    ('6', "-...."),
+    ('7', "--..."),
+    ('8', "---.."),
+    ('9', "----."),
+    ('.', ".-.-.-"),
+    (',', "--..--"),
+    ('?', "..--.."),
+    ('!', "-.-.--"),
+    ('/', "-..-."),
+    ('-', "-....-"),
+    ('=', "-...-"),
+    ('+', ".-.-."),
+    ('@', ".--.-."),
+    (':', "---..."),
+];
+
+// Encodes `text` as a sequence of Morse code units, with `true` for every unit
+// the signal is on. A dot is one unit on and a dash three units. Elements are
+// separated by one unit off, letters by three units and words by seven units.
+// Unsupported characters are skipped.
+pub(crate) fn encode(text: &str) -> Vec<bool> {
+    let mut units = Vec::new();
+    let mut gap = 0;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !units.is_empty() {
+                gap = 7;
+            }
+
+            continue;
+        }
+
+        let code = match CODES
+            .iter()
+            .find(|(letter, _)| *letter == c.to_ascii_uppercase())
+        {
+            Some((_, code)) => code,
+            None => continue,
+        };
+
+        units.resize(units.len() + gap, false);
+
+        for (index, element) in code.chars().enumerate() {
+            if index > 0 {
+                units.push(false);
+            }
+
+            let len = if element == '-' { 3 } else { 1 };
+            units.resize(units.len() + len, true);
+        }
+
+        gap = 3;
+    }
+
+    units
+}
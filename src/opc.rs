@@ -0,0 +1,174 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! An Open Pixel Control (OPC) server, to control the pixels from Processing
+//! sketches, Fadecandy tooling and other OPC clients.
+//!
+//! [`Server`] accepts OPC clients over TCP, and applies every Set Pixel
+//! Colors message (command `0`) to the local buffer, after which `show()` is
+//! called. Every message contains the red, green and blue values of
+//! consecutive pixels, starting at the first pixel. Any other commands,
+//! including System Exclusive messages, are ignored.
+//!
+//! OPC doesn't support authentication, so any host that can connect to the
+//! server can change the pixels. Only bind to an address on a trusted network,
+//! or restrict access with a firewall.
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! #
+//! # use blinkt::{Blinkt, BlinktSpi};
+//! use blinkt::opc::{Server, PORT};
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let mut blinkt = Blinkt::with_spi(BlinktSpi::default(), 144);
+//! let server = Server::bind(("0.0.0.0", PORT))?;
+//!
+//! server.run(&mut blinkt)?;
+//! #     Ok(())
+//! # }
+//! ```
+//!
+//! [`Server`]: struct.Server.html
+
+use std::io::{self, Read};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::{net, Blinkt, Result};
+
+/// The TCP port commonly used by OPC servers.
+pub const PORT: u16 = 7890;
+
+const BROADCAST_CHANNEL: u8 = 0;
+const COMMAND_SET_PIXEL_COLORS: u8 = 0;
+// Clients that don't send anything for this long are disconnected.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+// A message sent by an OPC client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Message {
+    // Channel 0 addresses all channels.
+    channel: u8,
+    command: u8,
+    data: Vec<u8>,
+}
+
+/// An OPC server that accepts clients over TCP.
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+    channel: u8,
+}
+
+impl Server {
+    /// Constructs a new `Server` listening on `address`.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+            channel: BROADCAST_CHANNEL,
+        })
+    }
+
+    /// Returns the local address the server is listening on.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Returns the channel the server responds to.
+    pub fn channel(&self) -> u8 {
+        self.channel
+    }
+
+    /// Sets the channel the server responds to. Messages sent to channel `0`
+    /// are always applied. If `channel` is set to `0`, messages sent to any
+    /// channel are applied.
+    ///
+    /// By default, this is set to `0`.
+    pub fn set_channel(&mut self, channel: u8) {
+        self.channel = channel;
+    }
+
+    /// Accepts clients and applies their messages to `blinkt` until an error
+    /// occurs while sending a frame to the pixels.
+    pub fn run(&self, blinkt: &mut Blinkt) -> Result<()> {
+        loop {
+            self.accept(blinkt)?;
+        }
+    }
+
+    /// Waits for a single client to connect, and applies its messages to
+    /// `blinkt` until it disconnects. Returns the number of frames that were
+    /// shown.
+    ///
+    /// Network errors are treated as a disconnect, as are clients that take
+    /// more than 30 seconds to send a message. Returns an error if no client can be
+    /// accepted, or a frame can't be sent to the pixels.
+    pub fn accept(&self, blinkt: &mut Blinkt) -> Result<usize> {
+        let stream = net::accept(&self.listener, READ_TIMEOUT)?;
+
+        let mut frames = 0;
+
+        while let Ok(message) = read_message(&stream) {
+            if self.apply(&message, blinkt) {
+                blinkt.show()?;
+                frames += 1;
+            }
+        }
+
+        Ok(frames)
+    }
+
+    // Applies a Set Pixel Colors message to the local buffer. Returns `false`
+    // if the message isn't supported, or is sent to a different channel.
+    fn apply(&self, message: &Message, blinkt: &mut Blinkt) -> bool {
+        if message.command != COMMAND_SET_PIXEL_COLORS
+            || (self.channel != BROADCAST_CHANNEL
+                && message.channel != BROADCAST_CHANNEL
+                && message.channel != self.channel)
+        {
+            return false;
+        }
+
+        for (index, rgb) in message.data.chunks_exact(3).enumerate() {
+            blinkt.set_pixel(index, rgb[0], rgb[1], rgb[2]);
+        }
+
+        true
+    }
+}
+
+// Reads the next message, which has to arrive within READ_TIMEOUT.
+fn read_message(stream: &TcpStream) -> io::Result<Message> {
+    let mut stream = net::DeadlineReader::new(stream, READ_TIMEOUT);
+
+    // Header: channel, command and a 16-bit big-endian length.
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header)?;
+
+    let mut data = vec![0u8; usize::from(u16::from_be_bytes([header[2], header[3]]))];
+    stream.read_exact(&mut data)?;
+
+    Ok(Message {
+        channel: header[0],
+        command: header[1],
+        data,
+    })
+}
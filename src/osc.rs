@@ -0,0 +1,386 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! An OSC (Open Sound Control) server, to control the pixels from TouchOSC,
+//! Max/MSP, VJ software and other live performance tools.
+//!
+//! [`Server`] receives OSC packets over UDP, and applies the following
+//! messages to the local buffer. Pixels are numbered starting at `0`. Colors
+//! can either be sent as integers between `0` and `255`, or as floats between
+//! `0.0` and `1.0`. Brightness is always specified as a float between `0.0`
+//! and `1.0`.
+//!
+//! | Address                        | Arguments         |
+//! |--------------------------------|-------------------|
+//! | `/blinkt/pixel/<n>/rgb`        | red, green, blue  |
+//! | `/blinkt/pixel/<n>/brightness` | brightness        |
+//! | `/blinkt/tag/<tag>/rgb`        | red, green, blue  |
+//! | `/blinkt/tag/<tag>/brightness` | brightness        |
+//! | `/blinkt/rgb`                  | red, green, blue  |
+//! | `/blinkt/brightness`           | brightness        |
+//! | `/blinkt/clear`                |                   |
+//!
+//! Effect parameters can be changed through `/blinkt/effect/<parameter>` with
+//! [`Message::apply_effect()`]. The arguments depend on the type of the
+//! parameter: a float for floating point parameters, an integer or float where
+//! any non-zero value means on for on/off parameters, and red, green and blue
+//! for colors. `/blinkt/preset` with the name of a preset as its argument
+//! applies a preset from a [`PresetStore`] through [`Message::apply_preset()`].
+//!
+//! The server doesn't authenticate its clients, or limit how often they send
+//! messages. Any host that can reach the socket can change the pixels, so only
+//! bind to an address on a trusted network, or restrict access with a firewall.
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! #
+//! # use blinkt::Blinkt;
+//! use blinkt::osc::Server;
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let mut blinkt = Blinkt::new()?;
+//! let mut server = Server::bind("0.0.0.0:9000")?;
+//!
+//! loop {
+//!     if server.receive(&mut blinkt)? > 0 {
+//!         blinkt.show()?;
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! [`Message::apply_effect()`]: struct.Message.html#method.apply_effect
+//! [`Message::apply_preset()`]: struct.Message.html#method.apply_preset
+//! [`PresetStore`]: ../struct.PresetStore.html
+
+use std::fmt;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use crate::effect::{Effect, Value};
+use crate::{Blinkt, PresetStore, Result};
+
+// Maximum size of a single UDP packet
+const MAX_PACKET_SIZE: usize = 65_536;
+// Maximum nesting depth of bundles, which limits the recursion depth of the
+// decoder. Elements of bundles nested any deeper are skipped.
+const MAX_BUNDLE_DEPTH: usize = 8;
+
+/// An argument of an OSC message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Argument {
+    /// A 32-bit integer (`i`).
+    Int(i32),
+    /// A 32-bit float (`f`).
+    Float(f32),
+    /// A string (`s`).
+    String(String),
+}
+
+impl Argument {
+    // Converts a color value to an 8-bit value.
+    fn color(&self) -> Option<u8> {
+        match *self {
+            Argument::Int(value) => Some(value.clamp(0, 255) as u8),
+            Argument::Float(value) => Some((value.clamp(0.0, 1.0) * 255.0).round() as u8),
+            Argument::String(_) => None,
+        }
+    }
+
+    fn float(&self) -> Option<f32> {
+        match *self {
+            Argument::Int(value) => Some(value as f32),
+            Argument::Float(value) => Some(value),
+            Argument::String(_) => None,
+        }
+    }
+}
+
+/// An OSC message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    address: String,
+    arguments: Vec<Argument>,
+}
+
+impl Message {
+    /// Constructs a new `Message`.
+    pub fn new(address: &str, arguments: Vec<Argument>) -> Self {
+        Self {
+            address: address.to_owned(),
+            arguments,
+        }
+    }
+
+    /// Returns the address pattern.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Returns the arguments.
+    pub fn arguments(&self) -> &[Argument] {
+        &self.arguments
+    }
+
+    /// Applies the message to the local buffer of `blinkt`. Returns `true` if
+    /// the message is supported.
+    pub fn apply(&self, blinkt: &mut Blinkt) -> bool {
+        let parts: Vec<&str> = self.address.trim_start_matches('/').split('/').collect();
+
+        match parts.as_slice() {
+            ["blinkt", "pixel", pixel, property] => match pixel.parse::<usize>() {
+                Ok(pixel) => match *property {
+                    "rgb" => self.rgb().map_or(false, |(red, green, blue)| {
+                        blinkt.set_pixel(pixel, red, green, blue);
+                        true
+                    }),
+                    "brightness" => self.brightness().map_or(false, |brightness| {
+                        blinkt.set_pixel_brightness(pixel, brightness);
+                        true
+                    }),
+                    _ => false,
+                },
+                Err(_) => false,
+            },
+            ["blinkt", "tag", tag, "rgb"] => self.rgb().map_or(false, |(red, green, blue)| {
+                blinkt.set_tagged(tag, red, green, blue);
+                true
+            }),
+            ["blinkt", "tag", tag, "brightness"] => self.brightness().map_or(false, |brightness| {
+                blinkt.set_tagged_brightness(tag, brightness);
+                true
+            }),
+            ["blinkt", "rgb"] => self.rgb().map_or(false, |(red, green, blue)| {
+                blinkt.set_all_pixels(red, green, blue);
+                true
+            }),
+            ["blinkt", "brightness"] => self.brightness().map_or(false, |brightness| {
+                blinkt.set_all_pixels_brightness(brightness);
+                true
+            }),
+            ["blinkt", "clear"] => {
+                blinkt.clear();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Applies a `/blinkt/effect/<parameter>` message to the parameters of
+    /// `effect`. Returns `true` if the parameter exists, and the arguments match
+    /// its type.
+    pub fn apply_effect(&self, effect: &mut dyn Effect) -> bool {
+        let name = match self.address.strip_prefix("/blinkt/effect/") {
+            Some(name) => name,
+            None => return false,
+        };
+
+        let parameter = effect
+            .parameters()
+            .into_iter()
+            .find(|parameter| parameter.name() == name);
+
+        let value = match parameter.map(|parameter| parameter.value()) {
+            Some(Value::Float(_)) => self.brightness().map(Value::Float),
+            Some(Value::Bool(_)) => self.brightness().map(|value| Value::Bool(value != 0.0)),
+            Some(Value::Color(..)) => self
+                .rgb()
+                .map(|(red, green, blue)| Value::Color(red, green, blue)),
+            None => None,
+        };
+
+        value.map_or(false, |value| effect.set_parameter(name, value))
+    }
+
+    /// Applies a `/blinkt/preset` message to `effect`, using the preset with
+    /// the name specified as the first argument. Returns `true` if the preset
+    /// was applied.
+    pub fn apply_preset(&self, presets: &PresetStore, effect: &mut dyn Effect) -> bool {
+        match (self.address.as_str(), self.arguments.first()) {
+            ("/blinkt/preset", Some(Argument::String(name))) => presets.apply(name, effect),
+            _ => false,
+        }
+    }
+
+    fn rgb(&self) -> Option<(u8, u8, u8)> {
+        match self.arguments.as_slice() {
+            [red, green, blue, ..] => Some((red.color()?, green.color()?, blue.color()?)),
+            _ => None,
+        }
+    }
+
+    fn brightness(&self) -> Option<f32> {
+        self.arguments.first()?.float()
+    }
+}
+
+/// Decodes an OSC packet containing a single message or a bundle.
+///
+/// Bundles are flattened, and their time tags are ignored. Any unsupported
+/// or malformed messages are skipped, as are bundles nested more than 8
+/// levels deep.
+///
+/// ```rust
+/// use blinkt::osc::{decode, Argument};
+///
+/// // Address, type tags and three integer arguments, padded to 4 bytes.
+/// let mut packet = b"/blinkt/pixel/1/rgb\0,iii\0\0\0\0".to_vec();
+/// for value in &[255, 128, 0] {
+///     packet.extend_from_slice(&i32::to_be_bytes(*value));
+/// }
+///
+/// let messages = decode(&packet);
+/// assert_eq!(messages.len(), 1);
+/// assert_eq!(messages[0].address(), "/blinkt/pixel/1/rgb");
+/// assert_eq!(
+///     messages[0].arguments(),
+///     [Argument::Int(255), Argument::Int(128), Argument::Int(0)]
+/// );
+///
+/// // Messages with missing arguments are skipped.
+/// assert!(decode(&packet[..packet.len() - 4]).is_empty());
+/// ```
+pub fn decode(packet: &[u8]) -> Vec<Message> {
+    let mut messages = Vec::new();
+    decode_into(packet, 0, &mut messages);
+
+    messages
+}
+
+fn decode_into(packet: &[u8], depth: usize, messages: &mut Vec<Message>) {
+    if packet.starts_with(b"#bundle\0") {
+        if depth == MAX_BUNDLE_DEPTH {
+            return;
+        }
+
+        // Skip the bundle identifier and time tag.
+        let mut offset = 16;
+
+        while let Some(len) = read_i32(packet, offset) {
+            let start = offset + 4;
+            let end = start + len.max(0) as usize;
+            match packet.get(start..end) {
+                Some(element) => decode_into(element, depth + 1, messages),
+                None => return,
+            }
+
+            offset = end;
+        }
+    } else if let Some(message) = decode_message(packet) {
+        messages.push(message);
+    }
+}
+
+fn decode_message(packet: &[u8]) -> Option<Message> {
+    let (address, mut offset) = read_string(packet, 0)?;
+    if !address.starts_with('/') {
+        return None;
+    }
+
+    let mut arguments = Vec::new();
+
+    // Messages without a type tag string are allowed by older implementations.
+    if let Some((type_tags, next)) = read_string(packet, offset) {
+        offset = next;
+
+        for type_tag in type_tags.strip_prefix(',')?.chars() {
+            let argument = match type_tag {
+                'i' => Argument::Int(read_i32(packet, offset)?),
+                'f' => Argument::Float(f32::from_bits(read_i32(packet, offset)? as u32)),
+                's' => {
+                    let (value, next) = read_string(packet, offset)?;
+                    offset = next;
+                    arguments.push(Argument::String(value));
+                    continue;
+                }
+                _ => return None,
+            };
+
+            offset += 4;
+            arguments.push(argument);
+        }
+    }
+
+    Some(Message { address, arguments })
+}
+
+// Reads a null-terminated string padded to a multiple of 4 bytes, and returns
+// the string and the offset of the next field.
+fn read_string(packet: &[u8], offset: usize) -> Option<(String, usize)> {
+    let data = packet.get(offset..)?;
+    let len = data.iter().position(|byte| *byte == 0)?;
+    let value = String::from_utf8(data[..len].to_vec()).ok()?;
+
+    Some((value, offset + (len + 4) / 4 * 4))
+}
+
+fn read_i32(packet: &[u8], offset: usize) -> Option<i32> {
+    let bytes = packet.get(offset..offset + 4)?;
+
+    Some(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// An OSC server that receives messages over UDP.
+pub struct Server {
+    socket: UdpSocket,
+    // Reused for every packet.
+    buffer: Vec<u8>,
+}
+
+impl fmt::Debug for Server {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Server")
+            .field("socket", &self.socket)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Server {
+    /// Constructs a new `Server` listening on `address`.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(address)?,
+            buffer: vec![0u8; MAX_PACKET_SIZE],
+        })
+    }
+
+    /// Returns the local address the server is listening on.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// When enabled, `receive()` returns an error of kind `WouldBlock` instead of
+    /// waiting for the next packet.
+    ///
+    /// By default, this is set to `false`.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> Result<()> {
+        Ok(self.socket.set_nonblocking(nonblocking)?)
+    }
+
+    /// Waits for the next packet, and applies all messages it contains to the
+    /// local buffer of `blinkt`. Returns the number of supported messages.
+    pub fn receive(&mut self, blinkt: &mut Blinkt) -> Result<usize> {
+        let (len, _) = self.socket.recv_from(&mut self.buffer)?;
+
+        Ok(decode(&self.buffer[..len])
+            .iter()
+            .filter(|message| message.apply(blinkt))
+            .count())
+    }
+}
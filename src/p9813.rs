@@ -0,0 +1,217 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::driver::DriverOutput;
+use crate::{
+    Backend, Blinkt, BlinktGpio, BlinktSpi, Driver, Protocol, Result, SerialOutput, TimingStrategy,
+};
+
+// Sends P9813 data through bitbanging mode or hardware SPI.
+struct BlinktP9813 {
+    output: Box<dyn SerialOutput + Send>,
+}
+
+impl SerialOutput for BlinktP9813 {
+    fn acquire(&mut self) -> Result<()> {
+        self.output.acquire()
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.output.write(data)
+    }
+
+    fn release(&mut self) -> Result<()> {
+        self.output.release()
+    }
+
+    fn clock_speed(&self) -> u32 {
+        self.output.clock_speed()
+    }
+
+    fn set_clock_speed(&mut self, clock_speed_hz: u32) -> Result<()> {
+        self.output.set_clock_speed(clock_speed_hz)
+    }
+
+    fn backend(&self) -> Backend {
+        self.output.backend()
+    }
+
+    fn set_timing_strategy(&mut self, timing_strategy: TimingStrategy) {
+        self.output.set_timing_strategy(timing_strategy);
+    }
+
+    fn protocol(&self) -> Protocol {
+        Protocol::P9813
+    }
+}
+
+impl Blinkt {
+    /// Constructs a new `Blinkt` for P9813 pixels, such as the Grove Chainable
+    /// RGB LED, using bitbanging mode with custom settings for the data pin,
+    /// clock pin, and number of pixels. Pins should be specified by their BCM
+    /// GPIO pin numbers.
+    ///
+    /// The P9813 uses the same data and clock lines as the APA102, but doesn't
+    /// have a separate brightness setting, so the red, green and blue values are
+    /// scaled by the brightness of each pixel before they're sent.
+    pub fn with_p9813(pin_data: u8, pin_clock: u8, num_pixels: usize) -> Result<Self> {
+        let output = Box::new(BlinktGpio::with_settings(pin_data, pin_clock)?);
+
+        Ok(Self::with_output(
+            Box::new(BlinktP9813 { output }),
+            num_pixels,
+        ))
+    }
+
+    /// Constructs a new `Blinkt` for P9813 pixels, such as the Grove Chainable
+    /// RGB LED, using hardware SPI with the specified number of pixels.
+    ///
+    /// See [`with_p9813()`] for details.
+    ///
+    /// [`with_p9813()`]: #method.with_p9813
+    pub fn with_p9813_spi(spi: BlinktSpi, num_pixels: usize) -> Self {
+        Self::with_output(
+            Box::new(BlinktP9813 {
+                output: Box::new(spi),
+            }),
+            num_pixels,
+        )
+    }
+
+    /// Constructs a new `Blinkt` for P9813 pixels, which sends the encoded
+    /// frames through a custom [`Driver`], with the specified number of pixels.
+    ///
+    /// See [`with_p9813()`] for details.
+    ///
+    /// ```rust
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::{Blinkt, MockOutput};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mock = MockOutput::new();
+    /// let mut blinkt = Blinkt::with_p9813_driver(mock.clone(), 1);
+    ///
+    /// blinkt.set_pixel_rgbb(0, 255, 0, 0, 1.0);
+    /// blinkt.show()?;
+    ///
+    /// let frame = mock.last_frame().unwrap();
+    /// // Start frame, the flag byte with the inverted two most significant bits
+    /// // of blue (00), green (00) and red (11), blue, green and red, and the end
+    /// // frame.
+    /// assert_eq!(frame, [0, 0, 0, 0, 0b1111_1100, 0, 0, 255, 0, 0, 0, 0]);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Driver`]: trait.Driver.html
+    /// [`with_p9813()`]: #method.with_p9813
+    pub fn with_p9813_driver<D: Driver + Send + 'static>(driver: D, num_pixels: usize) -> Self {
+        Self::with_output(
+            Box::new(DriverOutput::new(driver, Protocol::P9813)),
+            num_pixels,
+        )
+    }
+
+    /// Adds P9813 pixels to the end of the local buffer, which are sent through
+    /// bitbanging mode, with custom settings for the data pin and clock pin.
+    ///
+    /// See [`with_p9813()`] for details on the P9813 output, and `chain_gpio()`
+    /// for details on chaining.
+    ///
+    /// [`with_p9813()`]: #method.with_p9813
+    pub fn chain_p9813(&mut self, pin_data: u8, pin_clock: u8, num_pixels: usize) -> Result<()> {
+        let output = Box::new(BlinktGpio::with_settings(pin_data, pin_clock)?);
+        self.chain_output(Box::new(BlinktP9813 { output }), num_pixels);
+
+        Ok(())
+    }
+
+    /// Adds P9813 pixels to the end of the local buffer, which are sent through
+    /// hardware SPI.
+    ///
+    /// See [`with_p9813()`] for details on the P9813 output, and `chain_gpio()`
+    /// for details on chaining.
+    ///
+    /// [`with_p9813()`]: #method.with_p9813
+    pub fn chain_p9813_spi(&mut self, spi: BlinktSpi, num_pixels: usize) {
+        self.chain_output(
+            Box::new(BlinktP9813 {
+                output: Box::new(spi),
+            }),
+            num_pixels,
+        );
+    }
+
+    /// Adds P9813 pixels to the end of the local buffer, which are sent through
+    /// a custom [`Driver`].
+    ///
+    /// See [`with_p9813()`] for details on the P9813 output, and `chain_gpio()`
+    /// for details on chaining.
+    ///
+    /// [`Driver`]: trait.Driver.html
+    /// [`with_p9813()`]: #method.with_p9813
+    pub fn chain_p9813_driver<D: Driver + Send + 'static>(&mut self, driver: D, num_pixels: usize) {
+        self.chain_output(
+            Box::new(DriverOutput::new(driver, Protocol::P9813)),
+            num_pixels,
+        );
+    }
+
+    // Sends the frame buffer to a P9813 output.
+    pub(crate) fn write_p9813(&mut self, output: usize) -> Result<()> {
+        let pixels = self.outputs[output].pixels.clone();
+        let color_order = self.outputs[output].color_order;
+
+        // Start frame (32*0).
+        let mut data = vec![0u8; 4];
+        data.reserve(pixels.len() * 4 + 4);
+
+        // LED frames (2*1, 2*!blue, 2*!green, 2*!red, 8*blue, 8*green, 8*red).
+        // The flag byte contains the inverted two most significant bits of
+        // each color as a checksum.
+        for index in pixels {
+            let wire_pixel = self.preview_at(index, &self.frame[index]);
+            let brightness = u16::from(wire_pixel.brightness());
+            let bytes = wire_pixel.bytes_ordered(color_order);
+            let values = [1, 2, 3].map(|byte| (u16::from(bytes[byte]) * brightness / 31) as u8);
+
+            let flag = 0b1100_0000
+                | (!values[0] & 0b1100_0000) >> 2
+                | (!values[1] & 0b1100_0000) >> 4
+                | (!values[2] & 0b1100_0000) >> 6;
+
+            data.push(flag);
+            data.extend_from_slice(&values);
+
+            self.sent_frame.push(wire_pixel);
+        }
+
+        // End frame (32*0).
+        data.extend_from_slice(&[0u8; 4]);
+
+        let serial_output = &mut self.outputs[output].serial_output;
+        serial_output.acquire()?;
+        let result = serial_output.write(&data);
+        let released = serial_output.release();
+
+        result.and(released)
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+// Status colors with an equal relative luminance of around 18.5, based on the
+// Rec. 709 coefficients. Blue is the least luminous hue at full intensity, so
+// it determines the luminance of all other colors.
+const STATUS_COLORS: [(u8, u8, u8); 5] = [
+    (0, 26, 0),  // Ok (green)
+    (37, 15, 0), // Warn (amber)
+    (87, 0, 0),  // Error (red)
+    (0, 0, 255), // Info (blue)
+    (0, 0, 0),   // Off
+];
+
+// The Okabe-Ito palette, which remains distinguishable with deuteranopia and
+// protanopia, converted from sRGB to linear values for LEDs.
+const OKABE_ITO_COLORS: [(u8, u8, u8); 7] = [
+    (202, 88, 0),   // Orange
+    (24, 116, 208), // Sky blue
+    (0, 87, 44),    // Bluish green
+    (222, 198, 14), // Yellow
+    (0, 43, 114),   // Blue
+    (170, 29, 0),   // Vermillion
+    (154, 49, 99),  // Reddish purple
+];
+
+// Status colors based on the Okabe-Ito palette, avoiding the use of red and
+// green to tell statuses apart.
+const STATUS_COLORBLIND_COLORS: [(u8, u8, u8); 5] = [
+    (0, 43, 114),   // Ok (blue)
+    (222, 198, 14), // Warn (yellow)
+    (170, 29, 0),   // Error (vermillion)
+    (154, 49, 99),  // Info (reddish purple)
+    (0, 0, 0),      // Off
+];
+
+/// A status that can be indicated by a pixel, used with [`Palette::status()`].
+///
+/// [`Palette::status()`]: struct.Palette.html#method.status
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Status {
+    /// Everything is working as expected.
+    Ok,
+    /// Something requires attention.
+    Warn,
+    /// Something failed.
+    Error,
+    /// Informational.
+    Info,
+    /// No status.
+    Off,
+}
+
+/// An ordered set of colors, used by effects to pick their colors from.
+///
+/// Colors are stored as red, green and blue values, specified as 8-bit values
+/// between `0` (0%) and `255` (100%).
+///
+/// ```rust
+/// use blinkt::Palette;
+///
+/// let palette = Palette::new(&[(255, 0, 0), (0, 0, 255)]);
+///
+/// assert_eq!(palette.sample(0.5), (128, 0, 128));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Palette {
+    colors: Vec<(u8, u8, u8)>,
+}
+
+impl Palette {
+    /// Constructs a new `Palette` containing `colors`.
+    pub fn new(colors: &[(u8, u8, u8)]) -> Self {
+        Self {
+            colors: colors.to_vec(),
+        }
+    }
+
+    /// Returns a `Palette` for status indicators, with a color for every
+    /// [`Status`], in the order they're defined.
+    ///
+    /// On LEDs, green appears much brighter than red or blue at the same value.
+    /// The colors of this palette are tuned to appear equally bright, which
+    /// makes them suitable for status lights at low brightness. They assume the
+    /// default `Correction`, which doesn't apply gamma correction.
+    ///
+    /// ```rust
+    /// use blinkt::{Palette, Status};
+    ///
+    /// let palette = Palette::status();
+    ///
+    /// assert_eq!(palette.status_color(Status::Off), (0, 0, 0));
+    /// ```
+    ///
+    /// [`Status`]: enum.Status.html
+    pub fn status() -> Self {
+        Self::new(&STATUS_COLORS)
+    }
+
+    /// Returns a `Palette` for status indicators that remain distinguishable
+    /// for users with deuteranopia or protanopia (red-green color blindness),
+    /// with a color for every [`Status`], in the order they're defined.
+    ///
+    /// `Ok` is indicated by blue rather than green, and statuses also differ in
+    /// brightness, so they can be told apart without relying on hue alone.
+    ///
+    /// [`Status`]: enum.Status.html
+    pub fn status_colorblind() -> Self {
+        Self::new(&STATUS_COLORBLIND_COLORS)
+    }
+
+    /// Returns the Okabe-Ito palette, which contains 7 colors that remain
+    /// distinguishable for users with deuteranopia or protanopia. This is
+    /// well suited for graphs and other displays that differentiate between
+    /// several values by color.
+    pub fn okabe_ito() -> Self {
+        Self::new(&OKABE_ITO_COLORS)
+    }
+
+    /// Returns the color for `status`, for palettes with a color for every
+    /// [`Status`], such as the one returned by `status()`.
+    ///
+    /// [`Status`]: enum.Status.html
+    pub fn status_color(&self, status: Status) -> (u8, u8, u8) {
+        self.color(status as usize)
+    }
+
+    /// Constructs a new `Palette` containing the dominant colors of an image.
+    ///
+    /// `image` contains the red, green and blue values of every pixel in the
+    /// image, in that order, such as the raw pixel data of a decoded RGB image.
+    /// Up to `count` colors are extracted using the median cut algorithm, and
+    /// sorted by the number of pixels they represent, starting with the most
+    /// dominant color.
+    pub fn from_image(image: &[u8], count: usize) -> Self {
+        let pixels: Vec<[u8; 3]> = image
+            .chunks_exact(3)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            .collect();
+
+        if pixels.is_empty() || count == 0 {
+            return Self::default();
+        }
+
+        let mut boxes = vec![pixels];
+
+        while boxes.len() < count {
+            // Split the box with the widest range of values along its widest
+            // channel, at the median.
+            let widest = boxes
+                .iter()
+                .enumerate()
+                .filter(|(_, pixels)| pixels.len() > 1)
+                .map(|(index, pixels)| (index, widest_channel(pixels)))
+                .max_by_key(|(_, (_, range))| *range);
+
+            let (index, channel) = match widest {
+                Some((index, (channel, range))) if range > 0 => (index, channel),
+                _ => break,
+            };
+
+            let mut pixels = boxes.swap_remove(index);
+            pixels.sort_unstable_by_key(|pixel| pixel[channel]);
+            let upper = pixels.split_off(pixels.len() / 2);
+
+            boxes.push(pixels);
+            boxes.push(upper);
+        }
+
+        boxes.sort_by_key(|pixels| std::cmp::Reverse(pixels.len()));
+
+        Self {
+            colors: boxes.iter().map(|pixels| average(pixels)).collect(),
+        }
+    }
+
+    /// Returns the colors.
+    pub fn colors(&self) -> &[(u8, u8, u8)] {
+        &self.colors
+    }
+
+    /// Returns the number of colors.
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Returns `true` if the palette doesn't contain any colors.
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Returns the color at `index`, wrapping around at the end of the palette.
+    ///
+    /// Returns black if the palette doesn't contain any colors.
+    pub fn color(&self, index: usize) -> (u8, u8, u8) {
+        if self.colors.is_empty() {
+            (0, 0, 0)
+        } else {
+            self.colors[index % self.colors.len()]
+        }
+    }
+
+    /// Returns a color interpolated between the colors of the palette.
+    ///
+    /// `position` is specified as a floating point value between `0.0` (first
+    /// color) and `1.0` (last color). Returns black if the palette doesn't
+    /// contain any colors.
+    pub fn sample(&self, position: f32) -> (u8, u8, u8) {
+        if self.colors.len() < 2 {
+            return self.color(0);
+        }
+
+        let position = position.clamp(0.0, 1.0) * (self.colors.len() - 1) as f32;
+        let index = (position as usize).min(self.colors.len() - 2);
+        let amount = position - index as f32;
+
+        let (red_from, green_from, blue_from) = self.colors[index];
+        let (red_to, green_to, blue_to) = self.colors[index + 1];
+        let channel = |from: u8, to: u8| {
+            (f32::from(from) + (f32::from(to) - f32::from(from)) * amount).round() as u8
+        };
+
+        (
+            channel(red_from, red_to),
+            channel(green_from, green_to),
+            channel(blue_from, blue_to),
+        )
+    }
+}
+
+// Returns the channel with the widest range of values, and its range.
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let min = pixels.iter().map(|pixel| pixel[channel]).min().unwrap_or(0);
+            let max = pixels.iter().map(|pixel| pixel[channel]).max().unwrap_or(0);
+
+            (channel, max - min)
+        })
+        .max_by_key(|(_, range)| *range)
+        .unwrap_or((0, 0))
+}
+
+fn average(pixels: &[[u8; 3]]) -> (u8, u8, u8) {
+    let mut sum = [0u64; 3];
+
+    for pixel in pixels {
+        for (sum, value) in sum.iter_mut().zip(pixel) {
+            *sum += u64::from(*value);
+        }
+    }
+
+    let len = pixels.len().max(1) as u64;
+
+    (
+        (sum[0] / len) as u8,
+        (sum[1] / len) as u8,
+        (sum[2] / len) as u8,
+    )
+}
@@ -0,0 +1,101 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fs;
+
+use rppal::gpio::{Mode, Pin};
+
+use crate::profile::hat_product;
+use crate::{Error, Result};
+
+const PATH_SYSFS_GPIO: &str = "/sys/class/gpio";
+
+// Returns an error if the pin is currently used by another process or one of
+// the peripherals, which would otherwise result in seemingly random flicker.
+pub(crate) fn check_available(pin: &Pin) -> Result<()> {
+    let number = pin.pin();
+
+    let function = match pin.mode() {
+        Mode::Input | Mode::Output if is_exported(number) => Some("sysfs GPIO export".to_owned()),
+        Mode::Input | Mode::Output => None,
+        mode => Some(alt_function(number, mode)),
+    };
+
+    match function {
+        Some(mut function) => {
+            if let Some(product) = hat_product() {
+                function.push_str(&format!(", HAT: {}", product));
+            }
+
+            Err(Error::PinConflict(number, function))
+        }
+        None => Ok(()),
+    }
+}
+
+// Checks whether the pin is exported through the sysfs GPIO interface. Depending
+// on the kernel version, sysfs GPIO numbers might be offset by the base number of
+// the GPIO chip.
+fn is_exported(pin: u8) -> bool {
+    let mut bases = vec![0];
+
+    if let Ok(entries) = fs::read_dir(PATH_SYSFS_GPIO) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with("gpiochip") {
+                let base = fs::read_to_string(entry.path().join("base"))
+                    .ok()
+                    .and_then(|base| base.trim().parse::<u32>().ok());
+
+                if let Some(base) = base {
+                    bases.push(base);
+                }
+            }
+        }
+    }
+
+    bases.iter().any(|base| {
+        fs::metadata(format!("{}/gpio{}", PATH_SYSFS_GPIO, base + u32::from(pin))).is_ok()
+    })
+}
+
+// Returns a description of the alternate function selected for the pin. Only the
+// most common ALT0 functions on the BCM283x GPIO header are named explicitly.
+fn alt_function(pin: u8, mode: Mode) -> String {
+    let name = match (mode, pin) {
+        (Mode::Alt0, 2) => "I2C1 SDA",
+        (Mode::Alt0, 3) => "I2C1 SCL",
+        (Mode::Alt0, 7) => "SPI0 CE1",
+        (Mode::Alt0, 8) => "SPI0 CE0",
+        (Mode::Alt0, 9) => "SPI0 MISO",
+        (Mode::Alt0, 10) => "SPI0 MOSI",
+        (Mode::Alt0, 11) => "SPI0 SCLK",
+        (Mode::Alt0, 12) | (Mode::Alt0, 13) => "PWM",
+        (Mode::Alt0, 14) => "UART TXD",
+        (Mode::Alt0, 15) => "UART RXD",
+        (Mode::Alt0, 18..=21) => "PCM/I2S",
+        _ => "",
+    };
+
+    if name.is_empty() {
+        format!("alternate function {}", mode)
+    } else {
+        format!("{} ({})", name, mode)
+    }
+}
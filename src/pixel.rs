@@ -29,6 +29,8 @@ const IDX_RED: usize = 3;
 #[derive(Debug, Copy, Clone)]
 pub struct Pixel {
     value: [u8; 4], // Brightness, blue, green, red
+    white: u8,
+    low: [u8; 3], // Low bytes of 16-bit red, green, blue
 }
 
 impl Pixel {
@@ -50,6 +52,71 @@ impl Pixel {
         self.value[IDX_RED] = red;
         self.value[IDX_GREEN] = green;
         self.value[IDX_BLUE] = blue;
+        self.low = [red, green, blue];
+    }
+
+    /// Returns a tuple containing the 16-bit values for red, green and blue.
+    ///
+    /// 8-bit values are scaled to the full 16-bit range, so `255` is returned as
+    /// `65535`.
+    #[inline]
+    pub fn rgb16(&self) -> (u16, u16, u16) {
+        (
+            u16::from_be_bytes([self.value[IDX_RED], self.low[0]]),
+            u16::from_be_bytes([self.value[IDX_GREEN], self.low[1]]),
+            u16::from_be_bytes([self.value[IDX_BLUE], self.low[2]]),
+        )
+    }
+
+    /// Sets the 16-bit values for red, green and blue.
+    ///
+    /// `red`, `green` and `blue` are specified as 16-bit values between `0` (0%) and `65535` (100%).
+    /// The full 16-bit values are only sent to pixels that support them, such as the
+    /// HD108. Other pixels receive the 8 most significant bits.
+    #[inline]
+    pub fn set_rgb16(&mut self, red: u16, green: u16, blue: u16) {
+        let [red, red_low] = red.to_be_bytes();
+        let [green, green_low] = green.to_be_bytes();
+        let [blue, blue_low] = blue.to_be_bytes();
+
+        self.value[IDX_RED] = red;
+        self.value[IDX_GREEN] = green;
+        self.value[IDX_BLUE] = blue;
+        self.low = [red_low, green_low, blue_low];
+    }
+
+    /// Returns the value for white.
+    ///
+    /// The white value is only sent to RGBW pixels, such as the SK6812 RGBW.
+    #[inline]
+    pub fn white(&self) -> u8 {
+        self.white
+    }
+
+    /// Sets the value for white.
+    ///
+    /// `white` is specified as an 8-bit value between `0` (0%) and `255` (100%).
+    /// The white value is only sent to RGBW pixels, such as the SK6812 RGBW.
+    #[inline]
+    pub fn set_white(&mut self, white: u8) {
+        self.white = white;
+    }
+
+    /// Returns a tuple containing the values for red, green, blue and white.
+    #[inline]
+    pub fn rgbw(&self) -> (u8, u8, u8, u8) {
+        let (red, green, blue) = self.rgb();
+
+        (red, green, blue, self.white)
+    }
+
+    /// Sets the values for red, green, blue and white.
+    ///
+    /// `red`, `green`, `blue` and `white` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    #[inline]
+    pub fn set_rgbw(&mut self, red: u8, green: u8, blue: u8, white: u8) {
+        self.set_rgb(red, green, blue);
+        self.white = white;
     }
 
     /// Returns a tuple containing the values for red, green, blue and brightness.
@@ -73,6 +140,63 @@ impl Pixel {
         self.set_brightness(brightness);
     }
 
+    /// Returns a tuple containing the values for hue, saturation and value (HSV).
+    ///
+    /// `hue` is specified in degrees between `0.0` and `360.0`. `saturation` and
+    /// `value` are specified as floating point values between `0.0` (0%) and `1.0` (100%).
+    pub fn hsv(&self) -> (f32, f32, f32) {
+        let (red, green, blue) = self.rgb();
+        let (red, green, blue) = (
+            f32::from(red) / 255.0,
+            f32::from(green) / 255.0,
+            f32::from(blue) / 255.0,
+        );
+
+        let max = red.max(green).max(blue);
+        let delta = max - red.min(green).min(blue);
+
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == red {
+            60.0 * ((green - blue) / delta).rem_euclid(6.0)
+        } else if max == green {
+            60.0 * ((blue - red) / delta + 2.0)
+        } else {
+            60.0 * ((red - green) / delta + 4.0)
+        };
+
+        let saturation = if max == 0.0 { 0.0 } else { delta / max };
+
+        (hue, saturation, max)
+    }
+
+    /// Sets the values for red, green and blue based on hue, saturation and
+    /// value (HSV).
+    ///
+    /// `hue` is specified in degrees, and wraps around at `360.0`. `saturation` and
+    /// `value` are specified as floating point values between `0.0` (0%) and `1.0` (100%).
+    pub fn set_hsv(&mut self, hue: f32, saturation: f32, value: f32) {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (red, green, blue) = match hue as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+
+        let offset = value - chroma;
+        let channel = |value: f32| ((value + offset) * 255.0).round() as u8;
+
+        self.set_rgb(channel(red), channel(green), channel(blue));
+    }
+
     /// Returns the red value.
     #[inline]
     pub fn red(&self) -> u8 {
@@ -85,6 +209,7 @@ impl Pixel {
     #[inline]
     pub fn set_red(&mut self, red: u8) {
         self.value[IDX_RED] = red;
+        self.low[0] = red;
     }
 
     /// Returns the green value.
@@ -99,6 +224,7 @@ impl Pixel {
     #[inline]
     pub fn set_green(&mut self, green: u8) {
         self.value[IDX_GREEN] = green;
+        self.low[1] = green;
     }
 
     /// Returns the blue value.
@@ -113,6 +239,7 @@ impl Pixel {
     #[inline]
     pub fn set_blue(&mut self, blue: u8) {
         self.value[IDX_BLUE] = blue;
+        self.low[2] = blue;
     }
 
     /// Returns the brightness value.
@@ -126,18 +253,28 @@ impl Pixel {
     /// `brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%), and is converted to a 5-bit value.
     #[inline]
     pub fn set_brightness(&mut self, brightness: f32) {
-        self.value[IDX_BRIGHTNESS] = 0b1110_0000 | ((31.0 * brightness.max(0.0).min(1.0)) as u8);
+        self.value[IDX_BRIGHTNESS] = 0b1110_0000 | ((31.0 * brightness.clamp(0.0, 1.0)) as u8);
     }
 
-    /// Sets the red, green and blue values to `0`.
+    /// Sets the red, green, blue and white values to `0`.
     #[inline]
     pub fn clear(&mut self) {
-        self.set_rgb(0, 0, 0);
+        self.set_rgbw(0, 0, 0, 0);
+    }
+
+    #[inline]
+    pub(crate) fn low_bytes(&self) -> [u8; 3] {
+        self.low
+    }
+
+    #[inline]
+    pub(crate) fn brightness_bits(&self) -> u8 {
+        0b0001_1111 & self.value[IDX_BRIGHTNESS]
     }
 
     #[inline]
-    pub(crate) fn bytes(&self) -> &[u8] {
-        &self.value
+    pub(crate) fn set_brightness_bits(&mut self, brightness: u8) {
+        self.value[IDX_BRIGHTNESS] = 0b1110_0000 | (0b0001_1111 & brightness);
     }
 }
 
@@ -145,6 +282,154 @@ impl Default for Pixel {
     fn default() -> Self {
         Self {
             value: [0b1110_0000 | DEFAULT_BRIGHTNESS, 0, 0, 0],
+            white: 0,
+            low: [0; 3],
+        }
+    }
+}
+
+/// The values sent to a pixel after all corrections are applied.
+///
+/// `WirePixel` is returned by [`Blinkt::preview()`], and shows how a color
+/// is quantized before it's sent to the pixels. Two colors that result in the
+/// same `WirePixel` look identical.
+///
+/// [`Blinkt::preview()`]: struct.Blinkt.html#method.preview
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct WirePixel {
+    value: [u8; 4], // Brightness, blue, green, red
+    white: u8,
+    low: [u8; 3], // Low bytes of 16-bit red, green, blue, before correction
+}
+
+impl WirePixel {
+    #[inline]
+    pub(crate) fn new(brightness: u8, red: u8, green: u8, blue: u8) -> Self {
+        Self {
+            value: [0b1110_0000 | (0b0001_1111 & brightness), blue, green, red],
+            white: 0,
+            low: [0; 3],
+        }
+    }
+
+    #[inline]
+    pub(crate) fn with_brightness(mut self, brightness: u8) -> Self {
+        self.value[IDX_BRIGHTNESS] = 0b1110_0000 | (0b0001_1111 & brightness);
+        self
+    }
+
+    // Keeps track of the low bytes of 16-bit values, so changes are detected
+    // even if they don't affect the 8-bit values.
+    #[inline]
+    pub(crate) fn with_low(mut self, low: [u8; 3]) -> Self {
+        self.low = low;
+        self
+    }
+
+    #[inline]
+    pub(crate) fn with_white(mut self, white: u8) -> Self {
+        self.white = white;
+        self
+    }
+
+    /// Returns the value for white, which is only sent to RGBW pixels.
+    #[inline]
+    pub fn white(&self) -> u8 {
+        self.white
+    }
+
+    /// Returns a tuple containing the values for red, green and blue.
+    #[inline]
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        (
+            self.value[IDX_RED],
+            self.value[IDX_GREEN],
+            self.value[IDX_BLUE],
+        )
+    }
+
+    /// Returns the red value.
+    #[inline]
+    pub fn red(&self) -> u8 {
+        self.value[IDX_RED]
+    }
+
+    /// Returns the green value.
+    #[inline]
+    pub fn green(&self) -> u8 {
+        self.value[IDX_GREEN]
+    }
+
+    /// Returns the blue value.
+    #[inline]
+    pub fn blue(&self) -> u8 {
+        self.value[IDX_BLUE]
+    }
+
+    /// Returns the 5-bit brightness value, between `0` (0%) and `31` (100%).
+    #[inline]
+    pub fn brightness(&self) -> u8 {
+        0b0001_1111 & self.value[IDX_BRIGHTNESS]
+    }
+
+    /// Returns the LED frame (3*1, 5*brightness, 8*blue, 8*green, 8*red) as it's
+    /// sent to the pixel.
+    #[inline]
+    pub fn bytes(&self) -> [u8; 4] {
+        self.value
+    }
+
+    /// Returns the LED frame with the red, green and blue values in the
+    /// specified order.
+    #[inline]
+    pub fn bytes_ordered(&self, color_order: ColorOrder) -> [u8; 4] {
+        let [brightness, blue, green, red] = self.value;
+        let [first, second, third] = color_order.arrange(red, green, blue);
+
+        [brightness, first, second, third]
+    }
+}
+
+/// The order in which the red, green and blue values are sent to a pixel.
+///
+/// The APA102 and SK9822 expect blue first, followed by green and red. Some
+/// clones use a different order, which can be selected with
+/// [`Blinkt::set_color_order()`], so colors don't have to be swapped manually.
+///
+/// [`Blinkt::set_color_order()`]: struct.Blinkt.html#method.set_color_order
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ColorOrder {
+    /// Blue, green, red.
+    Bgr,
+    /// Blue, red, green.
+    Brg,
+    /// Green, blue, red.
+    Gbr,
+    /// Green, red, blue.
+    Grb,
+    /// Red, blue, green.
+    Rbg,
+    /// Red, green, blue.
+    Rgb,
+}
+
+impl ColorOrder {
+    // Returns the red, green and blue values in this order.
+    #[inline]
+    pub(crate) fn arrange<T>(self, red: T, green: T, blue: T) -> [T; 3] {
+        match self {
+            ColorOrder::Bgr => [blue, green, red],
+            ColorOrder::Brg => [blue, red, green],
+            ColorOrder::Gbr => [green, blue, red],
+            ColorOrder::Grb => [green, red, blue],
+            ColorOrder::Rbg => [red, blue, green],
+            ColorOrder::Rgb => [red, green, blue],
         }
     }
 }
+
+impl Default for ColorOrder {
+    fn default() -> Self {
+        ColorOrder::Bgr
+    }
+}
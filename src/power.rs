@@ -0,0 +1,121 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::Duration;
+
+use crate::WirePixel;
+
+/// A model of the current drawn by a single pixel, used to estimate the total
+/// power draw of an LED strip.
+///
+/// The default model is based on a typical APA102 pixel, which draws around
+/// 1 mA while all LEDs are off, and around 20 mA for each of its red, green
+/// and blue LEDs at full intensity and brightness. Actual values depend on the
+/// LEDs used, and can be measured to create a more accurate model.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PowerModel {
+    idle_ma: f32,
+    red_ma: f32,
+    green_ma: f32,
+    blue_ma: f32,
+}
+
+impl PowerModel {
+    /// Constructs a new `PowerModel`.
+    ///
+    /// `idle_ma` sets the current drawn by a pixel while all of its LEDs are off.
+    /// `red_ma`, `green_ma` and `blue_ma` set the additional current drawn by
+    /// each LED at full intensity and brightness. All values are specified in mA.
+    pub fn new(idle_ma: f32, red_ma: f32, green_ma: f32, blue_ma: f32) -> Self {
+        Self {
+            idle_ma: idle_ma.max(0.0),
+            red_ma: red_ma.max(0.0),
+            green_ma: green_ma.max(0.0),
+            blue_ma: blue_ma.max(0.0),
+        }
+    }
+
+    /// Returns the current in mA drawn by a pixel while all of its LEDs are off.
+    pub fn idle_ma(&self) -> f32 {
+        self.idle_ma
+    }
+
+    /// Returns a tuple containing the current in mA drawn by the red, green and
+    /// blue LEDs at full intensity and brightness.
+    pub fn rgb_ma(&self) -> (f32, f32, f32) {
+        (self.red_ma, self.green_ma, self.blue_ma)
+    }
+
+    pub(crate) fn current_ma(&self, pixel: &WirePixel) -> f32 {
+        let (red, green, blue) = pixel.rgb();
+        let brightness = f32::from(pixel.brightness()) / 31.0;
+
+        self.idle_ma
+            + brightness
+                * (f32::from(red) * self.red_ma
+                    + f32::from(green) * self.green_ma
+                    + f32::from(blue) * self.blue_ma)
+                / 255.0
+    }
+}
+
+impl Default for PowerModel {
+    fn default() -> Self {
+        Self::new(1.0, 20.0, 20.0, 20.0)
+    }
+}
+
+/// Settings for gradually ramping up the power draw of the pixels.
+///
+/// Switching a long LED strip from dark to full white at once can cause
+/// voltage drops on marginal power supplies. When a soft start is set with
+/// [`Blinkt::set_soft_start()`], and the estimated current of a new frame
+/// exceeds the current of the previous frame by more than the threshold,
+/// `show()` ramps up to the new frame over the specified duration.
+///
+/// [`Blinkt::set_soft_start()`]: struct.Blinkt.html#method.set_soft_start
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SoftStart {
+    threshold_ma: f32,
+    duration: Duration,
+}
+
+impl SoftStart {
+    /// Constructs a new `SoftStart`.
+    ///
+    /// `threshold_ma` sets the increase in current in mA that triggers a ramp,
+    /// and `duration` sets the time it takes to ramp up to the new frame.
+    pub fn new(threshold_ma: f32, duration: Duration) -> Self {
+        Self {
+            threshold_ma: threshold_ma.max(0.0),
+            duration,
+        }
+    }
+
+    /// Returns the increase in current in mA that triggers a ramp.
+    pub fn threshold_ma(&self) -> f32 {
+        self.threshold_ma
+    }
+
+    /// Returns the time it takes to ramp up to a new frame.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
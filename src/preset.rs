@@ -0,0 +1,278 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::color;
+use crate::effect::{Effect, Value};
+use crate::Result;
+
+// A named set of parameter values for a specific effect.
+#[derive(Debug, Clone, PartialEq)]
+struct Preset {
+    effect: String,
+    parameters: Vec<(String, Value)>,
+}
+
+/// A collection of named parameter sets for effects.
+///
+/// A preset stores the current parameter values of an effect, which can be
+/// applied again later, for instance when a button or MIDI program change
+/// selects a different look for the running effect. Presets are only applied
+/// to an effect with the same name as the effect they were saved from.
+///
+/// Presets can be saved to and loaded from a text file, with a section for
+/// every preset:
+///
+/// ```text
+/// [sunset]
+/// effect = breathe
+/// color = 255 96 0
+/// period = 8
+/// ```
+///
+/// Colors can also be specified in any of the notations supported by
+/// [`color::parse()`], such as `color = #ff6000` or `color = orange`. If a
+/// value can't be parsed, the error includes its key and line number.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// #
+/// # use blinkt::PresetStore;
+/// use blinkt::effect::{Effect, Rainbow, Value};
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut presets = PresetStore::new();
+/// let mut effect = Rainbow::new();
+///
+/// effect.set_parameter("speed", Value::Float(2.0));
+/// presets.save_preset("party", &effect);
+/// presets.save("presets.txt")?;
+///
+/// let presets = PresetStore::load("presets.txt")?;
+/// presets.apply("party", &mut effect);
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`color::parse()`]: color/fn.parse.html
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PresetStore {
+    presets: BTreeMap<String, Preset>,
+}
+
+impl PresetStore {
+    /// Constructs a new, empty `PresetStore`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads presets from the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Saves all presets to the file at `path`, replacing its contents.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_text())?;
+
+        Ok(())
+    }
+
+    /// Parses presets from `text`, using the same format as `load()`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut store = Self::new();
+        let mut current: Option<String> = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                let name = line[1..line.len() - 1].trim().to_owned();
+                store.presets.entry(name.clone()).or_insert(Preset {
+                    effect: String::new(),
+                    parameters: Vec::new(),
+                });
+                current = Some(name);
+
+                continue;
+            }
+
+            let preset = current
+                .as_ref()
+                .and_then(|name| store.presets.get_mut(name))
+                .ok_or_else(|| invalid_line(number))?;
+            let (key, value) = line.split_once('=').ok_or_else(|| invalid_line(number))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            if key == "effect" {
+                preset.effect = value.to_owned();
+            } else {
+                let value =
+                    parse_value(value).map_err(|reason| invalid_value(number, key, &reason))?;
+                preset.parameters.push((key.to_owned(), value));
+            }
+        }
+
+        Ok(store)
+    }
+
+    /// Returns the presets in the same format used by `save()`.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        for (name, preset) in &self.presets {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+
+            text.push_str(&format!("[{}]\neffect = {}\n", name, preset.effect));
+
+            for (parameter, value) in &preset.parameters {
+                let value = match *value {
+                    Value::Float(value) => value.to_string(),
+                    Value::Bool(value) => value.to_string(),
+                    Value::Color(red, green, blue) => format!("{} {} {}", red, green, blue),
+                };
+
+                text.push_str(&format!("{} = {}\n", parameter, value));
+            }
+        }
+
+        text
+    }
+
+    /// Stores the current parameter values of `effect` as the preset named
+    /// `name`, replacing any existing preset with the same name.
+    pub fn save_preset(&mut self, name: &str, effect: &dyn Effect) {
+        let parameters = effect
+            .parameters()
+            .into_iter()
+            .map(|parameter| (parameter.name().to_owned(), parameter.value()))
+            .collect();
+
+        self.presets.insert(
+            name.to_owned(),
+            Preset {
+                effect: effect.name().to_owned(),
+                parameters,
+            },
+        );
+    }
+
+    /// Applies the preset named `name` to the parameters of `effect`. Returns
+    /// `false` if the preset doesn't exist, or was saved from a different effect.
+    pub fn apply(&self, name: &str, effect: &mut dyn Effect) -> bool {
+        match self.presets.get(name) {
+            Some(preset) if preset.effect == effect.name() => {
+                for (parameter, value) in &preset.parameters {
+                    effect.set_parameter(parameter, *value);
+                }
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Removes the preset named `name`. Returns `true` if a preset was removed.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.presets.remove(name).is_some()
+    }
+
+    /// Returns the names of all presets in alphabetical order.
+    pub fn names(&self) -> Vec<&str> {
+        self.presets.keys().map(String::as_str).collect()
+    }
+
+    /// Returns the names of the presets saved from the effect named `effect`,
+    /// in alphabetical order.
+    pub fn names_for(&self, effect: &str) -> Vec<&str> {
+        self.presets
+            .iter()
+            .filter(|(_, preset)| preset.effect == effect)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Returns the number of presets.
+    pub fn len(&self) -> usize {
+        self.presets.len()
+    }
+
+    /// Returns `true` if the store doesn't contain any presets.
+    pub fn is_empty(&self) -> bool {
+        self.presets.is_empty()
+    }
+}
+
+// Parses `true` and `false` as on/off values, three integers as a color, a
+// number as a floating point value, and anything else as a color in one of the
+// notations supported by `color::parse()`. Returns a description of the problem
+// if the value can't be parsed.
+fn parse_value(value: &str) -> std::result::Result<Value, String> {
+    match value {
+        "true" => return Ok(Value::Bool(true)),
+        "false" => return Ok(Value::Bool(false)),
+        _ => (),
+    }
+
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    if let [red, green, blue] = parts.as_slice() {
+        if let (Ok(red), Ok(green), Ok(blue)) = (red.parse(), green.parse(), blue.parse()) {
+            return Ok(Value::Color(red, green, blue));
+        }
+    }
+
+    if let Ok(value) = value.parse() {
+        return Ok(Value::Float(value));
+    }
+
+    color::parse(value)
+        .map(|(red, green, blue)| Value::Color(red, green, blue))
+        .map_err(|err| err.to_string())
+}
+
+fn invalid_line(number: usize) -> crate::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid preset on line {}", number + 1),
+    )
+    .into()
+}
+
+fn invalid_value(number: usize, key: &str, reason: &str) -> crate::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "invalid value for `{}` on line {}: {}",
+            key,
+            number + 1,
+            reason
+        ),
+    )
+    .into()
+}
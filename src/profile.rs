@@ -0,0 +1,130 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fs;
+
+const PATH_HAT_VENDOR: &str = "/proc/device-tree/hat/vendor";
+const PATH_HAT_PRODUCT: &str = "/proc/device-tree/hat/product";
+
+/// The pins and number of pixels used by a board or LED strip in bitbanging mode.
+///
+/// Profiles for several Pimoroni boards are included. `detect()` identifies
+/// attached boards with an ID EEPROM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    name: String,
+    pin_data: u8,
+    pin_clock: u8,
+    num_pixels: usize,
+}
+
+impl Profile {
+    /// Constructs a new `Profile` with custom settings for the data pin, clock pin,
+    /// and number of pixels. Pins should be specified by their BCM GPIO pin numbers.
+    pub fn new(name: &str, pin_data: u8, pin_clock: u8, num_pixels: usize) -> Self {
+        Self {
+            name: name.to_owned(),
+            pin_data,
+            pin_clock,
+            num_pixels,
+        }
+    }
+
+    /// Returns a `Profile` for the Pimoroni Blinkt!, with 8 pixels on data pin
+    /// GPIO 23 (physical pin 16) and clock pin GPIO 24 (physical pin 18).
+    pub fn blinkt() -> Self {
+        Self::new("Blinkt!", 23, 24, 8)
+    }
+
+    /// Returns a `Profile` for the Pimoroni Rainbow HAT, with 7 pixels on data pin
+    /// GPIO 10 (physical pin 19) and clock pin GPIO 11 (physical pin 23).
+    pub fn rainbow_hat() -> Self {
+        Self::new("Rainbow HAT", 10, 11, 7)
+    }
+
+    /// Returns a `Profile` for the Pimoroni Fan SHIM, with a single pixel on data
+    /// pin GPIO 15 (physical pin 10) and clock pin GPIO 14 (physical pin 8).
+    pub fn fan_shim() -> Self {
+        Self::new("Fan SHIM", 15, 14, 1)
+    }
+
+    /// Returns the `Profile` for the attached board, based on the vendor and product
+    /// information stored in its ID EEPROM.
+    ///
+    /// The Raspberry Pi firmware makes this information available through
+    /// `/proc/device-tree/hat/`. Returns `None` if no board with an ID EEPROM
+    /// is attached, or the board isn't recognized. Note that some boards,
+    /// including the Blinkt!, don't have an ID EEPROM.
+    pub fn detect() -> Option<Self> {
+        let vendor = read_hat_string(PATH_HAT_VENDOR)?;
+        let product = hat_product()?;
+
+        if !vendor.to_lowercase().contains("pimoroni") {
+            return None;
+        }
+
+        [Self::blinkt(), Self::rainbow_hat(), Self::fan_shim()]
+            .into_iter()
+            .find(|profile| product.eq_ignore_ascii_case(&profile.name))
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the BCM GPIO pin number of the data pin.
+    pub fn pin_data(&self) -> u8 {
+        self.pin_data
+    }
+
+    /// Returns the BCM GPIO pin number of the clock pin.
+    pub fn pin_clock(&self) -> u8 {
+        self.pin_clock
+    }
+
+    /// Returns the number of pixels.
+    pub fn num_pixels(&self) -> usize {
+        self.num_pixels
+    }
+
+    /// Sets the number of pixels.
+    pub fn set_num_pixels(&mut self, num_pixels: usize) {
+        self.num_pixels = num_pixels;
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::blinkt()
+    }
+}
+
+// Returns the product name stored in the ID EEPROM of an attached HAT.
+pub(crate) fn hat_product() -> Option<String> {
+    read_hat_string(PATH_HAT_PRODUCT)
+}
+
+fn read_hat_string(path: &str) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|value| value.trim_end_matches('\0').trim().to_owned())
+        .filter(|value| !value.is_empty())
+}
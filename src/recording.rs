@@ -0,0 +1,620 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{Driver, Result};
+
+// Identifies a recording file, and its format version.
+const MAGIC: &[u8; 8] = b"BLINKTR1";
+// Timestamp and length of a frame.
+const ENTRY_HEADER_LEN: usize = 12;
+
+/// A frame read from a recording file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedFrame {
+    timestamp: Duration,
+    data: Vec<u8>,
+}
+
+impl RecordedFrame {
+    /// Returns the time the frame was sent, relative to the Unix epoch.
+    pub fn timestamp(&self) -> Duration {
+        self.timestamp
+    }
+
+    /// Returns all bytes sent for the frame, including the start frame and end
+    /// frame.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// The first difference between two recordings found by
+/// [`RecordingOutput::compare()`].
+///
+/// [`RecordingOutput::compare()`]: struct.RecordingOutput.html#method.compare
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordingMismatch {
+    /// The recordings contain a different number of frames. Includes the
+    /// expected and actual number of frames.
+    FrameCount(usize, usize),
+    /// The data of a frame is different. Includes the index of the frame.
+    Data(usize),
+    /// A frame was sent too early or too late. Includes the index of the frame,
+    /// and the difference between the expected and actual time since the first
+    /// frame.
+    Timing(usize, Duration),
+}
+
+impl fmt::Display for RecordingMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RecordingMismatch::FrameCount(expected, actual) => {
+                write!(f, "Expected {} frames, found {}", expected, actual)
+            }
+            RecordingMismatch::Data(index) => write!(f, "Frame {} contains different data", index),
+            RecordingMismatch::Timing(index, difference) => {
+                write!(f, "Frame {} is off by {:?}", index, difference)
+            }
+        }
+    }
+}
+
+impl error::Error for RecordingMismatch {}
+
+/// A [`Driver`] that appends every frame to a file.
+///
+/// Frames are stored with a timestamp, so the output of a device can be
+/// captured in the field and inspected later through `RecordingOutput::read()`.
+/// The file starts with the 8-byte identifier `BLINKTR1`, followed by one
+/// entry for every frame: the timestamp in microseconds since the Unix epoch
+/// (64-bit little-endian), the length of the frame in bytes (32-bit
+/// little-endian), and the frame data. Every frame is flushed to the file once
+/// it's complete.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// #
+/// # use blinkt::{Blinkt, RecordingOutput};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::with_driver(RecordingOutput::create("frames.bin")?, 8);
+///
+/// blinkt.set_all_pixels(255, 0, 0);
+/// blinkt.show()?;
+///
+/// for frame in RecordingOutput::read("frames.bin")? {
+///     println!("{:?}: {} bytes", frame.timestamp(), frame.data().len());
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`Driver`]: trait.Driver.html
+#[derive(Debug)]
+pub struct RecordingOutput {
+    writer: BufWriter<File>,
+    frame: Vec<u8>,
+}
+
+impl RecordingOutput {
+    /// Opens the file at `path` for recording, creating it if it doesn't exist.
+    /// New frames are appended to an existing recording.
+    ///
+    /// An incomplete frame at the end of an existing recording is removed
+    /// first, so it can't hide the new frames from `read()`. Returns an error if
+    /// the file isn't a recording file.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+
+        // An empty file, or an incomplete identifier, starts a new recording.
+        let len = if contents.starts_with(MAGIC) {
+            let mut offset = MAGIC.len();
+            while let Some((_, data)) = next_entry(&contents, offset) {
+                offset = data.end;
+            }
+
+            offset
+        } else if MAGIC.starts_with(&contents) {
+            0
+        } else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a recording file").into());
+        };
+
+        file.set_len(len as u64)?;
+        file.seek(SeekFrom::Start(len as u64))?;
+
+        let mut writer = BufWriter::new(file);
+        if len == 0 {
+            writer.write_all(MAGIC)?;
+            writer.flush()?;
+        }
+
+        Ok(Self {
+            writer,
+            frame: Vec::new(),
+        })
+    }
+
+    /// Reads all frames from the recording file at `path`.
+    ///
+    /// An incomplete frame at the end of the file, which is left behind if the
+    /// process is terminated while a frame is written, is ignored.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<RecordedFrame>> {
+        let contents = fs::read(path)?;
+
+        if !contents.starts_with(MAGIC) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a recording file").into());
+        }
+
+        let mut frames = Vec::new();
+        let mut offset = MAGIC.len();
+
+        while let Some((timestamp, data)) = next_entry(&contents, offset) {
+            offset = data.end;
+            frames.push(RecordedFrame {
+                timestamp,
+                data: contents[data].to_vec(),
+            });
+        }
+
+        Ok(frames)
+    }
+
+    /// Sends recorded frames to `driver`, with the same time between frames as
+    /// when they were recorded.
+    ///
+    /// This replays a recording through a simulator such as a [`MockOutput`] or
+    /// [`TerminalOutput`], or through an output connected to pixels.
+    ///
+    /// [`MockOutput`]: struct.MockOutput.html
+    /// [`TerminalOutput`]: struct.TerminalOutput.html
+    pub fn replay<D: Driver>(frames: &[RecordedFrame], driver: &mut D) -> Result<()> {
+        let started = Instant::now();
+        let first = frames
+            .first()
+            .map_or_else(Duration::default, |frame| frame.timestamp);
+
+        for frame in frames {
+            let elapsed = frame.timestamp.saturating_sub(first);
+            thread::sleep(elapsed.saturating_sub(started.elapsed()));
+
+            driver.begin_frame()?;
+            driver.write(&frame.data)?;
+            driver.end_frame()?;
+        }
+
+        Ok(())
+    }
+
+    /// Compares the frames of a new recording to the frames of a previous
+    /// recording, for instance to test that an animation doesn't change.
+    ///
+    /// The recordings match if they contain the same number of frames with the
+    /// same data, and the time since the first frame of every frame differs by at
+    /// most `tolerance`. Returns the first difference otherwise.
+    ///
+    /// ```rust
+    /// # use std::error::Error;
+    /// # use std::fs;
+    /// # use std::time::Duration;
+    /// #
+    /// # use blinkt::{Blinkt, RecordingMismatch, RecordingOutput};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # let path = std::env::temp_dir().join(format!("blinkt-compare-{}.bin", std::process::id()));
+    /// # let _ = fs::remove_file(&path);
+    /// let mut blinkt = Blinkt::with_driver(RecordingOutput::create(&path)?, 8);
+    /// for value in 0..10 {
+    ///     blinkt.set_all_pixels(value, 0, 0);
+    ///     blinkt.show()?;
+    /// }
+    /// drop(blinkt);
+    ///
+    /// let frames = RecordingOutput::read(&path)?;
+    /// # fs::remove_file(&path)?;
+    /// let tolerance = Duration::from_millis(50);
+    /// assert_eq!(RecordingOutput::compare(&frames, &frames, tolerance), Ok(()));
+    ///
+    /// // The frame cleared by dropping blinkt is missing.
+    /// assert_eq!(
+    ///     RecordingOutput::compare(&frames, &frames[..10], tolerance),
+    ///     Err(RecordingMismatch::FrameCount(11, 10))
+    /// );
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn compare(
+        expected: &[RecordedFrame],
+        actual: &[RecordedFrame],
+        tolerance: Duration,
+    ) -> std::result::Result<(), RecordingMismatch> {
+        let elapsed = |frames: &[RecordedFrame], index: usize| {
+            frames[index].timestamp.saturating_sub(frames[0].timestamp)
+        };
+
+        for index in 0..expected.len().min(actual.len()) {
+            if expected[index].data != actual[index].data {
+                return Err(RecordingMismatch::Data(index));
+            }
+
+            let (expected_elapsed, actual_elapsed) =
+                (elapsed(expected, index), elapsed(actual, index));
+            let difference = if expected_elapsed > actual_elapsed {
+                expected_elapsed - actual_elapsed
+            } else {
+                actual_elapsed - expected_elapsed
+            };
+
+            if difference > tolerance {
+                return Err(RecordingMismatch::Timing(index, difference));
+            }
+        }
+
+        if expected.len() != actual.len() {
+            return Err(RecordingMismatch::FrameCount(expected.len(), actual.len()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Plays back recorded frames through a [`Driver`], with controls to pause,
+/// change the playback speed, and seek.
+///
+/// `RecordingPlayer` doesn't block. Call `update()` regularly, for instance from
+/// the main loop of an interactive application, to send the frame for the
+/// current playback position. Frames that were skipped because `update()`
+/// wasn't called often enough, or because of a high playback speed, aren't
+/// sent. Once the last frame is sent, playback stops, and the callback set with
+/// `on_complete()` is called.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// # use std::thread;
+/// # use std::time::Duration;
+/// #
+/// # use blinkt::{RecordingOutput, RecordingPlayer, TcpOutput};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut output = TcpOutput::connect("raspberrypi.local:7890")?;
+/// let mut player = RecordingPlayer::new(RecordingOutput::read("show.bin")?);
+///
+/// // Skip the intro, and play the rest at double speed.
+/// player.seek(Duration::from_secs(10));
+/// player.set_speed(2.0);
+/// player.play();
+///
+/// while player.is_playing() {
+///     player.update(&mut output)?;
+///     thread::sleep(Duration::from_millis(5));
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`Driver`]: trait.Driver.html
+pub struct RecordingPlayer {
+    frames: Vec<RecordedFrame>,
+    position: Duration,
+    speed: f32,
+    playing: bool,
+    updated: Option<Instant>,
+    // Index of the most recently sent frame.
+    sent: Option<usize>,
+    on_complete: Option<Box<dyn FnMut() + Send>>,
+}
+
+impl fmt::Debug for RecordingPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordingPlayer")
+            .field("frames", &self.frames.len())
+            .field("position", &self.position)
+            .field("speed", &self.speed)
+            .field("playing", &self.playing)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RecordingPlayer {
+    /// Constructs a new paused `RecordingPlayer` for `frames`, positioned at the
+    /// first frame.
+    pub fn new(frames: Vec<RecordedFrame>) -> Self {
+        Self {
+            frames,
+            position: Duration::default(),
+            speed: 1.0,
+            playing: false,
+            updated: None,
+            sent: None,
+            on_complete: None,
+        }
+    }
+
+    /// Starts or resumes playback. If playback is complete, it starts over at
+    /// the first frame.
+    pub fn play(&mut self) {
+        if self.is_complete() {
+            self.seek(Duration::default());
+        }
+
+        self.playing = true;
+        self.updated = None;
+    }
+
+    /// Pauses playback at the current position.
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Returns `true` if the player is playing.
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Returns the playback speed.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Sets the playback speed, as a multiple of the original speed. Negative
+    /// values are treated as `0.0`.
+    ///
+    /// By default, this is set to `1.0`.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+
+    /// Returns the playback position, as the time since the first frame.
+    pub fn position(&self) -> Duration {
+        self.position
+    }
+
+    /// Returns the time between the first and the last frame.
+    pub fn duration(&self) -> Duration {
+        match (self.frames.first(), self.frames.last()) {
+            (Some(first), Some(last)) => last.timestamp.saturating_sub(first.timestamp),
+            _ => Duration::default(),
+        }
+    }
+
+    /// Moves the playback position to `position`, limited to the duration of
+    /// the recording. The frame at the new position is sent by the next call to
+    /// `update()`, even when the player is paused.
+    pub fn seek(&mut self, position: Duration) {
+        self.position = position.min(self.duration());
+        self.sent = None;
+        self.updated = None;
+    }
+
+    /// Sets a callback that's called when playback reaches the end of the
+    /// recording.
+    pub fn on_complete<F>(&mut self, on_complete: F)
+    where
+        F: FnMut() + Send + 'static,
+    {
+        self.on_complete = Some(Box::new(on_complete));
+    }
+
+    /// Advances the playback position by the time since the previous call, and
+    /// sends the frame for the current position to `driver` if it wasn't sent
+    /// yet. Returns `true` if a frame was sent.
+    pub fn update<D: Driver>(&mut self, driver: &mut D) -> Result<bool> {
+        let now = Instant::now();
+        if self.playing {
+            if let Some(updated) = self.updated {
+                // Limited to the remaining time, so high speeds can't overflow.
+                let remaining = self.duration().saturating_sub(self.position);
+                let elapsed = now.saturating_duration_since(updated).as_secs_f64();
+                let advance = (elapsed * f64::from(self.speed)).min(remaining.as_secs_f64());
+
+                self.position =
+                    (self.position + Duration::from_secs_f64(advance)).min(self.duration());
+            }
+
+            self.updated = Some(now);
+        }
+
+        let first = match self.frames.first() {
+            Some(frame) => frame.timestamp,
+            None => return Ok(false),
+        };
+
+        // The most recent frame at or before the playback position.
+        let current = self
+            .frames
+            .iter()
+            .rposition(|frame| frame.timestamp.saturating_sub(first) <= self.position)
+            .unwrap_or(0);
+
+        let sent = self.sent != Some(current);
+        if sent {
+            driver.begin_frame()?;
+            driver.write(&self.frames[current].data)?;
+            driver.end_frame()?;
+            self.sent = Some(current);
+        }
+
+        if self.playing && self.is_complete() {
+            self.playing = false;
+
+            if let Some(on_complete) = &mut self.on_complete {
+                on_complete();
+            }
+        }
+
+        Ok(sent)
+    }
+
+    fn is_complete(&self) -> bool {
+        self.position >= self.duration() && self.sent == Some(self.frames.len().saturating_sub(1))
+    }
+}
+
+// Returns the timestamp and the range of the data of the frame stored at
+// `offset`, or `None` if the frame is incomplete.
+fn next_entry(contents: &[u8], offset: usize) -> Option<(Duration, Range<usize>)> {
+    let header = contents.get(offset..offset + ENTRY_HEADER_LEN)?;
+    let mut timestamp = [0u8; 8];
+    let mut len = [0u8; 4];
+    timestamp.copy_from_slice(&header[..8]);
+    len.copy_from_slice(&header[8..]);
+
+    let start = offset + ENTRY_HEADER_LEN;
+    let end = start + u32::from_le_bytes(len) as usize;
+    if end > contents.len() {
+        return None;
+    }
+
+    Some((
+        Duration::from_micros(u64::from_le_bytes(timestamp)),
+        start..end,
+    ))
+}
+
+impl Driver for RecordingOutput {
+    fn begin_frame(&mut self) -> Result<()> {
+        self.frame.clear();
+
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.frame.extend_from_slice(data);
+
+        Ok(())
+    }
+
+    fn end_frame(&mut self) -> Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+
+        self.writer.write_all(&timestamp.to_le_bytes())?;
+        self.writer
+            .write_all(&(self.frame.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&self.frame)?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::MockOutput;
+
+    fn frame(millis: u64, data: &[u8]) -> RecordedFrame {
+        RecordedFrame {
+            timestamp: Duration::from_millis(millis),
+            data: data.to_vec(),
+        }
+    }
+
+    #[test]
+    fn replay_keeps_timing() {
+        let frames = [frame(1000, &[1]), frame(1020, &[2]), frame(1040, &[3])];
+        let mut mock = MockOutput::new();
+
+        let started = Instant::now();
+        RecordingOutput::replay(&frames, &mut mock).unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(40));
+        assert_eq!(mock.frames(), [vec![1], vec![2], vec![3]]);
+    }
+
+    #[test]
+    fn player_transport_controls() {
+        let frames = vec![frame(1000, &[1]), frame(1100, &[2]), frame(1200, &[3])];
+        let mut player = RecordingPlayer::new(frames);
+        let mut mock = MockOutput::new();
+
+        let completed = Arc::new(AtomicBool::new(false));
+        let on_complete = completed.clone();
+        player.on_complete(move || on_complete.store(true, Ordering::SeqCst));
+
+        // Paused players only send the frame at the current position once.
+        assert!(player.update(&mut mock).unwrap());
+        assert!(!player.update(&mut mock).unwrap());
+        assert_eq!(player.duration(), Duration::from_millis(200));
+
+        player.seek(Duration::from_millis(150));
+        assert!(player.update(&mut mock).unwrap());
+        assert_eq!(mock.last_frame(), Some(vec![2]));
+
+        // At ten times the speed, the last frame is due after 5 ms.
+        player.set_speed(10.0);
+        player.play();
+        while player.is_playing() {
+            player.update(&mut mock).unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(mock.frames(), [vec![1], vec![2], vec![3]]);
+        assert_eq!(player.position(), player.duration());
+        assert!(completed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn compare_timing_tolerance() {
+        let expected = [frame(1000, &[1]), frame(1020, &[2])];
+        let tolerance = Duration::from_millis(5);
+
+        // Only the time since the first frame matters.
+        let actual = [frame(5000, &[1]), frame(5024, &[2])];
+        assert_eq!(
+            RecordingOutput::compare(&expected, &actual, tolerance),
+            Ok(())
+        );
+
+        let actual = [frame(5000, &[1]), frame(5026, &[2])];
+        assert_eq!(
+            RecordingOutput::compare(&expected, &actual, tolerance),
+            Err(RecordingMismatch::Timing(1, Duration::from_millis(6)))
+        );
+
+        let actual = [frame(5000, &[1]), frame(5020, &[3])];
+        assert_eq!(
+            RecordingOutput::compare(&expected, &actual, tolerance),
+            Err(RecordingMismatch::Data(1))
+        );
+    }
+}
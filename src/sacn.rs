@@ -0,0 +1,374 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! An E1.31 (sACN) receiver, to use the pixels as a show prop controlled by
+//! xLights, Falcon Player and other lighting software.
+//!
+//! [`Server`] receives E1.31 data packets, and maps consecutive universes
+//! onto the local buffer in the same way as the [`artnet`] receiver, starting
+//! at the universe selected with [`Server::set_start_universe()`]. Use
+//! [`Server::join_multicast()`] to receive a universe that's sent to its
+//! multicast address instead of unicast.
+//!
+//! When multiple sources send data for the same universe, only the source
+//! with the highest priority is used. If that source stops sending data for
+//! more than 2.5 seconds, or terminates its stream, the next packet from any
+//! other source takes over. `show()` is called after every data packet, unless
+//! the packet specifies a synchronization universe, in which case `show()` is
+//! called once the corresponding synchronization packet is received.
+//!
+//! E1.31 doesn't support authentication, and data packets are accepted from any
+//! host without a rate limit. Source priorities don't protect against a host
+//! that sends with a higher priority, so only bind to an address on a trusted
+//! lighting network, or restrict access with a firewall.
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! #
+//! # use blinkt::Blinkt;
+//! use blinkt::sacn::{Server, PORT};
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let mut blinkt = Blinkt::with_settings(23, 24, 300)?;
+//! let mut server = Server::bind(("0.0.0.0", PORT))?;
+//!
+//! // 300 pixels are spread out over universes 1 and 2.
+//! server.join_multicast(1)?;
+//! server.join_multicast(2)?;
+//!
+//! loop {
+//!     server.receive(&mut blinkt)?;
+//! }
+//! # }
+//! ```
+//!
+//! [`Server`]: struct.Server.html
+//! [`artnet`]: ../artnet/index.html
+//! [`Server::set_start_universe()`]: struct.Server.html#method.set_start_universe
+//! [`Server::join_multicast()`]: struct.Server.html#method.join_multicast
+
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::{dmx, Blinkt, Result};
+
+/// The UDP port used by E1.31.
+pub const PORT: u16 = 5568;
+
+/// The number of pixels controlled by a single universe.
+pub const PIXELS_PER_UNIVERSE: usize = dmx::PIXELS_PER_UNIVERSE;
+
+const ACN_PACKET_IDENTIFIER: &[u8; 12] = b"ASC-E1.17\0\0\0";
+const VECTOR_ROOT_DATA: u32 = 0x0000_0004;
+const VECTOR_ROOT_EXTENDED: u32 = 0x0000_0008;
+const VECTOR_FRAMING_DATA: u32 = 0x0000_0002;
+const VECTOR_EXTENDED_SYNC: u32 = 0x0000_0001;
+const DATA_HEADER_LEN: usize = 126;
+const MAX_PACKET_SIZE: usize = DATA_HEADER_LEN + 512;
+const OPTION_PREVIEW: u8 = 0x80;
+const OPTION_TERMINATED: u8 = 0x40;
+// Time after which a source is considered lost (E131_NETWORK_DATA_LOSS_TIMEOUT).
+const SOURCE_TIMEOUT: Duration = Duration::from_millis(2500);
+
+/// A packet received by the E1.31 [`Server`].
+///
+/// [`Server`]: struct.Server.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    /// DMX channel values for a universe.
+    Data {
+        /// The unique identifier of the source.
+        cid: [u8; 16],
+        /// The priority of the source, between `0` and `200`.
+        priority: u8,
+        /// The universe used to synchronize this packet, or `0` if the data
+        /// should be shown immediately.
+        sync_universe: u16,
+        /// The sequence number.
+        sequence: u8,
+        /// `true` if the data is intended for visualization only.
+        preview: bool,
+        /// `true` if the source stops sending data for this universe.
+        terminated: bool,
+        /// The universe, between `1` and `63999`.
+        universe: u16,
+        /// The channel values, starting at channel 1.
+        data: Vec<u8>,
+    },
+    /// A request to show all previously received data that's synchronized
+    /// through `universe`.
+    Sync {
+        /// The synchronization universe.
+        universe: u16,
+    },
+}
+
+/// Decodes an E1.31 packet. Returns `None` for unsupported or invalid packets.
+///
+/// ```rust
+/// use blinkt::sacn::{decode, Packet};
+///
+/// let mut packet = vec![0u8; 129];
+/// packet[4..16].copy_from_slice(b"ASC-E1.17\0\0\0");
+/// // Root layer and framing layer vectors.
+/// packet[18..22].copy_from_slice(&4u32.to_be_bytes());
+/// packet[40..44].copy_from_slice(&2u32.to_be_bytes());
+/// // Priority, universe and property value count, including the start code.
+/// packet[108] = 100;
+/// packet[113..115].copy_from_slice(&1u16.to_be_bytes());
+/// packet[123..125].copy_from_slice(&4u16.to_be_bytes());
+/// packet[126..].copy_from_slice(&[255, 128, 0]);
+///
+/// assert_eq!(
+///     decode(&packet),
+///     Some(Packet::Data {
+///         cid: [0; 16],
+///         priority: 100,
+///         sync_universe: 0,
+///         sequence: 0,
+///         preview: false,
+///         terminated: false,
+///         universe: 1,
+///         data: vec![255, 128, 0],
+///     })
+/// );
+///
+/// // Packets that are shorter than their header or property value count are
+/// // rejected.
+/// assert_eq!(decode(&packet[..100]), None);
+/// assert_eq!(decode(&packet[..128]), None);
+/// ```
+pub fn decode(packet: &[u8]) -> Option<Packet> {
+    if packet.get(4..16)? != ACN_PACKET_IDENTIFIER {
+        return None;
+    }
+
+    let u16_at = |offset: usize| {
+        Some(u16::from_be_bytes([
+            *packet.get(offset)?,
+            *packet.get(offset + 1)?,
+        ]))
+    };
+    let u32_at = |offset: usize| {
+        let bytes = packet.get(offset..offset + 4)?;
+
+        Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+
+    match (u32_at(18)?, u32_at(40)?) {
+        (VECTOR_ROOT_DATA, VECTOR_FRAMING_DATA) => {
+            let header = packet.get(..DATA_HEADER_LEN)?;
+            // Only DMX data with the default start code is supported.
+            if header[125] != 0 {
+                return None;
+            }
+
+            let len = usize::from(u16_at(123)?.checked_sub(1)?);
+            let mut cid = [0u8; 16];
+            cid.copy_from_slice(&header[22..38]);
+
+            Some(Packet::Data {
+                cid,
+                priority: header[108],
+                sync_universe: u16_at(109)?,
+                sequence: header[111],
+                preview: header[112] & OPTION_PREVIEW != 0,
+                terminated: header[112] & OPTION_TERMINATED != 0,
+                universe: u16_at(113)?,
+                data: packet.get(DATA_HEADER_LEN..DATA_HEADER_LEN + len)?.to_vec(),
+            })
+        }
+        (VECTOR_ROOT_EXTENDED, VECTOR_EXTENDED_SYNC) => Some(Packet::Sync {
+            universe: u16_at(45)?,
+        }),
+        _ => None,
+    }
+}
+
+/// Returns the multicast address for `universe`.
+pub fn multicast_address(universe: u16) -> Ipv4Addr {
+    let [high, low] = universe.to_be_bytes();
+
+    Ipv4Addr::new(239, 255, high, low)
+}
+
+// The source currently in control of a universe.
+#[derive(Debug, Copy, Clone)]
+struct Source {
+    cid: [u8; 16],
+    priority: u8,
+    sequence: u8,
+    seen: Instant,
+}
+
+/// An E1.31 server that receives DMX data over UDP.
+#[derive(Debug)]
+pub struct Server {
+    socket: UdpSocket,
+    start_universe: u16,
+    sources: HashMap<u16, Source>,
+    pending: Vec<u16>,
+}
+
+impl Server {
+    /// Constructs a new `Server` listening on `address`.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(address)?,
+            start_universe: 1,
+            sources: HashMap::new(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Returns the local address the server is listening on.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// When enabled, `receive()` returns an error of kind `WouldBlock` instead of
+    /// waiting for the next packet.
+    ///
+    /// By default, this is set to `false`.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> Result<()> {
+        Ok(self.socket.set_nonblocking(nonblocking)?)
+    }
+
+    /// Joins the multicast group for `universe` on the default interface.
+    ///
+    /// Synchronization packets are sent to the multicast address of the
+    /// synchronization universe, which needs to be joined separately.
+    pub fn join_multicast(&self, universe: u16) -> Result<()> {
+        Ok(self
+            .socket
+            .join_multicast_v4(&multicast_address(universe), &Ipv4Addr::UNSPECIFIED)?)
+    }
+
+    /// Leaves the multicast group for `universe`.
+    pub fn leave_multicast(&self, universe: u16) -> Result<()> {
+        Ok(self
+            .socket
+            .leave_multicast_v4(&multicast_address(universe), &Ipv4Addr::UNSPECIFIED)?)
+    }
+
+    /// Returns the universe mapped onto the first pixel.
+    pub fn start_universe(&self) -> u16 {
+        self.start_universe
+    }
+
+    /// Sets the universe mapped onto the first pixel. Every following universe
+    /// controls the next 170 pixels.
+    ///
+    /// By default, this is set to `1`.
+    pub fn set_start_universe(&mut self, universe: u16) {
+        self.start_universe = universe;
+    }
+
+    /// Waits for the next packet, applies its DMX data to the local buffer of
+    /// `blinkt`, and calls `show()` when the pixels should be updated. Returns
+    /// `true` if `show()` was called.
+    pub fn receive(&mut self, blinkt: &mut Blinkt) -> Result<bool> {
+        let mut buffer = [0u8; MAX_PACKET_SIZE];
+        let (len, _) = self.socket.recv_from(&mut buffer)?;
+
+        match decode(&buffer[..len]) {
+            Some(Packet::Data {
+                cid,
+                priority,
+                sync_universe,
+                sequence,
+                preview: false,
+                terminated,
+                universe,
+                data,
+            }) => {
+                if terminated {
+                    if self.sources.get(&universe).map(|source| source.cid) == Some(cid) {
+                        self.sources.remove(&universe);
+                    }
+
+                    return Ok(false);
+                }
+
+                if !self.accept(universe, cid, priority, sequence)
+                    || !dmx::apply(blinkt, self.start_universe, universe, &data)
+                {
+                    return Ok(false);
+                }
+
+                if sync_universe != 0 {
+                    if !self.pending.contains(&sync_universe) {
+                        self.pending.push(sync_universe);
+                    }
+
+                    return Ok(false);
+                }
+            }
+            Some(Packet::Sync { universe }) => {
+                let len = self.pending.len();
+                self.pending.retain(|pending| *pending != universe);
+
+                if self.pending.len() == len {
+                    return Ok(false);
+                }
+            }
+            _ => return Ok(false),
+        }
+
+        blinkt.show()?;
+
+        Ok(true)
+    }
+
+    // Returns `true` if the packet comes from the source that's in control of
+    // the universe, or a source that takes over because its priority is higher
+    // or the current source was lost.
+    fn accept(&mut self, universe: u16, cid: [u8; 16], priority: u8, sequence: u8) -> bool {
+        let now = Instant::now();
+
+        if let Some(source) = self.sources.get(&universe) {
+            let lost = now.duration_since(source.seen) > SOURCE_TIMEOUT;
+
+            if source.cid == cid {
+                // Discard packets that arrive out of order, as described in
+                // section 6.7.2 of the E1.31 standard.
+                let difference = sequence.wrapping_sub(source.sequence) as i8;
+                if !lost && difference <= 0 && difference > -20 {
+                    return false;
+                }
+            } else if !lost && priority <= source.priority {
+                return false;
+            }
+        }
+
+        self.sources.insert(
+            universe,
+            Source {
+                cid,
+                priority,
+                sequence,
+                seen: now,
+            },
+        );
+
+        true
+    }
+}
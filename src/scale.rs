@@ -0,0 +1,118 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::{Blinkt, Pixel};
+
+/// The method used to fit a frame onto a different number of pixels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Each pixel takes the value of the nearest pixel in the frame, duplicating
+    /// or dropping pixels as needed.
+    Nearest,
+    /// Each pixel is interpolated between the two nearest pixels in the frame.
+    Linear,
+    /// The frame is centered without scaling. Pixels that fall outside of the
+    /// frame are cleared, and excess pixels in the frame are cropped.
+    CenterCrop,
+    /// The frame is repeated without scaling until all pixels are filled.
+    Tile,
+}
+
+impl Blinkt {
+    /// Copies `frame` to the local buffer, fitting it onto the available pixels
+    /// using the specified [`ScaleMode`].
+    ///
+    /// This is useful when frames are received from a source with a different
+    /// number of pixels, such as a network protocol or a recording.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::{Blinkt, Pixel, ScaleMode};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mut blinkt = Blinkt::with_settings(23, 24, 144)?;
+    /// let frame = vec![Pixel::default(); 60];
+    ///
+    /// blinkt.set_pixels_scaled(&frame, ScaleMode::Linear);
+    /// blinkt.show()?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ScaleMode`]: enum.ScaleMode.html
+    pub fn set_pixels_scaled(&mut self, frame: &[Pixel], mode: ScaleMode) {
+        if frame.is_empty() {
+            self.clear();
+            return;
+        }
+
+        let len = self.pixels.len();
+        let ratio = frame.len() as f32 / len as f32;
+
+        for (index, pixel) in self.pixels.iter_mut().enumerate() {
+            *pixel = match mode {
+                ScaleMode::Nearest => {
+                    let source = ((index as f32 + 0.5) * ratio) as usize;
+                    frame[source.min(frame.len() - 1)]
+                }
+                ScaleMode::Linear => {
+                    let position = ((index as f32 + 0.5) * ratio - 0.5).max(0.0);
+                    let source = (position as usize).min(frame.len() - 1);
+                    let next = (source + 1).min(frame.len() - 1);
+
+                    lerp(&frame[source], &frame[next], position - source as f32)
+                }
+                ScaleMode::CenterCrop => {
+                    let source = (index + frame.len() / 2).checked_sub(len / 2);
+                    match source.and_then(|source| frame.get(source)) {
+                        Some(value) => *value,
+                        None => {
+                            let mut value = *pixel;
+                            value.clear();
+                            value
+                        }
+                    }
+                }
+                ScaleMode::Tile => frame[index % frame.len()],
+            };
+        }
+
+        self.notify_change();
+    }
+}
+
+fn lerp(from: &Pixel, to: &Pixel, amount: f32) -> Pixel {
+    let (red_from, green_from, blue_from, brightness_from) = from.rgbb();
+    let (red_to, green_to, blue_to, brightness_to) = to.rgbb();
+    let channel = |from: u8, to: u8| {
+        (f32::from(from) + (f32::from(to) - f32::from(from)) * amount).round() as u8
+    };
+
+    let mut pixel = Pixel::default();
+    pixel.set_rgbb(
+        channel(red_from, red_to),
+        channel(green_from, green_to),
+        channel(blue_from, blue_to),
+        brightness_from + (brightness_to - brightness_from) * amount,
+    );
+
+    pixel
+}
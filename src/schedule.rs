@@ -0,0 +1,312 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::effect::Effect;
+use crate::{PresetStore, Result};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+// A point in the year, stored as (month, day, minute of the day).
+type Moment = (u32, u32, u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    start: Moment,
+    end: Moment,
+    preset: String,
+}
+
+impl Rule {
+    // Ranges that end before they start wrap around the end of the year.
+    fn contains(&self, moment: Moment) -> bool {
+        if self.start <= self.end {
+            moment >= self.start && moment <= self.end
+        } else {
+            moment >= self.start || moment <= self.end
+        }
+    }
+}
+
+/// A yearly schedule that selects presets based on the local date and time.
+///
+/// Every rule activates a preset from a [`PresetStore`] between a start and
+/// end date, optionally with a time of day. Ranges that end before they start
+/// wrap around the end of the year. When multiple rules match, the rule that
+/// was added first takes precedence, so more specific rules should be added
+/// before more general ones.
+///
+/// Schedules can be saved to and loaded from a text file, with a rule on every
+/// line:
+///
+/// ```text
+/// 12-31 18:00 - 01-01 06:00 = fireworks
+/// 10-20 - 10-31 = halloween
+/// 12-01 - 12-26 = christmas
+/// ```
+///
+/// Dates without a time of day start at 00:00 and end at 23:59.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// # use std::time::{Instant, SystemTime};
+/// #
+/// # use blinkt::{Blinkt, DateSchedule, PresetStore};
+/// use blinkt::effect::Breathe;
+///
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::new()?;
+/// let presets = PresetStore::load("presets.txt")?;
+/// let mut schedule = DateSchedule::load("schedule.txt")?;
+/// let mut effect = Breathe::new(255, 255, 255);
+/// let start = Instant::now();
+///
+/// loop {
+///     schedule.update(&presets, &mut effect, SystemTime::now());
+///
+///     blinkt.render(&mut effect, start.elapsed());
+///     blinkt.show()?;
+/// }
+/// # }
+/// ```
+///
+/// [`PresetStore`]: struct.PresetStore.html
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DateSchedule {
+    rules: Vec<Rule>,
+    current: Option<String>,
+}
+
+impl DateSchedule {
+    /// Constructs a new, empty `DateSchedule`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a schedule from the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Saves all rules to the file at `path`, replacing its contents.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_text())?;
+
+        Ok(())
+    }
+
+    /// Parses a schedule from `text`, using the same format as `load()`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut schedule = Self::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let rule = parse_rule(line).ok_or_else(|| invalid_line(number))?;
+            schedule.rules.push(rule);
+        }
+
+        Ok(schedule)
+    }
+
+    /// Returns the rules in the same format used by `save()`.
+    pub fn to_text(&self) -> String {
+        self.rules
+            .iter()
+            .map(|rule| {
+                format!(
+                    "{} - {} = {}\n",
+                    format_moment(rule.start),
+                    format_moment(rule.end),
+                    rule.preset
+                )
+            })
+            .collect()
+    }
+
+    /// Adds a rule that activates the preset named `preset` from the start of
+    /// day `start` until the end of day `end`.
+    ///
+    /// Dates are specified as a tuple containing the month (`1`-`12`) and day
+    /// of the month (`1`-`31`).
+    pub fn add(&mut self, preset: &str, start: (u32, u32), end: (u32, u32)) {
+        self.add_with_time(preset, (start.0, start.1, 0, 0), (end.0, end.1, 23, 59));
+    }
+
+    /// Adds a rule that activates the preset named `preset` between `start`
+    /// and `end`, both inclusive.
+    ///
+    /// Dates are specified as a tuple containing the month (`1`-`12`), day of
+    /// the month (`1`-`31`), hour (`0`-`23`) and minute (`0`-`59`).
+    pub fn add_with_time(
+        &mut self,
+        preset: &str,
+        start: (u32, u32, u32, u32),
+        end: (u32, u32, u32, u32),
+    ) {
+        self.rules.push(Rule {
+            start: (start.0, start.1, start.2 * 60 + start.3),
+            end: (end.0, end.1, end.2 * 60 + end.3),
+            preset: preset.to_owned(),
+        });
+    }
+
+    /// Removes all rules that activate the preset named `preset`. Returns
+    /// `true` if any rules were removed.
+    pub fn remove(&mut self, preset: &str) -> bool {
+        let len = self.rules.len();
+        self.rules.retain(|rule| rule.preset != preset);
+
+        self.rules.len() != len
+    }
+
+    /// Returns the number of rules.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Returns `true` if the schedule doesn't contain any rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the name of the preset that's scheduled at `time`, based on the
+    /// local time zone. Returns `None` if no rule matches.
+    pub fn active(&self, time: SystemTime) -> Option<&str> {
+        let moment = local_moment(time)?;
+
+        self.rules
+            .iter()
+            .find(|rule| rule.contains(moment))
+            .map(|rule| rule.preset.as_str())
+    }
+
+    /// Applies the preset that's scheduled at `time` to `effect`, if it's
+    /// different from the preset that was scheduled during the previous call.
+    ///
+    /// Calling `update()` for every frame only changes the parameters of
+    /// `effect` whenever a different rule becomes active, which leaves any
+    /// changes made in the meantime intact. Returns `true` if a preset was
+    /// applied.
+    pub fn update(
+        &mut self,
+        presets: &PresetStore,
+        effect: &mut dyn Effect,
+        time: SystemTime,
+    ) -> bool {
+        let active = self.active(time).map(str::to_owned);
+        if active == self.current {
+            return false;
+        }
+
+        self.current = active;
+
+        match &self.current {
+            Some(preset) => presets.apply(preset, effect),
+            None => false,
+        }
+    }
+}
+
+// Parses a rule formatted as `MM-DD [HH:MM] - MM-DD [HH:MM] = preset`.
+fn parse_rule(line: &str) -> Option<Rule> {
+    let (range, preset) = line.split_once('=')?;
+    let (start, end) = range.split_once(" - ")?;
+    let preset = preset.trim();
+
+    if preset.is_empty() {
+        return None;
+    }
+
+    Some(Rule {
+        start: parse_moment(start, 0)?,
+        end: parse_moment(end, MINUTES_PER_DAY - 1)?,
+        preset: preset.to_owned(),
+    })
+}
+
+fn parse_moment(value: &str, default_minute: u32) -> Option<Moment> {
+    let mut parts = value.split_whitespace();
+    let (month, day) = parts.next()?.split_once('-')?;
+    let (month, day): (u32, u32) = (month.parse().ok()?, day.parse().ok()?);
+
+    let minute = match parts.next() {
+        Some(time) => {
+            let (hour, minute) = time.split_once(':')?;
+            let (hour, minute): (u32, u32) = (hour.parse().ok()?, minute.parse().ok()?);
+
+            if hour > 23 || minute > 59 {
+                return None;
+            }
+
+            hour * 60 + minute
+        }
+        None => default_minute,
+    };
+
+    if parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    Some((month, day, minute))
+}
+
+fn format_moment((month, day, minute): Moment) -> String {
+    format!(
+        "{:02}-{:02} {:02}:{:02}",
+        month,
+        day,
+        minute / 60,
+        minute % 60
+    )
+}
+
+// Converts `time` to the month, day and minute of the day in the local time
+// zone.
+fn local_moment(time: SystemTime) -> Option<Moment> {
+    let seconds = time.duration_since(UNIX_EPOCH).ok()?.as_secs() as libc::time_t;
+    let mut tm: libc::tm = unsafe { mem::zeroed() };
+
+    if unsafe { libc::localtime_r(&seconds, &mut tm) }.is_null() {
+        return None;
+    }
+
+    Some((
+        tm.tm_mon as u32 + 1,
+        tm.tm_mday as u32,
+        tm.tm_hour as u32 * 60 + tm.tm_min as u32,
+    ))
+}
+
+fn invalid_line(number: usize) -> crate::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid schedule rule on line {}", number + 1),
+    )
+    .into()
+}
@@ -0,0 +1,138 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::{Duration, Instant};
+
+use crate::Pixel;
+
+/// A temporal smoothing filter for noisy sources of frames.
+///
+/// `Smoothing` applies an exponential moving average to the red, green and
+/// blue values of each pixel, with separate time constants for rising values
+/// (attack) and falling values (decay). This removes flicker from displays
+/// driven by audio or screen capture, where consecutive frames can differ
+/// considerably.
+///
+/// The filter is based on the time that passed between frames, so it behaves
+/// the same regardless of the frame rate. Use [`Blinkt::set_smoothing()`] to
+/// apply the filter to all frames sent by `show()`.
+///
+/// [`Blinkt::set_smoothing()`]: struct.Blinkt.html#method.set_smoothing
+#[derive(Debug, Clone)]
+pub struct Smoothing {
+    attack: Duration,
+    decay: Duration,
+    state: Vec<[f32; 3]>,
+    updated: Option<Instant>,
+}
+
+impl Smoothing {
+    /// Constructs a new `Smoothing` filter.
+    ///
+    /// `attack` and `decay` set the time it takes for rising and falling values
+    /// to cover roughly 63% of the difference with their target. A zero duration
+    /// disables smoothing in that direction.
+    pub fn new(attack: Duration, decay: Duration) -> Self {
+        Self {
+            attack,
+            decay,
+            state: Vec::new(),
+            updated: None,
+        }
+    }
+
+    /// Returns the attack time.
+    pub fn attack(&self) -> Duration {
+        self.attack
+    }
+
+    /// Sets the attack time.
+    pub fn set_attack(&mut self, attack: Duration) {
+        self.attack = attack;
+    }
+
+    /// Returns the decay time.
+    pub fn decay(&self) -> Duration {
+        self.decay
+    }
+
+    /// Sets the decay time.
+    pub fn set_decay(&mut self, decay: Duration) {
+        self.decay = decay;
+    }
+
+    /// Discards the filter state, so the next frame is passed through unchanged.
+    pub fn reset(&mut self) {
+        self.state.clear();
+        self.updated = None;
+    }
+
+    /// Replaces the red, green and blue values of `pixels` with their smoothed
+    /// values.
+    pub fn apply(&mut self, pixels: &mut [Pixel]) {
+        let now = Instant::now();
+        let elapsed = self
+            .updated
+            .map_or(Duration::default(), |updated| now.duration_since(updated));
+        self.updated = Some(now);
+
+        if self.state.len() != pixels.len() {
+            self.state = pixels
+                .iter()
+                .map(|pixel| {
+                    let (red, green, blue) = pixel.rgb();
+                    [f32::from(red), f32::from(green), f32::from(blue)]
+                })
+                .collect();
+
+            return;
+        }
+
+        let attack = factor(elapsed, self.attack);
+        let decay = factor(elapsed, self.decay);
+
+        for (pixel, state) in pixels.iter_mut().zip(&mut self.state) {
+            let (red, green, blue) = pixel.rgb();
+
+            for (value, target) in state.iter_mut().zip(&[red, green, blue]) {
+                let target = f32::from(*target);
+                let factor = if target > *value { attack } else { decay };
+
+                *value += (target - *value) * factor;
+            }
+
+            pixel.set_rgb(
+                state[0].round() as u8,
+                state[1].round() as u8,
+                state[2].round() as u8,
+            );
+        }
+    }
+}
+
+// Returns the fraction of the difference with the target that's covered after
+// `elapsed` for the specified time constant.
+fn factor(elapsed: Duration, time_constant: Duration) -> f32 {
+    if time_constant == Duration::default() {
+        1.0
+    } else {
+        1.0 - (-elapsed.as_secs_f32() / time_constant.as_secs_f32()).exp()
+    }
+}
@@ -0,0 +1,278 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::Blinkt;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+// Elevation of the center of the sun at sunrise and sunset, taking refraction
+// and the radius of the sun into account.
+const HORIZON: f64 = -0.833;
+// Elevation below which it's fully dark (civil twilight).
+const TWILIGHT: f64 = -6.0;
+// Elevation above which it's fully light.
+const DAYLIGHT: f64 = 6.0;
+
+/// Adjusts the brightness and color temperature based on the position of the
+/// sun at a specific location.
+///
+/// The schedule follows the actual sunrise and sunset, so it automatically
+/// adapts to the time of year. Between the end of civil twilight (the sun 6°
+/// below the horizon) and the sun reaching 6° above the horizon, the brightness
+/// and color temperature gradually change between their night and day values.
+/// All calculations are based on absolute time, so no time zone needs to be
+/// configured.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// # use std::thread;
+/// # use std::time::{Duration, SystemTime};
+/// #
+/// # use blinkt::{Blinkt, SolarSchedule};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::new()?;
+/// // Amsterdam
+/// let schedule = SolarSchedule::new(52.37, 4.90);
+///
+/// println!("Sunset: {:?}", schedule.sunset(SystemTime::now()));
+///
+/// loop {
+///     schedule.apply(&mut blinkt, SystemTime::now());
+///     blinkt.set_all_pixels(255, 255, 255);
+///     blinkt.show()?;
+///
+///     thread::sleep(Duration::from_secs(60));
+/// }
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SolarSchedule {
+    latitude: f64,
+    longitude: f64,
+    brightness: (f32, f32),
+    color_temperature: (f32, f32),
+}
+
+impl SolarSchedule {
+    /// Constructs a new `SolarSchedule` for the specified location.
+    ///
+    /// `latitude` and `longitude` are specified in degrees, with positive values
+    /// north of the equator and east of the prime meridian.
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self {
+            latitude: latitude.clamp(-90.0, 90.0),
+            longitude,
+            brightness: (1.0, 0.1),
+            color_temperature: (6500.0, 2700.0),
+        }
+    }
+
+    /// Returns the latitude in degrees.
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    /// Returns the longitude in degrees.
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    /// Returns a tuple containing the day and night brightness.
+    pub fn brightness_range(&self) -> (f32, f32) {
+        self.brightness
+    }
+
+    /// Sets the global brightness during the day and at night.
+    ///
+    /// `day` and `night` are specified as floating point values between `0.0` (0%) and `1.0` (100%).
+    /// By default, these are set to `1.0` and `0.1`.
+    pub fn set_brightness_range(&mut self, day: f32, night: f32) {
+        self.brightness = (day.clamp(0.0, 1.0), night.clamp(0.0, 1.0));
+    }
+
+    /// Returns a tuple containing the day and night color temperature in K.
+    pub fn color_temperature_range(&self) -> (f32, f32) {
+        self.color_temperature
+    }
+
+    /// Sets the color temperature in K during the day and at night.
+    ///
+    /// By default, these are set to `6500.0` (daylight) and `2700.0` (warm white).
+    pub fn set_color_temperature_range(&mut self, day: f32, night: f32) {
+        self.color_temperature = (day.clamp(1000.0, 40000.0), night.clamp(1000.0, 40000.0));
+    }
+
+    /// Returns the elevation of the sun in degrees above the horizon at the
+    /// specified time.
+    pub fn elevation(&self, time: SystemTime) -> f64 {
+        elevation(self.latitude, self.longitude, seconds(time))
+    }
+
+    /// Returns the time of sunrise on the day containing `time`, or `None` if the
+    /// sun doesn't rise or set on that day.
+    ///
+    /// Days start at local solar midnight, around midnight local time.
+    pub fn sunrise(&self, time: SystemTime) -> Option<SystemTime> {
+        self.crossing(time, true)
+    }
+
+    /// Returns the time of sunset on the day containing `time`, or `None` if the
+    /// sun doesn't rise or set on that day.
+    ///
+    /// Days start at local solar midnight, around midnight local time.
+    pub fn sunset(&self, time: SystemTime) -> Option<SystemTime> {
+        self.crossing(time, false)
+    }
+
+    /// Returns the amount of daylight at the specified time, between `0.0`
+    /// (night) and `1.0` (day).
+    pub fn daylight(&self, time: SystemTime) -> f32 {
+        ((self.elevation(time) - TWILIGHT) / (DAYLIGHT - TWILIGHT)).clamp(0.0, 1.0) as f32
+    }
+
+    /// Returns the global brightness for the specified time.
+    pub fn brightness(&self, time: SystemTime) -> f32 {
+        let (day, night) = self.brightness;
+
+        night + (day - night) * self.daylight(time)
+    }
+
+    /// Returns the color temperature in K for the specified time.
+    pub fn color_temperature(&self, time: SystemTime) -> f32 {
+        let (day, night) = self.color_temperature;
+
+        night + (day - night) * self.daylight(time)
+    }
+
+    /// Sets the global brightness of `blinkt`, and the calibration of its color
+    /// correction, based on the position of the sun at the specified time.
+    pub fn apply(&self, blinkt: &mut Blinkt, time: SystemTime) {
+        let (red, green, blue) = temperature_rgb(self.color_temperature(time));
+        let mut correction = blinkt.correction().clone();
+        correction.set_calibration(red, green, blue);
+
+        blinkt.set_correction(correction);
+        blinkt.set_global_brightness(self.brightness(time));
+    }
+
+    // Finds the time the sun rises or sets within the solar day containing `time`.
+    fn crossing(&self, time: SystemTime, rising: bool) -> Option<SystemTime> {
+        let offset = self.longitude / 360.0 * SECONDS_PER_DAY;
+        let start = ((seconds(time) + offset) / SECONDS_PER_DAY).floor() * SECONDS_PER_DAY - offset;
+        let height = |time| elevation(self.latitude, self.longitude, time) - HORIZON;
+
+        // Sample every 10 minutes, and refine the first matching crossing.
+        let step = 600.0;
+        let mut from = start;
+        while from < start + SECONDS_PER_DAY {
+            let to = from + step;
+            let (from_height, to_height) = (height(from), height(to));
+
+            if (rising && from_height < 0.0 && to_height >= 0.0)
+                || (!rising && from_height >= 0.0 && to_height < 0.0)
+            {
+                let (mut low, mut high) = (from, to);
+                for _ in 0..20 {
+                    let middle = (low + high) / 2.0;
+                    if (height(middle) >= 0.0) == rising {
+                        high = middle;
+                    } else {
+                        low = middle;
+                    }
+                }
+
+                return Some(UNIX_EPOCH + Duration::from_secs_f64(high.max(0.0)));
+            }
+
+            from = to;
+        }
+
+        None
+    }
+}
+
+// Returns the number of seconds since the Unix epoch.
+fn seconds(time: SystemTime) -> f64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs_f64(),
+        Err(err) => -err.duration().as_secs_f64(),
+    }
+}
+
+// Calculates the elevation of the sun in degrees, based on the simplified
+// algorithm of the Astronomical Almanac, which is accurate to within a fraction
+// of a degree.
+fn elevation(latitude: f64, longitude: f64, seconds: f64) -> f64 {
+    // Days since J2000.0
+    let days = seconds / SECONDS_PER_DAY - 10_957.5;
+
+    let mean_anomaly = (357.529 + 0.985_600_28 * days).to_radians();
+    let mean_longitude = 280.459 + 0.985_647_36 * days;
+    let ecliptic_longitude =
+        (mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2.0 * mean_anomaly).sin())
+            .to_radians();
+    let obliquity = (23.439 - 0.000_000_36 * days).to_radians();
+
+    let right_ascension =
+        (obliquity.cos() * ecliptic_longitude.sin()).atan2(ecliptic_longitude.cos());
+    let declination = (obliquity.sin() * ecliptic_longitude.sin()).asin();
+
+    let sidereal_time = (280.460_618_37 + 360.985_647_366_29 * days + longitude).to_radians();
+    let hour_angle = sidereal_time - right_ascension;
+    let latitude = latitude.to_radians();
+
+    (latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos())
+        .asin()
+        .to_degrees()
+}
+
+// Converts a color temperature in K to red, green and blue multipliers between
+// 0.0 and 1.0, based on an approximation of the Planckian locus.
+fn temperature_rgb(temperature: f32) -> (f32, f32, f32) {
+    let t = temperature / 100.0;
+
+    let red = if t <= 66.0 {
+        255.0
+    } else {
+        329.698_73 * (t - 60.0).powf(-0.133_204_76)
+    };
+
+    let green = if t <= 66.0 {
+        99.470_8 * t.ln() - 161.119_57
+    } else {
+        288.122_17 * (t - 60.0).powf(-0.075_514_85)
+    };
+
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+
+    (
+        (red / 255.0).clamp(0.0, 1.0),
+        (green / 255.0).clamp(0.0, 1.0),
+        (blue / 255.0).clamp(0.0, 1.0),
+    )
+}
@@ -0,0 +1,281 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::checksum::crc32;
+use crate::{net, Blinkt, Driver, Protocol, Result};
+
+// Frames larger than this are rejected by the receiver, which protects against
+// clients that don't use the expected protocol.
+const MAX_FRAME_LEN: usize = 1024 * 1024;
+// Set in the length of a frame that's followed by a CRC-32 checksum.
+const FLAG_CHECKSUM: u32 = 1 << 31;
+// Clients that don't send anything for this long are disconnected.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A [`Driver`] that streams the encoded frames over TCP to a
+/// [`TcpReceiver`], which forwards them to the pixels.
+///
+/// `TcpOutput` moves the rendering workload to a different machine, while the
+/// Raspberry Pi connected to the LED strip only forwards the received bytes to
+/// its SPI or GPIO output. Every frame is sent as a 32-bit big-endian length,
+/// followed by the frame data.
+///
+/// When checksums are enabled with `set_checksum()`, the highest bit of the
+/// length is set, and the frame data is followed by its 32-bit big-endian
+/// CRC-32 checksum. The receiver drops any frame with an invalid checksum, so
+/// the pixels keep showing the previous frame until the next frame arrives,
+/// instead of showing corrupted data. This is mainly useful when frames pass
+/// through proxies or other software that might alter the data.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// #
+/// # use blinkt::{Blinkt, TcpOutput};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let output = TcpOutput::connect("raspberrypi.local:7890")?;
+/// let mut blinkt = Blinkt::with_driver(output, 144);
+///
+/// blinkt.set_all_pixels(255, 0, 0);
+/// blinkt.show()?;
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`Driver`]: trait.Driver.html
+/// [`TcpReceiver`]: struct.TcpReceiver.html
+#[derive(Debug)]
+pub struct TcpOutput {
+    stream: TcpStream,
+    frame: Vec<u8>,
+    checksum: bool,
+}
+
+impl TcpOutput {
+    /// Constructs a new `TcpOutput` connected to the `TcpReceiver` listening on
+    /// `address`.
+    pub fn connect<A: ToSocketAddrs>(address: A) -> Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        // Frames are sent in a single write, and shouldn't wait for more data.
+        stream.set_nodelay(true)?;
+
+        Ok(Self {
+            stream,
+            frame: Vec::new(),
+            checksum: false,
+        })
+    }
+
+    /// Returns `true` if a CRC-32 checksum is sent after every frame.
+    pub fn checksum(&self) -> bool {
+        self.checksum
+    }
+
+    /// When enabled, a CRC-32 checksum is sent after every frame, so the
+    /// receiver can detect corrupted frames.
+    ///
+    /// By default, this is set to `false`.
+    pub fn set_checksum(&mut self, checksum: bool) {
+        self.checksum = checksum;
+    }
+
+    /// Returns the address of the connected `TcpReceiver`.
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        Ok(self.stream.peer_addr()?)
+    }
+}
+
+impl Driver for TcpOutput {
+    fn begin_frame(&mut self) -> Result<()> {
+        self.frame.clear();
+        self.frame.extend_from_slice(&[0u8; 4]);
+
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.frame.extend_from_slice(data);
+
+        Ok(())
+    }
+
+    fn end_frame(&mut self) -> Result<()> {
+        let mut len = (self.frame.len() - 4) as u32;
+
+        if self.checksum {
+            let crc = crc32(&self.frame[4..]);
+            self.frame.extend_from_slice(&crc.to_be_bytes());
+            len |= FLAG_CHECKSUM;
+        }
+
+        self.frame[..4].copy_from_slice(&len.to_be_bytes());
+
+        Ok(self.stream.write_all(&self.frame)?)
+    }
+}
+
+/// A receiver that forwards frames sent by a [`TcpOutput`] to the pixels.
+///
+/// `TcpReceiver` runs on the Raspberry Pi connected to the LED strip. Received
+/// frames are sent to the first output of a local `Blinkt` as-is, without any
+/// further processing. Settings that are applied while encoding a frame, such
+/// as the brightness, color correction and color order, should be configured
+/// on the `Blinkt` that renders the frames instead.
+///
+/// Only a single client is served at a time. When a client disconnects, the
+/// receiver waits for the next client to connect. Clients that take more than
+/// 30 seconds to send a frame are disconnected. Frames with an invalid checksum
+/// are dropped.
+///
+/// The frames are encoded for APA102 pixels, so the first output of the local
+/// `Blinkt` has to be an APA102 output with a single data line.
+///
+/// Clients aren't authenticated, so any host that can connect to the receiver
+/// can send frames to the pixels. Only bind to an address on a trusted network,
+/// or restrict access with a firewall.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// #
+/// # use blinkt::{Blinkt, BlinktSpi, TcpReceiver};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::with_spi(BlinktSpi::default(), 144);
+/// let receiver = TcpReceiver::bind("0.0.0.0:7890")?;
+///
+/// receiver.run(&mut blinkt)?;
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`TcpOutput`]: struct.TcpOutput.html
+#[derive(Debug)]
+pub struct TcpReceiver {
+    listener: TcpListener,
+}
+
+impl TcpReceiver {
+    /// Constructs a new `TcpReceiver` listening on `address`.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+        })
+    }
+
+    /// Returns the local address the receiver is listening on.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Accepts clients and forwards their frames to `blinkt` until an error
+    /// occurs while sending a frame to the pixels.
+    pub fn run(&self, blinkt: &mut Blinkt) -> Result<()> {
+        loop {
+            self.accept(blinkt)?;
+        }
+    }
+
+    /// Waits for a single client to connect, and forwards its frames to
+    /// `blinkt` until it disconnects. Returns the number of forwarded frames,
+    /// which doesn't include any frames that were dropped because of an invalid
+    /// checksum.
+    ///
+    /// Network errors and invalid frames are treated as a disconnect. Returns
+    /// an error if no client can be accepted, or a frame can't be sent to the
+    /// pixels, which includes a first output that doesn't use APA102 pixels on
+    /// a single data line.
+    pub fn accept(&self, blinkt: &mut Blinkt) -> Result<usize> {
+        let stream = net::accept(&self.listener, READ_TIMEOUT)?;
+
+        let mut frame = Vec::new();
+        let mut frames = 0;
+
+        while let Ok(valid) = read_frame(&stream, &mut frame) {
+            if valid {
+                blinkt.write_raw(&frame)?;
+                frames += 1;
+            }
+        }
+
+        Ok(frames)
+    }
+}
+
+impl Blinkt {
+    // Sends an encoded frame to the first output without any processing.
+    fn write_raw(&mut self, frame: &[u8]) -> Result<()> {
+        // The pixels no longer show the frame sent by show_diff().
+        self.sent_frame.clear();
+
+        let output = match self.outputs.first_mut() {
+            Some(output) => output,
+            None => return Ok(()),
+        };
+
+        if output.serial_output.protocol() != Protocol::Apa102 || output.serial_output.lanes() != 1
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "received frames require a single-lane APA102 output",
+            )
+            .into());
+        }
+
+        output.serial_output.acquire()?;
+        let result = output.serial_output.write(frame);
+        let released = output.serial_output.release();
+
+        result.and(released)
+    }
+}
+
+// Reads the next frame, which has to arrive within READ_TIMEOUT. Returns `false`
+// if the frame's checksum is invalid.
+fn read_frame(stream: &TcpStream, frame: &mut Vec<u8>) -> io::Result<bool> {
+    let mut stream = net::DeadlineReader::new(stream, READ_TIMEOUT);
+
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header)?;
+
+    let header = u32::from_be_bytes(header);
+    let len = (header & !FLAG_CHECKSUM) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame exceeds maximum length",
+        ));
+    }
+
+    frame.resize(len, 0);
+    stream.read_exact(frame)?;
+
+    if header & FLAG_CHECKSUM == 0 {
+        return Ok(true);
+    }
+
+    let mut crc = [0u8; 4];
+    stream.read_exact(&mut crc)?;
+
+    Ok(u32::from_be_bytes(crc) == crc32(frame))
+}
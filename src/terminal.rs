@@ -0,0 +1,108 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::io::{self, Write};
+
+use crate::driver::decode_frame;
+use crate::{ColorOrder, Driver, Result};
+
+/// A [`Driver`] that shows the pixels as colored blocks in the terminal.
+///
+/// Every frame sent by `show()` is decoded and printed on a single line, using
+/// 24-bit ANSI color codes, with the brightness of each pixel applied to its
+/// color. The line is overwritten by the next frame. This makes it possible to
+/// develop animations without watching the actual pixels, for instance over
+/// SSH. The terminal needs to support 24-bit color.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// #
+/// # use blinkt::{Blinkt, TerminalOutput};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::with_driver(TerminalOutput::new(), 8);
+///
+/// blinkt.set_all_pixels(255, 0, 0);
+/// blinkt.show()?;
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`Driver`]: trait.Driver.html
+pub struct TerminalOutput {
+    writer: Box<dyn Write + Send>,
+    frame: Vec<u8>,
+    color_order: ColorOrder,
+}
+
+impl TerminalOutput {
+    /// Constructs a new `TerminalOutput` that prints to the standard output.
+    pub fn new() -> Self {
+        Self::with_writer(Box::new(io::stdout()))
+    }
+
+    /// Constructs a new `TerminalOutput` that prints to `writer`.
+    pub fn with_writer(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer,
+            frame: Vec::new(),
+            color_order: ColorOrder::default(),
+        }
+    }
+}
+
+impl Default for TerminalOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Driver for TerminalOutput {
+    fn begin_frame(&mut self) -> Result<()> {
+        self.frame.clear();
+
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.frame.extend_from_slice(data);
+
+        Ok(())
+    }
+
+    fn end_frame(&mut self) -> Result<()> {
+        let mut line = String::from("\r");
+
+        for (red, green, blue) in decode_frame(&self.frame, self.color_order) {
+            line.push_str(&format!("\x1b[38;2;{};{};{}m██", red, green, blue));
+        }
+
+        line.push_str("\x1b[0m");
+
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+
+    fn set_color_order(&mut self, color_order: ColorOrder) {
+        self.color_order = color_order;
+    }
+}
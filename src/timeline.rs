@@ -0,0 +1,398 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// The easing curve used to transition between two keyframes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Easing {
+    /// Changes at a constant rate.
+    Linear,
+    /// Holds the value of the first keyframe until the next keyframe is reached.
+    Step,
+    /// Starts slowly, and speeds up towards the next keyframe.
+    EaseIn,
+    /// Starts quickly, and slows down towards the next keyframe.
+    EaseOut,
+    /// Starts and ends slowly, and speeds up in between.
+    EaseInOut,
+}
+
+impl Easing {
+    /// Returns the eased progress for `t`.
+    ///
+    /// `t` and the returned value are specified as floating point values between
+    /// `0.0` (start) and `1.0` (end).
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::Step => {
+                if t < 1.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::Linear
+    }
+}
+
+/// The number of times a [`Timeline`] is played.
+///
+/// [`Timeline`]: struct.Timeline.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Playback {
+    /// Plays the timeline once.
+    Once,
+    /// Plays the timeline the specified number of times.
+    Repeat(u32),
+    /// Plays the timeline indefinitely.
+    Loop,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Playback::Once
+    }
+}
+
+// Values that can be interpolated between keyframes.
+trait Interpolate: Copy {
+    fn interpolate(self, to: Self, amount: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(self, to: Self, amount: f32) -> Self {
+        self + (to - self) * amount
+    }
+}
+
+impl Interpolate for (u8, u8, u8) {
+    fn interpolate(self, to: Self, amount: f32) -> Self {
+        let channel = |from: u8, to: u8| {
+            (f32::from(from) + (f32::from(to) - f32::from(from)) * amount).round() as u8
+        };
+
+        (
+            channel(self.0, to.0),
+            channel(self.1, to.1),
+            channel(self.2, to.2),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Keyframe<T> {
+    time: Duration,
+    value: T,
+    easing: Easing,
+}
+
+#[derive(Debug, Clone)]
+struct Track<T> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Interpolate> Track<T> {
+    fn new() -> Self {
+        Self {
+            keyframes: Vec::new(),
+        }
+    }
+
+    fn insert(&mut self, time: Duration, value: T, easing: Easing) {
+        let keyframe = Keyframe {
+            time,
+            value,
+            easing,
+        };
+
+        match self
+            .keyframes
+            .binary_search_by_key(&time, |keyframe| keyframe.time)
+        {
+            Ok(index) => self.keyframes[index] = keyframe,
+            Err(index) => self.keyframes.insert(index, keyframe),
+        }
+    }
+
+    fn value(&self, time: Duration) -> Option<T> {
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time);
+
+        match next {
+            Some(0) => self.keyframes.first().map(|keyframe| keyframe.value),
+            Some(next) => {
+                let from = &self.keyframes[next - 1];
+                let to = &self.keyframes[next];
+                let t = (time - from.time).as_secs_f32() / (to.time - from.time).as_secs_f32();
+
+                Some(from.value.interpolate(to.value, from.easing.apply(t)))
+            }
+            None => self.keyframes.last().map(|keyframe| keyframe.value),
+        }
+    }
+}
+
+/// A sequence of keyframes for colors and other values that change over time.
+///
+/// A `Timeline` consists of named tracks, each with its own keyframes. Color
+/// tracks contain red, green and blue values, and can be used to animate any
+/// number of pixels. Value tracks contain floating point values, and can be
+/// used to animate brightness or any other effect parameter. The easing of
+/// a keyframe determines how the value transitions to the next keyframe.
+/// Before the first keyframe and after the last keyframe of a track, the value
+/// of that keyframe is held.
+///
+/// Timelines are evaluated at a specific time, usually the elapsed time of a
+/// [`FrameClock`]. The [`Playback`] mode determines how many times the
+/// timeline is played before it's finished. When ping-pong is enabled, the
+/// timeline is played forward and then in reverse on each repetition.
+/// `completes()` returns `true` on the frame in which the timeline finishes.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// # use std::time::Duration;
+/// #
+/// # use blinkt::{Blinkt, Easing, FrameClock, Playback, Timeline};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let mut blinkt = Blinkt::new()?;
+/// let mut clock = FrameClock::new();
+/// let mut timeline = Timeline::new(Duration::from_secs(4));
+/// timeline.set_playback(Playback::Loop);
+///
+/// timeline.add_color_keyframe("color", Duration::from_secs(0), (255, 0, 0), Easing::Linear);
+/// timeline.add_color_keyframe("color", Duration::from_secs(2), (0, 0, 255), Easing::Linear);
+/// timeline.add_color_keyframe("color", Duration::from_secs(4), (255, 0, 0), Easing::Linear);
+/// timeline.add_keyframe("brightness", Duration::from_secs(0), 0.1, Easing::EaseInOut);
+/// timeline.add_keyframe("brightness", Duration::from_secs(2), 1.0, Easing::EaseInOut);
+/// timeline.add_keyframe("brightness", Duration::from_secs(4), 0.1, Easing::EaseInOut);
+///
+/// loop {
+///     clock.tick();
+///
+///     let (red, green, blue) = timeline.color("color", clock.elapsed()).unwrap_or_default();
+///     let brightness = timeline.value("brightness", clock.elapsed()).unwrap_or(1.0);
+///
+///     blinkt.set_all_pixels_rgbb(red, green, blue, brightness);
+///     blinkt.show()?;
+/// #   break;
+/// }
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`FrameClock`]: struct.FrameClock.html
+/// [`Playback`]: enum.Playback.html
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    length: Duration,
+    playback: Playback,
+    ping_pong: bool,
+    values: HashMap<String, Track<f32>>,
+    colors: HashMap<String, Track<(u8, u8, u8)>>,
+}
+
+impl Timeline {
+    /// Constructs a new, empty `Timeline` with the specified length.
+    pub fn new(length: Duration) -> Self {
+        Self {
+            length,
+            playback: Playback::Once,
+            ping_pong: false,
+            values: HashMap::new(),
+            colors: HashMap::new(),
+        }
+    }
+
+    /// Returns the length.
+    pub fn length(&self) -> Duration {
+        self.length
+    }
+
+    /// Sets the length.
+    pub fn set_length(&mut self, length: Duration) {
+        self.length = length;
+    }
+
+    /// Returns the playback mode.
+    pub fn playback(&self) -> Playback {
+        self.playback
+    }
+
+    /// Sets the number of times the timeline is played.
+    ///
+    /// By default, this is set to `Playback::Once`.
+    pub fn set_playback(&mut self, playback: Playback) {
+        self.playback = playback;
+    }
+
+    /// Returns `true` if ping-pong is enabled.
+    pub fn is_ping_pong(&self) -> bool {
+        self.ping_pong
+    }
+
+    /// When enabled, the timeline is played forward and then in reverse on each
+    /// repetition, which doubles the duration of a single repetition.
+    ///
+    /// By default, this is set to `false`.
+    pub fn set_ping_pong(&mut self, ping_pong: bool) {
+        self.ping_pong = ping_pong;
+    }
+
+    /// Returns the total playback duration, taking the playback mode and
+    /// ping-pong into account, or `None` if the timeline is played indefinitely.
+    pub fn duration(&self) -> Option<Duration> {
+        let repetitions = match self.playback {
+            Playback::Once => 1,
+            Playback::Repeat(repetitions) => repetitions,
+            Playback::Loop => return None,
+        };
+
+        self.cycle().checked_mul(repetitions)
+    }
+
+    /// Adds a keyframe to the value track named `track`, creating the track if
+    /// it doesn't exist yet.
+    ///
+    /// `easing` determines how `value` transitions to the next keyframe. An
+    /// existing keyframe at the same `time` is replaced.
+    pub fn add_keyframe(&mut self, track: &str, time: Duration, value: f32, easing: Easing) {
+        self.values
+            .entry(track.to_owned())
+            .or_insert_with(Track::new)
+            .insert(time, value, easing);
+    }
+
+    /// Adds a keyframe to the color track named `track`, creating the track if it
+    /// doesn't exist yet.
+    ///
+    /// `color` contains the red, green and blue values, specified as 8-bit values
+    /// between `0` (0%) and `255` (100%). `easing` determines how `color`
+    /// transitions to the next keyframe. An existing keyframe at the same `time`
+    /// is replaced.
+    pub fn add_color_keyframe(
+        &mut self,
+        track: &str,
+        time: Duration,
+        color: (u8, u8, u8),
+        easing: Easing,
+    ) {
+        self.colors
+            .entry(track.to_owned())
+            .or_insert_with(Track::new)
+            .insert(time, color, easing);
+    }
+
+    /// Removes the value track or color track named `track`. Returns `true` if
+    /// a track was removed.
+    pub fn remove_track(&mut self, track: &str) -> bool {
+        let value = self.values.remove(track).is_some();
+        let color = self.colors.remove(track).is_some();
+
+        value || color
+    }
+
+    /// Returns the position within the timeline for the specified elapsed time,
+    /// taking the playback mode and ping-pong into account.
+    ///
+    /// Once the timeline is finished, the position is held at the end, or at the
+    /// start when ping-pong is enabled.
+    pub fn position(&self, elapsed: Duration) -> Duration {
+        if self.length == Duration::default() {
+            return self.length;
+        }
+
+        if self.is_finished(elapsed) {
+            return if self.ping_pong {
+                Duration::default()
+            } else {
+                self.length
+            };
+        }
+
+        let length = self.length.as_nanos();
+        let position = elapsed.as_nanos() % self.cycle().as_nanos();
+
+        if position > length {
+            Duration::from_nanos((2 * length - position) as u64)
+        } else {
+            Duration::from_nanos(position as u64)
+        }
+    }
+
+    /// Returns `true` if the timeline is finished at the specified elapsed time.
+    /// A timeline that's played indefinitely never finishes.
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        self.duration()
+            .map_or(false, |duration| elapsed >= duration)
+    }
+
+    /// Returns `true` if the timeline finishes within the frame that ends at
+    /// `elapsed` and lasted `delta`, usually the elapsed time and delta time of
+    /// a [`FrameClock`].
+    ///
+    /// This can be used to trigger an action once the timeline is complete,
+    /// without having to keep track of whether it was finished before.
+    ///
+    /// [`FrameClock`]: struct.FrameClock.html
+    pub fn completes(&self, elapsed: Duration, delta: Duration) -> bool {
+        self.duration().map_or(false, |duration| {
+            elapsed >= duration && elapsed.saturating_sub(delta) < duration
+        })
+    }
+
+    // Returns the duration of a single repetition.
+    fn cycle(&self) -> Duration {
+        if self.ping_pong {
+            self.length * 2
+        } else {
+            self.length
+        }
+    }
+
+    /// Returns the value of the value track named `track` at the specified elapsed
+    /// time, or `None` if the track doesn't exist or doesn't contain any keyframes.
+    pub fn value(&self, track: &str, elapsed: Duration) -> Option<f32> {
+        self.values.get(track)?.value(self.position(elapsed))
+    }
+
+    /// Returns the red, green and blue values of the color track named `track` at
+    /// the specified elapsed time, or `None` if the track doesn't exist or doesn't
+    /// contain any keyframes.
+    pub fn color(&self, track: &str, elapsed: Duration) -> Option<(u8, u8, u8)> {
+        self.colors.get(track)?.value(self.position(elapsed))
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::Pixel;
+
+/// Tone mapping for colors captured from screens or video sources.
+///
+/// Colors taken directly from a screen or video tend to look washed out on
+/// LEDs. A `ToneMap` clips dark values to black, and boosts saturation and
+/// contrast. Each source of frames can use its own `ToneMap` with settings
+/// tuned for that source.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// #
+/// # use blinkt::{Blinkt, ToneMap};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #     let mut blinkt = Blinkt::new()?;
+/// let tone_map = ToneMap::screen_capture();
+///
+/// // Update the pixels with captured colors, and then apply the tone mapping.
+/// tone_map.apply(&mut blinkt);
+/// blinkt.show()?;
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ToneMap {
+    contrast: f32,
+    saturation: f32,
+    black_level: f32,
+}
+
+impl ToneMap {
+    /// Constructs a new `ToneMap` that leaves all colors unchanged.
+    pub fn new() -> Self {
+        Self {
+            contrast: 1.0,
+            saturation: 1.0,
+            black_level: 0.0,
+        }
+    }
+
+    /// Constructs a new `ToneMap` with settings suitable for most screen capture
+    /// and video sources.
+    pub fn screen_capture() -> Self {
+        Self {
+            contrast: 1.2,
+            saturation: 1.4,
+            black_level: 0.05,
+        }
+    }
+
+    /// Returns the contrast value.
+    pub fn contrast(&self) -> f32 {
+        self.contrast
+    }
+
+    /// Sets the contrast value.
+    ///
+    /// A `contrast` of `1.0` leaves the colors unchanged. Higher values increase
+    /// the contrast, and lower values decrease it.
+    pub fn set_contrast(&mut self, contrast: f32) {
+        self.contrast = contrast.max(0.0);
+    }
+
+    /// Returns the saturation value.
+    pub fn saturation(&self) -> f32 {
+        self.saturation
+    }
+
+    /// Sets the saturation value.
+    ///
+    /// A `saturation` of `1.0` leaves the colors unchanged, and `0.0` turns them
+    /// into shades of gray. Higher values make colors more vivid.
+    pub fn set_saturation(&mut self, saturation: f32) {
+        self.saturation = saturation.max(0.0);
+    }
+
+    /// Returns the black level.
+    pub fn black_level(&self) -> f32 {
+        self.black_level
+    }
+
+    /// Sets the black level. Color values below the black level are clipped to
+    /// `0`, and the remaining range is stretched to cover all values.
+    ///
+    /// `black_level` is specified as a floating point value between `0.0` (0%) and `1.0` (100%).
+    pub fn set_black_level(&mut self, black_level: f32) {
+        self.black_level = black_level.clamp(0.0, 0.99);
+    }
+
+    /// Applies the tone mapping to all specified pixels.
+    ///
+    /// `pixels` accepts anything that iterates over mutable `Pixel` references,
+    /// like `&mut Blinkt` or `&mut [Pixel]`.
+    pub fn apply<'a, I>(&self, pixels: I)
+    where
+        I: IntoIterator<Item = &'a mut Pixel>,
+    {
+        for pixel in pixels {
+            self.apply_pixel(pixel);
+        }
+    }
+
+    /// Applies the tone mapping to a single pixel.
+    pub fn apply_pixel(&self, pixel: &mut Pixel) {
+        let (red, green, blue) = pixel.rgb();
+        let mut rgb = [red, green, blue].map(|value| {
+            ((f32::from(value) / 255.0 - self.black_level) / (1.0 - self.black_level)).max(0.0)
+        });
+
+        let luma = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+        for value in &mut rgb {
+            let saturated = luma + (*value - luma) * self.saturation;
+            *value = ((saturated - 0.5) * self.contrast + 0.5).clamp(0.0, 1.0);
+        }
+
+        pixel.set_rgb(
+            (rgb[0] * 255.0).round() as u8,
+            (rgb[1] * 255.0).round() as u8,
+            (rgb[2] * 255.0).round() as u8,
+        );
+    }
+}
+
+impl Default for ToneMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::slice;
+
+use crate::Pixel;
+
+/// A set of pixel changes that are applied to the local buffer all at once.
+///
+/// A `Transaction` is created by [`Blinkt::transaction()`], and works on a copy
+/// of the local buffer. When the closure passed to `transaction()` returns,
+/// all changes are committed at the same time, unless `rollback()` was called.
+///
+/// [`Blinkt::transaction()`]: struct.Blinkt.html#method.transaction
+#[derive(Debug)]
+pub struct Transaction {
+    pixels: Vec<Pixel>,
+    show: bool,
+    rollback: bool,
+}
+
+impl Transaction {
+    pub(crate) fn new(pixels: &[Pixel]) -> Self {
+        Self {
+            pixels: pixels.to_vec(),
+            show: false,
+            rollback: false,
+        }
+    }
+
+    /// Returns a mutable iterator over all staged `Pixel`s.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, Pixel> {
+        self.pixels.iter_mut()
+    }
+
+    /// Sets the red, green and blue values for a single pixel.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    pub fn set_pixel(&mut self, pixel: usize, red: u8, green: u8, blue: u8) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_rgb(red, green, blue);
+        }
+    }
+
+    /// Sets the red, green, blue and brightness values for a single pixel.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    /// `brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%), and is converted to a 5-bit value.
+    pub fn set_pixel_rgbb(&mut self, pixel: usize, red: u8, green: u8, blue: u8, brightness: f32) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_rgbb(red, green, blue, brightness);
+        }
+    }
+
+    /// Sets the red, green, blue and white values for a single pixel.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `red`, `green`, `blue` and `white` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    /// The white value is only sent to RGBW pixels, such as the SK6812 RGBW.
+    pub fn set_pixel_rgbw(&mut self, pixel: usize, red: u8, green: u8, blue: u8, white: u8) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_rgbw(red, green, blue, white);
+        }
+    }
+
+    /// Sets the 16-bit red, green and blue values for a single pixel.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `red`, `green` and `blue` are specified as 16-bit values between `0` (0%) and `65535` (100%).
+    /// The full 16-bit values are only sent to pixels that support them, such as the
+    /// HD108. Other pixels receive the 8 most significant bits.
+    pub fn set_pixel_u16(&mut self, pixel: usize, red: u16, green: u16, blue: u16) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_rgb16(red, green, blue);
+        }
+    }
+
+    /// Sets the red, green and blue values for a single pixel, based on hue,
+    /// saturation and value (HSV).
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `hue` is specified in degrees, and wraps around at `360.0`. `saturation` and
+    /// `value` are specified as floating point values between `0.0` (0%) and `1.0` (100%).
+    pub fn set_pixel_hsv(&mut self, pixel: usize, hue: f32, saturation: f32, value: f32) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_hsv(hue, saturation, value);
+        }
+    }
+
+    /// Sets the brightness value for a single pixel.
+    ///
+    /// Pixels are numbered starting at `0`.
+    /// `brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%), and is converted to a 5-bit value.
+    pub fn set_pixel_brightness(&mut self, pixel: usize, brightness: f32) {
+        if let Some(pixel) = self.pixels.get_mut(pixel) {
+            pixel.set_brightness(brightness);
+        }
+    }
+
+    /// Sets the red, green and blue values for all pixels.
+    ///
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    pub fn fill(&mut self, red: u8, green: u8, blue: u8) {
+        for pixel in &mut self.pixels {
+            pixel.set_rgb(red, green, blue);
+        }
+    }
+
+    /// Sets the red, green, blue and brightness values for all pixels.
+    ///
+    /// `red`, `green` and `blue` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    /// `brightness` is specified as a floating point value between `0.0` (0%) and `1.0` (100%), and is converted to a 5-bit value.
+    pub fn fill_rgbb(&mut self, red: u8, green: u8, blue: u8, brightness: f32) {
+        for pixel in &mut self.pixels {
+            pixel.set_rgbb(red, green, blue, brightness);
+        }
+    }
+
+    /// Sets the red, green, blue and white values for all pixels.
+    ///
+    /// `red`, `green`, `blue` and `white` are specified as 8-bit values between `0` (0%) and `255` (100%).
+    /// The white value is only sent to RGBW pixels, such as the SK6812 RGBW.
+    pub fn fill_rgbw(&mut self, red: u8, green: u8, blue: u8, white: u8) {
+        for pixel in &mut self.pixels {
+            pixel.set_rgbw(red, green, blue, white);
+        }
+    }
+
+    /// Sets the 16-bit red, green and blue values for all pixels.
+    ///
+    /// `red`, `green` and `blue` are specified as 16-bit values between `0` (0%) and `65535` (100%).
+    /// The full 16-bit values are only sent to pixels that support them, such as the
+    /// HD108. Other pixels receive the 8 most significant bits.
+    pub fn fill_u16(&mut self, red: u16, green: u16, blue: u16) {
+        for pixel in &mut self.pixels {
+            pixel.set_rgb16(red, green, blue);
+        }
+    }
+
+    /// Sets the red, green and blue values for all pixels, based on hue,
+    /// saturation and value (HSV).
+    ///
+    /// `hue` is specified in degrees, and wraps around at `360.0`. `saturation` and
+    /// `value` are specified as floating point values between `0.0` (0%) and `1.0` (100%).
+    pub fn fill_hsv(&mut self, hue: f32, saturation: f32, value: f32) {
+        for pixel in &mut self.pixels {
+            pixel.set_hsv(hue, saturation, value);
+        }
+    }
+
+    /// Sets the red, green and blue values for all pixels to `0`.
+    pub fn clear(&mut self) {
+        self.fill(0, 0, 0);
+    }
+
+    /// Sends the local buffer to the pixels once the changes are committed.
+    pub fn show(&mut self) {
+        self.show = true;
+    }
+
+    /// Discards all changes made during this transaction.
+    pub fn rollback(&mut self) {
+        self.rollback = true;
+    }
+
+    // Returns the staged pixels if the changes should be committed, and whether
+    // show() was requested.
+    pub(crate) fn into_parts(self) -> (Option<Vec<Pixel>>, bool) {
+        if self.rollback {
+            (None, false)
+        } else {
+            (Some(self.pixels), self.show)
+        }
+    }
+}
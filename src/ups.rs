@@ -0,0 +1,343 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Battery status of Raspberry Pi UPS HATs, read from common I2C fuel gauges,
+//! and an effect that shows the charge level and charging state.
+//!
+//! [`Max17040`] reads the state of charge calculated by a MAX17040 or
+//! MAX17048 fuel gauge. [`Ina219`] estimates the charge level based on the
+//! battery voltage, and the charging state based on the direction of the
+//! current measured by an INA219 current sensor.
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! # use std::thread;
+//! # use std::time::Duration;
+//! #
+//! # use blinkt::{Blinkt, FrameClock};
+//! use blinkt::ups::{BatteryEffect, FuelGauge, Ina219};
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let mut blinkt = Blinkt::new()?;
+//! let mut clock = FrameClock::new();
+//! // Two 18650 cells in series.
+//! let mut gauge = Ina219::new(1, 0x42, 6.0, 8.4)?;
+//! let mut effect = BatteryEffect::new();
+//!
+//! loop {
+//!     clock.tick();
+//!
+//!     effect.set_status(gauge.read()?);
+//!     blinkt.render(&mut effect, clock.elapsed());
+//!     blinkt.show()?;
+//!
+//!     thread::sleep(Duration::from_millis(50));
+//! }
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use rppal::i2c::I2c;
+
+use crate::effect::{Effect, Parameter, Value};
+use crate::{Pixel, Result};
+
+// MAX17040 registers
+const MAX17040_ADDRESS: u16 = 0x36;
+const MAX17040_REG_VCELL: u8 = 0x02;
+const MAX17040_REG_SOC: u8 = 0x04;
+
+// INA219 registers
+const INA219_REG_SHUNT_VOLTAGE: u8 = 0x01;
+const INA219_REG_BUS_VOLTAGE: u8 = 0x02;
+
+/// Whether the battery is charging or discharging.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChargeState {
+    /// The battery is charging.
+    Charging,
+    /// The battery is powering the load.
+    Discharging,
+    /// The charging state can't be determined.
+    Unknown,
+}
+
+/// The status of a battery, as reported by a [`FuelGauge`].
+///
+/// [`FuelGauge`]: trait.FuelGauge.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BatteryStatus {
+    level: f32,
+    voltage: f32,
+    state: ChargeState,
+}
+
+impl BatteryStatus {
+    /// Constructs a new `BatteryStatus`.
+    ///
+    /// `level` is specified as a floating point value between `0.0` (empty) and
+    /// `1.0` (full). `voltage` is specified in V.
+    pub fn new(level: f32, voltage: f32, state: ChargeState) -> Self {
+        Self {
+            level: level.clamp(0.0, 1.0),
+            voltage,
+            state,
+        }
+    }
+
+    /// Returns the charge level between `0.0` (empty) and `1.0` (full).
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Returns the battery voltage in V.
+    pub fn voltage(&self) -> f32 {
+        self.voltage
+    }
+
+    /// Returns the charging state.
+    pub fn state(&self) -> ChargeState {
+        self.state
+    }
+}
+
+impl Default for BatteryStatus {
+    fn default() -> Self {
+        Self::new(0.0, 0.0, ChargeState::Unknown)
+    }
+}
+
+/// A sensor that reports the status of a battery.
+pub trait FuelGauge {
+    /// Reads the current battery status.
+    fn read(&mut self) -> Result<BatteryStatus>;
+}
+
+/// A MAX17040 or MAX17048 fuel gauge.
+///
+/// These fuel gauges don't measure the current, so the charging state is based
+/// on whether the state of charge increased or decreased since the previous
+/// reading.
+pub struct Max17040 {
+    i2c: I2c,
+    previous: Option<f32>,
+    state: ChargeState,
+}
+
+impl Max17040 {
+    /// Constructs a new `Max17040` on the specified I2C bus, using the default
+    /// slave address (`0x36`).
+    pub fn new(bus: u8) -> Result<Self> {
+        let mut i2c = I2c::with_bus(bus)?;
+        i2c.set_slave_address(MAX17040_ADDRESS)?;
+
+        Ok(Self {
+            i2c,
+            previous: None,
+            state: ChargeState::Unknown,
+        })
+    }
+}
+
+impl FuelGauge for Max17040 {
+    fn read(&mut self) -> Result<BatteryStatus> {
+        let soc = read_register(&self.i2c, MAX17040_REG_SOC)?;
+        let vcell = read_register(&self.i2c, MAX17040_REG_VCELL)?;
+
+        // The state of charge is stored in units of 1/256%, and the cell voltage
+        // in the upper 12 bits, in units of 1.25 mV.
+        let level = f32::from(soc) / 256.0 / 100.0;
+        let voltage = f32::from(vcell >> 4) * 0.00125;
+
+        match self.previous {
+            Some(previous) if level > previous => self.state = ChargeState::Charging,
+            Some(previous) if level < previous => self.state = ChargeState::Discharging,
+            _ => (),
+        }
+        self.previous = Some(level);
+
+        Ok(BatteryStatus::new(level, voltage, self.state))
+    }
+}
+
+/// An INA219 current sensor, as used on several UPS HATs.
+///
+/// The charge level is estimated linearly between the empty and full battery
+/// voltage. The charging state is based on the direction of the current through
+/// the shunt resistor, where a positive current means the battery is
+/// discharging. Depending on how the sensor is wired, the direction might need
+/// to be inverted with `set_inverted()`.
+pub struct Ina219 {
+    i2c: I2c,
+    empty_voltage: f32,
+    full_voltage: f32,
+    inverted: bool,
+}
+
+impl Ina219 {
+    /// Constructs a new `Ina219` on the specified I2C bus and slave address.
+    ///
+    /// `empty_voltage` and `full_voltage` set the battery voltage in V at 0% and
+    /// 100% charge. For a single lithium-ion cell, these are around `3.0` and
+    /// `4.2`.
+    pub fn new(bus: u8, address: u16, empty_voltage: f32, full_voltage: f32) -> Result<Self> {
+        let mut i2c = I2c::with_bus(bus)?;
+        i2c.set_slave_address(address)?;
+
+        Ok(Self {
+            i2c,
+            empty_voltage,
+            full_voltage,
+            inverted: false,
+        })
+    }
+
+    /// Returns `true` if the direction of the current is inverted.
+    pub fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+
+    /// When enabled, a positive current means the battery is charging.
+    ///
+    /// By default, this is set to `false`.
+    pub fn set_inverted(&mut self, inverted: bool) {
+        self.inverted = inverted;
+    }
+}
+
+impl FuelGauge for Ina219 {
+    fn read(&mut self) -> Result<BatteryStatus> {
+        let shunt = read_register(&self.i2c, INA219_REG_SHUNT_VOLTAGE)? as i16;
+        let bus = read_register(&self.i2c, INA219_REG_BUS_VOLTAGE)?;
+
+        // The bus voltage is stored in the upper 13 bits, in units of 4 mV.
+        let voltage = f32::from(bus >> 3) * 0.004;
+        let level = (voltage - self.empty_voltage) / (self.full_voltage - self.empty_voltage);
+
+        let state = match (shunt.signum(), self.inverted) {
+            (0, _) => ChargeState::Unknown,
+            (1, false) | (-1, true) => ChargeState::Discharging,
+            _ => ChargeState::Charging,
+        };
+
+        Ok(BatteryStatus::new(level, voltage, state))
+    }
+}
+
+// Reads a 16-bit big-endian register.
+fn read_register(i2c: &I2c, register: u8) -> Result<u16> {
+    let mut buffer = [0u8; 2];
+    i2c.write_read(&[register], &mut buffer)?;
+
+    Ok(u16::from_be_bytes(buffer))
+}
+
+/// Shows the charge level of a battery as a bar graph.
+///
+/// The bar is green above 50%, yellow above the low level and red below.
+/// While charging, a brighter pixel runs along the bar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatteryEffect {
+    status: BatteryStatus,
+    low_level: f32,
+    brightness: f32,
+}
+
+impl BatteryEffect {
+    /// Constructs a new `BatteryEffect`, with the low level set to 20%.
+    pub fn new() -> Self {
+        Self {
+            status: BatteryStatus::default(),
+            low_level: 0.2,
+            brightness: 0.5,
+        }
+    }
+
+    /// Returns the battery status that's shown.
+    pub fn status(&self) -> BatteryStatus {
+        self.status
+    }
+
+    /// Sets the battery status that's shown.
+    pub fn set_status(&mut self, status: BatteryStatus) {
+        self.status = status;
+    }
+}
+
+impl Default for BatteryEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for BatteryEffect {
+    fn name(&self) -> &str {
+        "battery"
+    }
+
+    fn render(&mut self, elapsed: Duration, pixels: &mut [Pixel]) {
+        let level = self.status.level();
+        let (red, green, blue) = if level < self.low_level {
+            (255, 0, 0)
+        } else if level < 0.5 {
+            (255, 160, 0)
+        } else {
+            (0, 255, 0)
+        };
+
+        // Always light up at least one pixel, so an empty battery is visible.
+        let lit = ((level * pixels.len() as f32).ceil() as usize).clamp(1, pixels.len().max(1));
+        let runner = if self.status.state() == ChargeState::Charging {
+            Some((elapsed.as_millis() / 250) as usize % lit)
+        } else {
+            None
+        };
+
+        for (index, pixel) in pixels.iter_mut().enumerate() {
+            if index >= lit {
+                pixel.clear();
+            } else if Some(index) == runner {
+                pixel.set_rgbb(red, green, blue, 1.0);
+            } else {
+                pixel.set_rgbb(red, green, blue, self.brightness);
+            }
+        }
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![
+            Parameter::float("low_level", self.low_level, 0.0, 1.0),
+            Parameter::float("brightness", self.brightness, 0.0, 1.0),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: Value) -> bool {
+        match (name, value) {
+            ("low_level", Value::Float(level)) => self.low_level = level.clamp(0.0, 1.0),
+            ("brightness", Value::Float(brightness)) => {
+                self.brightness = brightness.clamp(0.0, 1.0)
+            }
+            _ => return false,
+        }
+
+        true
+    }
+}
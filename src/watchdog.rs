@@ -0,0 +1,196 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::effect::{Breathe, Effect, Value};
+use crate::Blinkt;
+
+// Time between frames sent by the background thread.
+const FRAME_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The way the [`Watchdog`] indicates that the application stopped sending
+/// heartbeats.
+///
+/// [`Watchdog`]: struct.Watchdog.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WatchdogAction {
+    /// Breathes faster in the specified red, green and blue color.
+    Color(u8, u8, u8),
+    /// Stops breathing, and turns off all pixels.
+    Stop,
+}
+
+struct Shared {
+    heartbeat: Mutex<Instant>,
+    running: AtomicBool,
+}
+
+/// A breathing indicator that shows whether the application is still running.
+///
+/// `Watchdog` takes ownership of a `Blinkt`, and runs a breathing effect in a
+/// background thread. As long as the application calls `heartbeat()` within
+/// the timeout, the pixels breathe slowly in the specified color. Once the
+/// timeout expires, the [`WatchdogAction`] is applied until the next
+/// heartbeat, which gives a physical indication that the application hung.
+///
+/// Errors returned by `show()` in the background thread are ignored.
+///
+/// ```rust,no_run
+/// # use std::error::Error;
+/// # use std::time::Duration;
+/// #
+/// # use blinkt::{Blinkt, Watchdog, WatchdogAction};
+/// #
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let blinkt = Blinkt::new()?;
+/// let watchdog = Watchdog::spawn(
+///     blinkt,
+///     Duration::from_secs(5),
+///     (0, 255, 0),
+///     WatchdogAction::Color(255, 0, 0),
+/// );
+///
+/// loop {
+///     // Do some work, and report that the application is still running.
+///     watchdog.heartbeat();
+/// #   break;
+/// }
+///
+/// let blinkt = watchdog.stop();
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`WatchdogAction`]: enum.WatchdogAction.html
+pub struct Watchdog {
+    shared: Arc<Shared>,
+    timeout: Duration,
+    thread: Option<JoinHandle<Blinkt>>,
+}
+
+impl Watchdog {
+    /// Starts a background thread that breathes in `color` while heartbeats are
+    /// received within `timeout`, and applies `action` otherwise.
+    ///
+    /// `color` contains the red, green and blue values, specified as 8-bit values
+    /// between `0` (0%) and `255` (100%).
+    pub fn spawn(
+        mut blinkt: Blinkt,
+        timeout: Duration,
+        color: (u8, u8, u8),
+        action: WatchdogAction,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            heartbeat: Mutex::new(Instant::now()),
+            running: AtomicBool::new(true),
+        });
+
+        let thread_shared = shared.clone();
+        let thread = thread::spawn(move || {
+            let started = Instant::now();
+            let (red, green, blue) = color;
+            let mut breathe = Breathe::new(red, green, blue);
+            let mut alive = true;
+
+            while thread_shared.running.load(Ordering::SeqCst) {
+                let was_alive = alive;
+                alive = thread_shared.elapsed() < timeout;
+
+                if alive != was_alive {
+                    let (color, period) = match (alive, action) {
+                        (false, WatchdogAction::Color(red, green, blue)) => {
+                            (Value::Color(red, green, blue), 0.5)
+                        }
+                        _ => (Value::Color(color.0, color.1, color.2), 4.0),
+                    };
+
+                    breathe.set_parameter("color", color);
+                    breathe.set_parameter("period", Value::Float(period));
+                }
+
+                if alive || action != WatchdogAction::Stop {
+                    blinkt.render(&mut breathe, started.elapsed());
+                } else {
+                    blinkt.clear();
+                }
+
+                let _ = blinkt.show();
+                thread::sleep(FRAME_INTERVAL);
+            }
+
+            blinkt
+        });
+
+        Self {
+            shared,
+            timeout,
+            thread: Some(thread),
+        }
+    }
+
+    /// Reports that the application is still running.
+    pub fn heartbeat(&self) {
+        *self.shared.lock() = Instant::now();
+    }
+
+    /// Returns `true` if the last heartbeat was received within the timeout.
+    pub fn is_alive(&self) -> bool {
+        self.shared.elapsed() < self.timeout
+    }
+
+    /// Stops the background thread, and returns the `Blinkt`.
+    pub fn stop(mut self) -> Blinkt {
+        self.join().expect("watchdog thread is only joined once")
+    }
+
+    fn join(&mut self) -> Option<Blinkt> {
+        self.shared.running.store(false, Ordering::SeqCst);
+
+        match self.thread.take()?.join() {
+            Ok(blinkt) => Some(blinkt),
+            Err(err) => panic::resume_unwind(err),
+        }
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.join();
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Instant> {
+        self.heartbeat
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.lock().elapsed()
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Time-based waveforms for animations.
+//!
+//! Each waveform takes the elapsed time, usually provided by
+//! [`FrameClock::elapsed()`], a `frequency` in Hz, and a `phase` offset
+//! specified as a fraction of a cycle between `0.0` and `1.0`. All waveforms
+//! return a value between `0.0` and `1.0`, and start at `0.0` when both the
+//! elapsed time and `phase` are zero.
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! #
+//! # use blinkt::{Blinkt, FrameClock, wave};
+//! #
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! #     let mut blinkt = Blinkt::new()?;
+//! let mut clock = FrameClock::new();
+//!
+//! loop {
+//!     clock.tick();
+//!
+//!     // Breathe at a rate of one breath every four seconds.
+//!     blinkt.set_all_pixels_rgbb(0, 0, 255, wave::sine(clock.elapsed(), 0.25, 0.0));
+//!     blinkt.show()?;
+//! }
+//! # }
+//! ```
+//!
+//! [`FrameClock::elapsed()`]: ../struct.FrameClock.html#method.elapsed
+
+use std::f32::consts::PI;
+use std::time::Duration;
+
+// Returns the position within the current cycle, between 0.0 and 1.0.
+#[inline]
+fn cycle(elapsed: Duration, frequency: f32, phase: f32) -> f32 {
+    let position = (elapsed.as_secs_f64() * f64::from(frequency) + f64::from(phase)).fract() as f32;
+
+    if position < 0.0 {
+        position + 1.0
+    } else {
+        position
+    }
+}
+
+/// Returns a smooth sine wave, which is useful for breathing effects.
+pub fn sine(elapsed: Duration, frequency: f32, phase: f32) -> f32 {
+    0.5 - 0.5 * (2.0 * PI * cycle(elapsed, frequency, phase)).cos()
+}
+
+/// Returns a triangle wave, rising linearly to `1.0` halfway through each
+/// cycle, and falling back to `0.0` at the end.
+pub fn triangle(elapsed: Duration, frequency: f32, phase: f32) -> f32 {
+    let position = cycle(elapsed, frequency, phase);
+
+    if position < 0.5 {
+        position * 2.0
+    } else {
+        2.0 - position * 2.0
+    }
+}
+
+/// Returns a sawtooth wave, rising linearly to `1.0` over each cycle before
+/// dropping back to `0.0`.
+pub fn sawtooth(elapsed: Duration, frequency: f32, phase: f32) -> f32 {
+    cycle(elapsed, frequency, phase)
+}
+
+/// Returns a pulse wave, which is `1.0` for the first part of each cycle, and
+/// `0.0` for the remainder.
+///
+/// `duty_cycle` sets the fraction of each cycle the pulse is high, specified as
+/// a floating point value between `0.0` (0%) and `1.0` (100%).
+pub fn pulse(elapsed: Duration, frequency: f32, phase: f32, duty_cycle: f32) -> f32 {
+    if cycle(elapsed, frequency, phase) < duty_cycle.clamp(0.0, 1.0) {
+        1.0
+    } else {
+        0.0
+    }
+}
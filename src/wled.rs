@@ -0,0 +1,802 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! An HTTP server implementing a subset of the WLED JSON API, to control the
+//! pixels from WLED apps, Home Assistant and other WLED integrations.
+//!
+//! [`Server`] answers the following requests, one client at a time. Every
+//! response closes the connection, and clients that take more than 5 seconds
+//! to send their request are disconnected without a response.
+//!
+//! | Request             | Response                                   |
+//! |---------------------|--------------------------------------------|
+//! | `GET /json`         | The current state and device information   |
+//! | `GET /json/state`   | The current state                          |
+//! | `GET /json/info`    | Device information                         |
+//! | `POST /json/state`  | Applies the state, and calls `show()`      |
+//!
+//! The state supports the `on` (including `"t"` to toggle) and `bri` keys, and
+//! a `seg` array, or a single `seg` object, with the `id`, `start`, `stop`,
+//! `on`, `bri` and `col` keys of every segment. Only the primary color of a
+//! segment is used, specified as an array of red, green and blue values, or a
+//! hex string. Segments that don't exist yet are added. Pixels outside of any
+//! segment are turned off. Effects, palettes, presets and other keys are
+//! ignored. `POST /json` is accepted as an alias for `POST /json/state`.
+//!
+//! Requests aren't authenticated, and there's no rate limit, so any host that
+//! can reach the server can change the state. Only bind to an address on a
+//! trusted network, or put the server behind a reverse proxy that handles
+//! authentication.
+//!
+//! ```rust,no_run
+//! # use std::error::Error;
+//! #
+//! # use blinkt::{Blinkt, BlinktSpi};
+//! use blinkt::wled::Server;
+//!
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! let mut blinkt = Blinkt::with_spi(BlinktSpi::default(), 60);
+//! let mut server = Server::bind("0.0.0.0:80")?;
+//!
+//! server.run(&mut blinkt)?;
+//! #     Ok(())
+//! # }
+//! ```
+//!
+//! [`Server`]: struct.Server.html
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::{color, net, Blinkt, Result};
+
+// Version of the WLED JSON API reported by /json/info.
+const WLED_VERSION: &str = "0.14.0";
+// Maximum number of segments reported by /json/info.
+const MAX_SEGMENTS: usize = 16;
+const MAX_BODY_LEN: usize = 64 * 1024;
+// Maximum combined length of the request line and headers.
+const MAX_HEADER_LEN: u64 = 8 * 1024;
+// Maximum nesting depth of JSON arrays and objects, which limits the recursion
+// depth of the parser.
+const MAX_DEPTH: usize = 32;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// A parsed JSON value.
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn as_u8(&self) -> Option<u8> {
+        match *self {
+            Json::Number(value) => Some(value.clamp(0.0, 255.0).round() as u8),
+            _ => None,
+        }
+    }
+
+    fn as_usize(&self) -> Option<usize> {
+        match *self {
+            Json::Number(value) if value >= 0.0 => Some(value as usize),
+            _ => None,
+        }
+    }
+
+    // Returns the new value for an on/off key, where "t" toggles the value.
+    fn as_switch(&self, current: bool) -> Option<bool> {
+        match self {
+            Json::Bool(value) => Some(*value),
+            Json::String(value) if value == "t" => Some(!current),
+            _ => None,
+        }
+    }
+
+    fn as_color(&self) -> Option<(u8, u8, u8)> {
+        match self {
+            Json::Array(values) => Some((
+                values.first()?.as_u8()?,
+                values.get(1)?.as_u8()?,
+                values.get(2)?.as_u8()?,
+            )),
+            Json::String(hex) => color::parse(&format!("#{}", hex)).ok(),
+            _ => None,
+        }
+    }
+}
+
+// A recursive descent parser for JSON text.
+struct Parser<'a> {
+    text: &'a [u8],
+    offset: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(text: &'a str) -> Option<Json> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            offset: 0,
+            depth: 0,
+        };
+
+        let value = parser.value()?;
+        parser.skip_whitespace();
+
+        if parser.offset == parser.text.len() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .text
+            .get(self.offset)
+            .map_or(false, |c| c.is_ascii_whitespace())
+        {
+            self.offset += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.text.get(self.offset).copied()
+    }
+
+    fn expect(&mut self, literal: &str) -> Option<()> {
+        if self.text[self.offset..].starts_with(literal.as_bytes()) {
+            self.offset += literal.len();
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        match self.peek()? {
+            b'{' | b'[' => {
+                if self.depth >= MAX_DEPTH {
+                    return None;
+                }
+
+                self.depth += 1;
+                let value = if self.peek()? == b'{' {
+                    self.object()
+                } else {
+                    self.array()
+                };
+                self.depth -= 1;
+
+                value
+            }
+            b'"' => self.string().map(Json::String),
+            b't' => self.expect("true").map(|_| Json::Bool(true)),
+            b'f' => self.expect("false").map(|_| Json::Bool(false)),
+            b'n' => self.expect("null").map(|_| Json::Null),
+            _ => self.number(),
+        }
+    }
+
+    fn object(&mut self) -> Option<Json> {
+        let mut members = Vec::new();
+        self.offset += 1;
+
+        if self.peek()? == b'}' {
+            self.offset += 1;
+            return Some(Json::Object(members));
+        }
+
+        loop {
+            if self.peek()? != b'"' {
+                return None;
+            }
+
+            let name = self.string()?;
+            if self.peek()? != b':' {
+                return None;
+            }
+
+            self.offset += 1;
+            members.push((name, self.value()?));
+
+            match self.peek()? {
+                b',' => self.offset += 1,
+                b'}' => {
+                    self.offset += 1;
+                    return Some(Json::Object(members));
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn array(&mut self) -> Option<Json> {
+        let mut values = Vec::new();
+        self.offset += 1;
+
+        if self.peek()? == b']' {
+            self.offset += 1;
+            return Some(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+
+            match self.peek()? {
+                b',' => self.offset += 1,
+                b']' => {
+                    self.offset += 1;
+                    return Some(Json::Array(values));
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let mut bytes = Vec::new();
+        self.offset += 1;
+
+        loop {
+            let c = *self.text.get(self.offset)?;
+            self.offset += 1;
+
+            match c {
+                b'"' => return String::from_utf8(bytes).ok(),
+                b'\\' => {
+                    let escaped = match *self.text.get(self.offset)? {
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'u' => {
+                            let hex = self.text.get(self.offset + 1..self.offset + 5)?;
+                            if !hex.iter().all(|c| c.is_ascii_hexdigit()) {
+                                return None;
+                            }
+
+                            let code =
+                                u32::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?;
+                            self.offset += 4;
+
+                            // Surrogate pairs are replaced, since they're never
+                            // used for the supported keys.
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        c @ (b'"' | b'\\' | b'/') => char::from(c),
+                        _ => return None,
+                    };
+
+                    self.offset += 1;
+                    bytes.extend_from_slice(escaped.encode_utf8(&mut [0u8; 4]).as_bytes());
+                }
+                c => bytes.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Option<Json> {
+        let start = self.offset;
+
+        while self.text.get(self.offset).map_or(false, |c| {
+            matches!(c, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        }) {
+            self.offset += 1;
+        }
+
+        std::str::from_utf8(&self.text[start..self.offset])
+            .ok()?
+            .parse()
+            .ok()
+            .map(Json::Number)
+    }
+}
+
+// Escapes a string for use in JSON text.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+// A range of pixels with its own color and brightness.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Segment {
+    start: usize,
+    stop: usize,
+    on: bool,
+    brightness: u8,
+    color: (u8, u8, u8),
+}
+
+impl Segment {
+    fn new(start: usize, stop: usize) -> Self {
+        Self {
+            start,
+            stop,
+            on: true,
+            brightness: 255,
+            color: (255, 160, 0),
+        }
+    }
+}
+
+/// A WLED-compatible HTTP server.
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+    name: String,
+    on: bool,
+    brightness: u8,
+    segments: Vec<Segment>,
+}
+
+impl Server {
+    /// Constructs a new `Server` listening on `address`.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+            name: "blinkt".to_owned(),
+            on: false,
+            brightness: 128,
+            segments: Vec::new(),
+        })
+    }
+
+    /// Returns the local address the server is listening on.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Returns the device name reported to clients.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sets the device name reported to clients.
+    ///
+    /// By default, this is set to `blinkt`.
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_owned();
+    }
+
+    /// Accepts clients and answers their requests until an error occurs while
+    /// sending a frame to the pixels.
+    pub fn run(&mut self, blinkt: &mut Blinkt) -> Result<()> {
+        loop {
+            self.accept(blinkt)?;
+        }
+    }
+
+    /// Waits for a single client to connect, and answers its request. Returns
+    /// `true` if the state was changed, and `show()` was called.
+    ///
+    /// Network errors and invalid requests only disconnect the client. Returns
+    /// an error if no client can be accepted, or a frame can't be sent to the
+    /// pixels.
+    ///
+    /// ```rust
+    /// # use std::error::Error;
+    /// use std::io::{Read, Write};
+    /// use std::net::TcpStream;
+    /// use std::thread;
+    ///
+    /// use blinkt::wled::Server;
+    /// use blinkt::{Blinkt, MockOutput};
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mut blinkt = Blinkt::with_driver(MockOutput::new(), 8);
+    /// let mut server = Server::bind("127.0.0.1:0")?;
+    /// let address = server.local_addr()?;
+    ///
+    /// // Truncated JSON, and arrays nested too deeply.
+    /// for body in &["{\"on\":tru".to_owned(), "[".repeat(1000)] {
+    ///     let mut stream = TcpStream::connect(address)?;
+    ///     write!(
+    ///         stream,
+    ///         "POST /json/state HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+    ///         body.len(),
+    ///         body
+    ///     )?;
+    ///
+    ///     let client = thread::spawn(move || {
+    ///         let mut response = String::new();
+    ///         stream.read_to_string(&mut response).map(|_| response)
+    ///     });
+    ///
+    ///     assert!(!server.accept(&mut blinkt)?);
+    ///     assert!(client.join().unwrap()?.starts_with("HTTP/1.1 400"));
+    /// }
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn accept(&mut self, blinkt: &mut Blinkt) -> Result<bool> {
+        let mut stream = net::accept(&self.listener, READ_TIMEOUT)?;
+
+        if self.segments.is_empty() {
+            self.segments.push(Segment::new(0, blinkt.pixels.len()));
+        }
+
+        let (method, path, body) = match read_request(&stream) {
+            Ok(request) => request,
+            Err(_) => return Ok(false),
+        };
+
+        let path = path.trim_end_matches('/');
+        let (status, response, changed) = match (method.as_str(), path) {
+            ("GET", "/json") => (
+                "200 OK",
+                format!(
+                    "{{\"state\":{},\"info\":{}}}",
+                    self.state(),
+                    self.info(blinkt)
+                ),
+                false,
+            ),
+            ("GET", "/json/state") => ("200 OK", self.state(), false),
+            ("GET", "/json/info") => ("200 OK", self.info(blinkt), false),
+            ("POST", "/json") | ("POST", "/json/state") => match Parser::parse(&body) {
+                Some(state @ Json::Object(_)) => {
+                    self.apply(&state, blinkt.pixels.len());
+
+                    let response = if state.get("v") == Some(&Json::Bool(true)) {
+                        self.state()
+                    } else {
+                        "{\"success\":true}".to_owned()
+                    };
+
+                    ("200 OK", response, true)
+                }
+                _ => ("400 Bad Request", "{\"error\":9}".to_owned(), false),
+            },
+            _ => (
+                "404 Not Found",
+                "{\"error\":\"Not implemented\"}".to_owned(),
+                false,
+            ),
+        };
+
+        if changed {
+            self.render(blinkt);
+            blinkt.show()?;
+        }
+
+        // The client might already have disconnected.
+        let _ = write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            response.len(),
+            response
+        );
+
+        Ok(changed)
+    }
+
+    fn apply(&mut self, state: &Json, num_pixels: usize) {
+        if let Some(on) = state.get("on").and_then(|on| on.as_switch(self.on)) {
+            self.on = on;
+        }
+
+        if let Some(brightness) = state.get("bri").and_then(Json::as_u8) {
+            self.brightness = brightness;
+        }
+
+        let segments = match state.get("seg") {
+            Some(Json::Array(segments)) => segments.iter().collect(),
+            Some(segment @ Json::Object(_)) => vec![segment],
+            _ => Vec::new(),
+        };
+
+        for (index, values) in segments.into_iter().enumerate() {
+            let id = values.get("id").and_then(Json::as_usize).unwrap_or(index);
+            if id > self.segments.len() || id >= MAX_SEGMENTS {
+                continue;
+            }
+
+            if id == self.segments.len() {
+                self.segments.push(Segment::new(0, num_pixels));
+            }
+
+            let segment = &mut self.segments[id];
+
+            if let Some(start) = values.get("start").and_then(Json::as_usize) {
+                segment.start = start;
+            }
+
+            if let Some(stop) = values.get("stop").and_then(Json::as_usize) {
+                segment.stop = stop;
+            }
+
+            if let Some(on) = values.get("on").and_then(|on| on.as_switch(segment.on)) {
+                segment.on = on;
+            }
+
+            if let Some(brightness) = values.get("bri").and_then(Json::as_u8) {
+                segment.brightness = brightness;
+            }
+
+            if let Some(Json::Array(colors)) = values.get("col") {
+                if let Some(color) = colors.first().and_then(Json::as_color) {
+                    segment.color = color;
+                }
+            }
+        }
+    }
+
+    fn render(&self, blinkt: &mut Blinkt) {
+        let num_pixels = blinkt.pixels.len();
+        blinkt.clear();
+
+        if !self.on {
+            return;
+        }
+
+        for segment in self.segments.iter().filter(|segment| segment.on) {
+            let brightness =
+                f32::from(self.brightness) / 255.0 * f32::from(segment.brightness) / 255.0;
+            let (red, green, blue) = segment.color;
+
+            for index in segment.start.min(num_pixels)..segment.stop.min(num_pixels) {
+                blinkt.set_pixel_rgbb(index, red, green, blue, brightness);
+            }
+        }
+    }
+
+    fn state(&self) -> String {
+        let segments: Vec<String> = self
+            .segments
+            .iter()
+            .enumerate()
+            .map(|(id, segment)| {
+                let (red, green, blue) = segment.color;
+
+                format!(
+                    "{{\"id\":{},\"start\":{},\"stop\":{},\"len\":{},\"on\":{},\"bri\":{},\"col\":[[{},{},{}],[0,0,0],[0,0,0]]}}",
+                    id,
+                    segment.start,
+                    segment.stop,
+                    segment.stop.saturating_sub(segment.start),
+                    segment.on,
+                    segment.brightness,
+                    red,
+                    green,
+                    blue
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\"on\":{},\"bri\":{},\"transition\":0,\"ps\":-1,\"seg\":[{}]}}",
+            self.on,
+            self.brightness,
+            segments.join(",")
+        )
+    }
+
+    fn info(&self, blinkt: &Blinkt) -> String {
+        format!(
+            "{{\"ver\":\"{}\",\"name\":\"{}\",\"brand\":\"blinkt\",\"product\":\"blinkt\",\"leds\":{{\"count\":{},\"rgbw\":false,\"wv\":false}},\"maxseg\":{}}}",
+            WLED_VERSION,
+            escape(&self.name),
+            blinkt.pixels.len(),
+            MAX_SEGMENTS
+        )
+    }
+}
+
+// Reads an HTTP request, which has to arrive within READ_TIMEOUT, and returns
+// its method, path and body.
+fn read_request(stream: &TcpStream) -> io::Result<(String, String, String)> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid HTTP request");
+    let stream = net::DeadlineReader::new(stream, READ_TIMEOUT);
+    let mut reader = BufReader::new(stream.take(MAX_HEADER_LEN));
+
+    // Lines that don't end with a newline exceed the maximum header length.
+    let mut read_line = |line: &mut String| -> io::Result<()> {
+        reader.read_line(line)?;
+
+        if line.ends_with('\n') {
+            Ok(())
+        } else {
+            Err(invalid())
+        }
+    };
+
+    let mut line = String::new();
+    read_line(&mut line)?;
+
+    let mut parts = line.split_whitespace();
+    let method = parts.next().ok_or_else(invalid)?.to_owned();
+    let path = parts.next().ok_or_else(invalid)?.to_owned();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        read_line(&mut header)?;
+
+        if header.trim().is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().map_err(|_| invalid())?;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY_LEN {
+        return Err(invalid());
+    }
+
+    reader.get_mut().set_limit(content_length as u64);
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok((
+        method,
+        path,
+        String::from_utf8(body).map_err(|_| invalid())?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sends a request to read_request() through a local connection.
+    fn request(request: &[u8]) -> io::Result<(String, String, String)> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let mut client = TcpStream::connect(listener.local_addr()?)?;
+        client.write_all(request)?;
+
+        let (stream, _) = listener.accept()?;
+
+        read_request(&stream)
+    }
+
+    #[test]
+    fn parse_values() {
+        let json = Parser::parse(
+            r#" {"on": "t", "bri": 128, "seg": [{"col": [[255, 0, 0]]}], "n": null} "#,
+        )
+        .unwrap();
+
+        assert_eq!(json.get("on").unwrap().as_switch(true), Some(false));
+        assert_eq!(json.get("bri").unwrap().as_u8(), Some(128));
+        assert_eq!(json.get("n"), Some(&Json::Null));
+        assert_eq!(Parser::parse("[]"), Some(Json::Array(Vec::new())));
+        assert_eq!(Parser::parse("-1.5e2"), Some(Json::Number(-150.0)));
+    }
+
+    #[test]
+    fn parse_strings() {
+        let string = |text: &str| match Parser::parse(text) {
+            Some(Json::String(value)) => Some(value),
+            _ => None,
+        };
+
+        assert_eq!(string(r#""a\"b\\c\/d\n""#).unwrap(), "a\"b\\c/d\n");
+        assert_eq!(string(r#""Aé""#).unwrap(), "Aé");
+        assert_eq!(string(r#""\ud83d""#).unwrap(), "\u{fffd}");
+
+        assert_eq!(string(r#""\u004""#), None);
+        assert_eq!(string(r#""\u+041""#), None);
+        assert_eq!(string(r#""\u00zz""#), None);
+        assert_eq!(string(r#""\x""#), None);
+        assert_eq!(string(r#""\"#), None);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for text in &[
+            "",
+            "{",
+            r#"{"on""#,
+            r#"{"on":"#,
+            r#"{"on":true"#,
+            r#"{"on":true,}"#,
+            "[1,",
+            "[1 2]",
+            "tru",
+            "nul",
+            "{} {}",
+            "[1]]",
+            "1 x",
+            "{on: true}",
+        ] {
+            assert_eq!(Parser::parse(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_depth_limit() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        assert!(Parser::parse(&nested(MAX_DEPTH)).is_some());
+        assert!(Parser::parse(&nested(MAX_DEPTH + 1)).is_none());
+
+        // Deeply nested input doesn't overflow the stack.
+        assert!(Parser::parse(&"[".repeat(1_000_000)).is_none());
+    }
+
+    #[test]
+    fn read_request_body() {
+        let (method, path, body) =
+            request(b"POST /json/state HTTP/1.1\r\nContent-Length: 11\r\n\r\n{\"on\":true}")
+                .unwrap();
+
+        assert_eq!((method.as_str(), path.as_str()), ("POST", "/json/state"));
+        assert_eq!(body, "{\"on\":true}");
+    }
+
+    #[test]
+    fn read_request_rejects_oversized_content_length() {
+        let header = format!(
+            "POST /json HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_LEN + 1
+        );
+        let err = request(header.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let err = request(b"POST /json HTTP/1.1\r\nContent-Length: -1\r\n\r\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn read_request_rejects_oversized_header() {
+        let header = format!(
+            "GET /json HTTP/1.1\r\nX-Padding: {}\r\n\r\n",
+            "a".repeat(MAX_HEADER_LEN as usize)
+        );
+        let err = request(header.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
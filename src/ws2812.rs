@@ -0,0 +1,299 @@
+// Copyright (c) 2016-2022 Rene van der Meer
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL
+// THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::driver::DriverOutput;
+use crate::{spi, Backend, Blinkt, BlinktSpi, Driver, Protocol, Result, SerialOutput};
+
+// SPI clock speed used to generate the WS2812 timing. Every data bit is encoded
+// as 3 SPI bits of ~417 ns each, resulting in ~417 ns high and ~833 ns low for
+// a 0, and ~833 ns high and ~417 ns low for a 1.
+const WS2812_CLOCK_SPEED: u32 = 2_400_000;
+// Number of bytes held low after every frame to latch the data (~320 µs). Newer
+// revisions of the WS2812B require at least 280 µs.
+const WS2812_RESET_LEN: usize = 96;
+
+// Sends WS2812 or SK6812 RGBW data through hardware SPI, using only the MOSI pin.
+struct BlinktWs2812 {
+    spi: BlinktSpi,
+    protocol: Protocol,
+}
+
+impl SerialOutput for BlinktWs2812 {
+    fn acquire(&mut self) -> Result<()> {
+        self.spi.acquire()
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.spi.write(data)
+    }
+
+    fn release(&mut self) -> Result<()> {
+        self.spi.release()
+    }
+
+    fn clock_speed(&self) -> u32 {
+        self.spi.clock_speed()
+    }
+
+    fn set_clock_speed(&mut self, clock_speed_hz: u32) -> Result<()> {
+        self.spi.set_clock_speed(clock_speed_hz)
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Spi
+    }
+
+    fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+}
+
+impl Blinkt {
+    /// Constructs a new `Blinkt` for WS2812 (NeoPixel) LED strips, using
+    /// hardware SPI with the specified number of pixels.
+    ///
+    /// WS2812 pixels use a single data line, which is connected to the MOSI pin
+    /// of `bus`. For `Bus::Spi0`, that's GPIO 10 (physical pin 19). The SPI clock
+    /// is set to 2.4 MHz, and every data bit is encoded as three SPI bits to
+    /// generate the required pulse widths, so the clock speed shouldn't be
+    /// changed.
+    ///
+    /// WS2812 pixels don't have a separate brightness setting, so the red, green
+    /// and blue values are scaled by the brightness of each pixel before they're
+    /// sent. The color order is set to `ColorOrder::Grb`.
+    ///
+    /// Every transfer is limited to the SPI buffer size, which defaults to 4096
+    /// bytes, or 444 pixels. The buffer size can be increased by adding
+    /// `spidev.bufsiz=<size>` to `/boot/cmdline.txt`.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::{spi::Bus, Blinkt};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mut blinkt = Blinkt::with_ws2812_spi(Bus::Spi0, 60)?;
+    ///
+    /// blinkt.set_all_pixels(255, 0, 0);
+    /// blinkt.show()?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn with_ws2812_spi(bus: spi::Bus, num_pixels: usize) -> Result<Self> {
+        Self::with_single_wire(bus, Protocol::Ws2812, num_pixels)
+    }
+
+    /// Constructs a new `Blinkt` for SK6812 RGBW LED strips, using hardware SPI
+    /// with the specified number of pixels.
+    ///
+    /// The SK6812 RGBW has an additional white LED, which is set through
+    /// `set_pixel_rgbw()` and `set_all_pixels_rgbw()`. It uses the same
+    /// single-wire protocol as the WS2812, and the same limitations apply. See
+    /// [`with_ws2812_spi()`] for details. Every pixel takes 12 bytes, so the
+    /// default SPI buffer size allows up to 333 pixels.
+    ///
+    /// The white value isn't affected by color correction, but is scaled by the
+    /// brightness of each pixel.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::{spi::Bus, Blinkt};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mut blinkt = Blinkt::with_sk6812_rgbw_spi(Bus::Spi0, 60)?;
+    ///
+    /// // Warm white, using both the white LED and the red LED.
+    /// blinkt.set_all_pixels_rgbw(64, 0, 0, 255);
+    /// blinkt.show()?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`with_ws2812_spi()`]: #method.with_ws2812_spi
+    pub fn with_sk6812_rgbw_spi(bus: spi::Bus, num_pixels: usize) -> Result<Self> {
+        Self::with_single_wire(bus, Protocol::Sk6812Rgbw, num_pixels)
+    }
+
+    /// Constructs a new `Blinkt` for WS2812 (NeoPixel) LED strips, which sends
+    /// the encoded frames through a custom [`Driver`], with the specified
+    /// number of pixels.
+    ///
+    /// Frames are encoded the same way as for [`with_ws2812_spi()`], as SPI data
+    /// for a 2.4 MHz clock, and every frame is passed to the driver in a single
+    /// call to `write()`.
+    ///
+    /// ```rust
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::{Blinkt, MockOutput};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let mock = MockOutput::new();
+    /// let mut blinkt = Blinkt::with_ws2812_driver(mock.clone(), 1);
+    ///
+    /// blinkt.set_pixel_rgbb(0, 255, 0, 0, 1.0);
+    /// blinkt.show()?;
+    ///
+    /// let frame = mock.last_frame().unwrap();
+    /// // Green, red and blue, with every bit sent as 100 (0) or 110 (1).
+    /// assert_eq!(frame[..3], [0x92, 0x49, 0x24]);
+    /// assert_eq!(frame[3..6], [0xdb, 0x6d, 0xb6]);
+    /// assert_eq!(frame[6..9], [0x92, 0x49, 0x24]);
+    /// // The data line is held low to latch the data.
+    /// assert_eq!(frame[9..], [0; 96]);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Driver`]: trait.Driver.html
+    /// [`with_ws2812_spi()`]: #method.with_ws2812_spi
+    pub fn with_ws2812_driver<D: Driver + Send + 'static>(driver: D, num_pixels: usize) -> Self {
+        Self::with_output(
+            Box::new(DriverOutput::new(driver, Protocol::Ws2812)),
+            num_pixels,
+        )
+    }
+
+    /// Adds WS2812 (NeoPixel) pixels to the end of the local buffer, which are
+    /// sent through hardware SPI.
+    ///
+    /// See [`with_ws2812_spi()`] for details on the WS2812 output, and
+    /// `chain_gpio()` for details on chaining. The color order of the chained
+    /// output is set to `ColorOrder::Grb`, without affecting any other outputs.
+    ///
+    /// ```rust,no_run
+    /// # use std::error::Error;
+    /// #
+    /// # use blinkt::{spi::Bus, Blinkt};
+    /// #
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// // Pixels 0-7 are located on a Blinkt! board, and pixels 8-67 on a WS2812
+    /// // strip connected to the MOSI pin.
+    /// let mut blinkt = Blinkt::new()?;
+    /// blinkt.chain_ws2812_spi(Bus::Spi0, 60)?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`with_ws2812_spi()`]: #method.with_ws2812_spi
+    pub fn chain_ws2812_spi(&mut self, bus: spi::Bus, num_pixels: usize) -> Result<()> {
+        self.chain_output(single_wire(bus, Protocol::Ws2812)?, num_pixels);
+
+        Ok(())
+    }
+
+    /// Adds SK6812 RGBW pixels to the end of the local buffer, which are sent
+    /// through hardware SPI.
+    ///
+    /// See [`with_sk6812_rgbw_spi()`] for details on the SK6812 RGBW output, and
+    /// `chain_gpio()` for details on chaining.
+    ///
+    /// [`with_sk6812_rgbw_spi()`]: #method.with_sk6812_rgbw_spi
+    pub fn chain_sk6812_rgbw_spi(&mut self, bus: spi::Bus, num_pixels: usize) -> Result<()> {
+        self.chain_output(single_wire(bus, Protocol::Sk6812Rgbw)?, num_pixels);
+
+        Ok(())
+    }
+
+    /// Adds WS2812 (NeoPixel) pixels to the end of the local buffer, which are
+    /// sent through a custom [`Driver`].
+    ///
+    /// See [`with_ws2812_driver()`] for details on the encoded frames, and
+    /// `chain_gpio()` for details on chaining.
+    ///
+    /// [`Driver`]: trait.Driver.html
+    /// [`with_ws2812_driver()`]: #method.with_ws2812_driver
+    pub fn chain_ws2812_driver<D: Driver + Send + 'static>(
+        &mut self,
+        driver: D,
+        num_pixels: usize,
+    ) {
+        self.chain_output(
+            Box::new(DriverOutput::new(driver, Protocol::Ws2812)),
+            num_pixels,
+        );
+    }
+
+    fn with_single_wire(bus: spi::Bus, protocol: Protocol, num_pixels: usize) -> Result<Self> {
+        Ok(Self::with_output(single_wire(bus, protocol)?, num_pixels))
+    }
+
+    // Sends the frame buffer to a WS2812 or SK6812 RGBW output. The entire frame
+    // is sent in a single transfer, because any gap longer than a few µs latches
+    // the data prematurely.
+    pub(crate) fn write_ws2812(&mut self, output: usize) -> Result<()> {
+        let pixels = self.outputs[output].pixels.clone();
+        let rgbw = self.outputs[output].serial_output.protocol() == Protocol::Sk6812Rgbw;
+        let color_order = self.outputs[output].color_order;
+        let mut data = Vec::with_capacity(pixels.len() * 12 + WS2812_RESET_LEN);
+
+        for index in pixels {
+            let wire_pixel = self.preview_at(index, &self.frame[index]);
+            let brightness = u16::from(wire_pixel.brightness());
+            let bytes = wire_pixel.bytes_ordered(color_order);
+
+            for value in &bytes[1..] {
+                encode((u16::from(*value) * brightness / 31) as u8, &mut data);
+            }
+
+            if rgbw {
+                let white = u16::from(wire_pixel.white());
+                encode((white * brightness / 31) as u8, &mut data);
+            }
+
+            self.sent_frame.push(wire_pixel);
+        }
+
+        data.resize(data.len() + WS2812_RESET_LEN, 0);
+
+        let serial_output = &mut self.outputs[output].serial_output;
+        serial_output.acquire()?;
+        let result = serial_output.write(&data);
+        let released = serial_output.release();
+
+        result.and(released)
+    }
+}
+
+fn single_wire(bus: spi::Bus, protocol: Protocol) -> Result<Box<dyn SerialOutput + Send>> {
+    let spi = BlinktSpi::with_settings(
+        bus,
+        spi::SlaveSelect::Ss0,
+        WS2812_CLOCK_SPEED,
+        spi::Mode::Mode0,
+    )?;
+
+    Ok(Box::new(BlinktWs2812 { spi, protocol }))
+}
+
+// Encodes every bit of `value` as 3 SPI bits (0 as 100, 1 as 110).
+fn encode(value: u8, data: &mut Vec<u8>) {
+    let symbols = (0..8).rev().fold(0u32, |symbols, bit| {
+        (symbols << 3)
+            | if value & (1 << bit) != 0 {
+                0b110
+            } else {
+                0b100
+            }
+    });
+
+    data.extend_from_slice(&symbols.to_be_bytes()[1..]);
+}